@@ -0,0 +1,113 @@
+//! Runs one side of a netplay session without a window, with scripted inputs, and prints a
+//! checksum of the world once a given frame is confirmed. Run a host and a joiner side by side:
+//!
+//!     cargo run --example netplay_loopback -- --host 7000 --net-loss 0.2 --net-latency 60
+//!     cargo run --example netplay_loopback -- --join 127.0.0.1:7000 --net-loss 0.2 --net-latency 60
+//!
+//! Both should print the same checksum however many rollbacks they needed to get there.
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use ecsteroids::{
+    cli::{parse_value, NetFlags},
+    modes,
+    netplay::{self, NetInput, NetSession},
+    playing::PlayingInputs,
+    state::{self, State},
+};
+use glam::Vec2;
+
+// keeps answering the peer for a while after finishing so they can confirm the last frames too
+const LINGER: Duration = Duration::from_secs(2);
+
+fn main() {
+    let mut net = NetFlags::default();
+    let mut frames: u32 = 1200;
+    let mut seed = None;
+
+    // the game's netplay flags and --seed, and how many frames to play
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(arg) = raw_args.next() {
+        let parsed = match arg.as_str() {
+            "--frames" => parse_value(&arg, raw_args.next()).map(|value| frames = value),
+            "--seed" => parse_value(&arg, raw_args.next()).map(|value| seed = Some(value)),
+            _ => match net.parse(&arg, &mut raw_args) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(format!("Unknown argument: {}", arg)),
+                Err(e) => Err(e),
+            },
+        };
+        if let Err(e) = parsed {
+            exit_with(&e);
+        }
+    }
+    if frames == 0 {
+        exit_with("--frames has to be at least 1");
+    }
+
+    let mut state = State::new();
+    state.seed = seed;
+    let session = match (net.host, net.join) {
+        (Some(port), _) => NetSession::host(&state, port, net.conditions),
+        (None, Some(address)) => NetSession::join(address, net.conditions),
+        (None, None) => exit_with("Pass --host PORT or --join ADDRESS"),
+    }
+    .expect("couldn't open a socket");
+    let player = if net.host.is_some() { 0 } else { 1 };
+    netplay::start(&mut state, session);
+
+    let timestep = Duration::from_secs_f32(1.0 / state::FRAMES_PER_SECOND as f32);
+    let mut step: u32 = 0;
+    let mut finished: Option<Instant> = None;
+    while finished.is_none_or(|finished| finished.elapsed() < LINGER) {
+        modes::apply_transition(&mut state);
+        if modes::current(&state) != Some(state::GameMode::Netplay) {
+            println!("Session ended");
+            std::process::exit(1);
+        }
+
+        state.resources.insert(scripted_input(player, step));
+        modes::step(&mut state);
+        step += 1;
+
+        if finished.is_none() {
+            if let Some(session) = state.resources.get::<NetSession>() {
+                if let Some(checksum) = session.checksum(frames - 1) {
+                    println!(
+                        "Frame {} checksum {:016x} after {} rollbacks",
+                        frames - 1,
+                        checksum,
+                        session.rollbacks()
+                    );
+                    finished = Some(Instant::now());
+                }
+            }
+        }
+        thread::sleep(timestep);
+    }
+}
+
+// busy enough that the peers' predictions are often wrong
+fn scripted_input(player: usize, step: u32) -> NetInput {
+    let t = step as f32 / 30.0 + player as f32;
+    NetInput {
+        inputs: PlayingInputs {
+            turn: t.sin(),
+            thrust: if (step / 90 + player as u32) % 3 == 0 {
+                0.0
+            } else {
+                1.0
+            },
+            shoot: step % 20 < 3,
+        },
+        mouse_pos: Vec2::new(120.0 + 60.0 * t.cos(), 80.0 + 40.0 * t.sin()),
+    }
+}
+
+fn exit_with(message: &str) -> ! {
+    println!("{}", message);
+    std::process::exit(1);
+}
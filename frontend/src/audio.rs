@@ -1,3 +1,4 @@
+use ecsteroids::settings::AudioSettings;
 use raylib::prelude::*;
 use raylib::{prelude::RaylibAudio, RaylibHandle, RaylibThread};
 
@@ -50,13 +51,15 @@ impl Audio {
             sounds.push(sound);
         }
 
-        Self {
+        let mut audio = Self {
             rl_audio_device,
             songs,
             sounds,
             music_volume: 1.0,
             sound_effects_volume: 1.0,
-        }
+        };
+        audio.set_volumes();
+        audio
     }
 
     // pub fn play_sound_effect(&mut self, sound_effect: SoundEffect) {
@@ -64,13 +67,35 @@ impl Audio {
     //     self.rl_audio_device.play_sound(sound_effect);
     // }
 
-    // pub fn set_sound_volumes(&mut self) {
-    //     for sound_effect in SoundEffect::iter() {
-    //         let sound_effect = &mut self.sounds[sound_effect as usize];
-    //         self.rl_audio_device
-    //             .set_sound_volume(sound_effect, self.sound_effects_volume);
-    //     }
-    // }
+    pub fn pause_music(&mut self) {
+        for song in self.songs.iter_mut() {
+            self.rl_audio_device.pause_music_stream(song);
+        }
+    }
+
+    pub fn resume_music(&mut self) {
+        for song in self.songs.iter_mut() {
+            self.rl_audio_device.resume_music_stream(song);
+        }
+    }
+
+    pub fn apply_settings(&mut self, settings: &AudioSettings) {
+        self.music_volume = settings.music_volume;
+        self.sound_effects_volume = settings.sound_effects_volume;
+        self.set_volumes();
+    }
+
+    pub fn set_volumes(&mut self) {
+        for song in self.songs.iter_mut() {
+            self.rl_audio_device
+                .set_music_volume(song, self.music_volume);
+        }
+        for sound_effect in SoundEffect::iter() {
+            let sound_effect = &self.sounds[sound_effect as usize];
+            self.rl_audio_device
+                .set_sound_volume(sound_effect, self.sound_effects_volume);
+        }
+    }
 }
 
 pub fn get_sound_file_name(sound_effect: SoundEffect) -> &'static str {
@@ -0,0 +1,46 @@
+use std::collections::HashSet;
+
+use ecsteroids::{
+    audio_playing::{AudioCommand, AudioCommandBuffer},
+    rng::AudioRng,
+};
+use rand::Rng;
+use raylib::prelude::*;
+
+use crate::audio::{Audio, SoundEffect};
+
+pub fn execute_audio_command_buffer(
+    _rl: &mut RaylibHandle,
+    audio: &mut Audio,
+    audio_command_buffer: &mut AudioCommandBuffer,
+    rng: &mut AudioRng,
+) {
+    let unique_commands: HashSet<_> = audio_command_buffer.iter().cloned().collect();
+    for command in unique_commands.iter() {
+        match command {
+            AudioCommand::Shoot => {
+                audio
+                    .rl_audio_device
+                    .play_sound(&audio.sounds[SoundEffect::SmallLaser as usize]);
+            }
+            AudioCommand::AsteroidExplosion => {
+                let explosion_variants = [
+                    SoundEffect::ExplosionOne as usize,
+                    SoundEffect::ExplosionTwo as usize,
+                    SoundEffect::ExplosionThree as usize,
+                ];
+                let random_explosion = explosion_variants[rng.0.gen_range(0..3)];
+                audio
+                    .rl_audio_device
+                    .play_sound(&audio.sounds[random_explosion]);
+            }
+            AudioCommand::PauseMusic => {
+                audio.pause_music();
+            }
+            AudioCommand::ResumeMusic => {
+                audio.resume_music();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use ecsteroids::cli::{parse_value, NetFlags};
+
+pub struct Args {
+    pub seed: Option<u64>,
+    // where to save the inputs of each run when it ends
+    pub record: Option<PathBuf>,
+    // start by watching this replay instead of at the title
+    pub replay: Option<PathBuf>,
+    // start playing from a saved snapshot instead of at the title
+    pub snapshot: Option<PathBuf>,
+    // --host, --join and the simulated link conditions, shared with the netplay example
+    pub net: NetFlags,
+}
+
+impl Args {
+    pub fn parse() -> Self {
+        let mut args = Args {
+            seed: None,
+            record: None,
+            replay: None,
+            snapshot: None,
+            net: NetFlags::default(),
+        };
+
+        let mut raw_args = std::env::args().skip(1);
+        while let Some(arg) = raw_args.next() {
+            let parsed = match arg.as_str() {
+                "--seed" => parse_value(&arg, raw_args.next()).map(|seed| args.seed = Some(seed)),
+                "--record" => {
+                    parse_value(&arg, raw_args.next()).map(|path| args.record = Some(path))
+                }
+                "--replay" => {
+                    parse_value(&arg, raw_args.next()).map(|path| args.replay = Some(path))
+                }
+                "--snapshot" => {
+                    parse_value(&arg, raw_args.next()).map(|path| args.snapshot = Some(path))
+                }
+                _ => match args.net.parse(&arg, &mut raw_args) {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Err(format!("Unknown argument: {}", arg)),
+                    Err(e) => Err(e),
+                },
+            };
+            if let Err(e) = parsed {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+        }
+
+        args
+    }
+}
@@ -0,0 +1,132 @@
+use ecsteroids::{
+    actions::{Action, Binding},
+    controls_menu::{self, controls_menu_items, ControlsInputs, ControlsMenu, ControlsMenuItem},
+    settings::Settings,
+    state::{GameMode, State},
+    DIMS,
+};
+use glam::Vec2;
+use raylib::prelude::*;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input,
+    rendering::LowResDrawHandle,
+};
+
+// the list is longer than the screen, so only a window around the selection is drawn
+const VISIBLE_ROWS: usize = 10;
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::Controls,
+        FrontendMode {
+            process_events_and_input,
+            draw,
+            overlay: false,
+        },
+    );
+}
+
+pub fn process_events_and_input(rl: &mut RaylibHandle, state: &mut State) {
+    let capturing = state
+        .resources
+        .get::<ControlsMenu>()
+        .map(|menu| menu.capturing)
+        .unwrap_or(false);
+    let actions = input::action_state(state);
+    let back = actions.active(Action::Back);
+
+    let inputs = if capturing {
+        ControlsInputs {
+            back,
+            captured: input::captured_binding(rl, state).filter(|_| !back),
+            ..Default::default()
+        }
+    } else {
+        input::disarm_capture(state);
+        ControlsInputs {
+            up: actions.active(Action::MenuUp),
+            down: actions.active(Action::MenuDown),
+            confirm: actions.active(Action::Confirm),
+            back,
+            // fixed so bindings can always be removed, whatever they are
+            clear: rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_BACKSPACE)
+                || rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_DELETE),
+            captured: None,
+        }
+    };
+    controls_menu::handle_inputs(state, inputs);
+}
+
+pub fn draw(state: &State, d: &mut LowResDrawHandle) {
+    let mut cursor = Vec2::new(DIMS.x as f32 * 0.05, 4.0);
+    let size = 10;
+    d.draw_text(
+        "CONTROLS",
+        cursor.x as i32,
+        cursor.y as i32,
+        size,
+        Color::WHITE,
+    );
+    cursor.y += size as f32 * 1.5;
+
+    let (selected, capturing) = state
+        .resources
+        .get::<ControlsMenu>()
+        .map(|menu| (menu.selected, menu.capturing))
+        .unwrap_or((0, false));
+    let settings = match state.resources.get::<Settings>() {
+        Some(settings) => settings.clone(),
+        None => return,
+    };
+
+    let items = controls_menu_items();
+    let first = selected
+        .saturating_sub(VISIBLE_ROWS / 2)
+        .min(items.len().saturating_sub(VISIBLE_ROWS));
+    let value_x = DIMS.x as f32 * 0.42;
+    for (i, item) in items.iter().enumerate().skip(first).take(VISIBLE_ROWS) {
+        let color = if i == selected {
+            Color::GOLD
+        } else {
+            Color::WHITE
+        };
+        let marker = if i == selected { "> " } else { "  " };
+        let text = format!("{}{}", marker, item.label());
+        d.draw_text(&text, cursor.x as i32, cursor.y as i32, size, color);
+
+        if let ControlsMenuItem::Rebind(action) = item {
+            let value = if capturing && i == selected {
+                "press any input".to_string()
+            } else {
+                let names: Vec<String> = settings
+                    .controls
+                    .bindings
+                    .get(action)
+                    .map(|bindings| bindings.iter().map(Binding::name).collect())
+                    .unwrap_or_default();
+                if names.is_empty() {
+                    "-".to_string()
+                } else {
+                    names.join(", ")
+                }
+            };
+            d.draw_text(&value, value_x as i32, cursor.y as i32, size, color);
+        }
+        cursor.y += size as f32 * 1.2;
+    }
+
+    let hint = if capturing {
+        "back cancels"
+    } else {
+        "confirm adds a key, backspace clears"
+    };
+    d.draw_text(
+        hint,
+        cursor.x as i32,
+        DIMS.y as i32 - size - 4,
+        size,
+        Color::GRAY,
+    );
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use ecsteroids::{
+    modes,
+    state::{GameMode, State},
+};
+use raylib::prelude::*;
+
+use crate::rendering::LowResDrawHandle;
+
+pub type FrontendModeRegistry = HashMap<GameMode, FrontendMode>;
+
+// the raylib half of a mode, the simulation half is registered in ecsteroids::modes
+#[derive(Clone, Copy)]
+pub struct FrontendMode {
+    pub process_events_and_input: fn(&mut RaylibHandle, &mut State),
+    pub draw: fn(&State, &mut LowResDrawHandle),
+    // draw the modes underneath first, for menus shown over the game
+    pub overlay: bool,
+}
+
+pub fn process_events_and_input(
+    frontend_modes: &FrontendModeRegistry,
+    rl: &mut RaylibHandle,
+    state: &mut State,
+) {
+    if let Some(frontend_mode) = modes::current(state).and_then(|top| frontend_modes.get(&top)) {
+        (frontend_mode.process_events_and_input)(rl, state);
+    }
+}
+
+pub fn draw(frontend_modes: &FrontendModeRegistry, state: &State, d: &mut LowResDrawHandle) {
+    // find the lowest mode that is still visible through the overlays above it
+    let mut bottom = state.mode_stack.len();
+    for (i, game_mode) in state.mode_stack.iter().enumerate().rev() {
+        bottom = i;
+        match frontend_modes.get(game_mode) {
+            Some(frontend_mode) if frontend_mode.overlay => {}
+            _ => break,
+        }
+    }
+
+    for game_mode in state.mode_stack[bottom..].iter() {
+        if let Some(frontend_mode) = frontend_modes.get(game_mode) {
+            (frontend_mode.draw)(state, d);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use ecsteroids::{
+    actions::Action,
+    game_over,
+    state::{GameMode, State},
+};
+use raylib::RaylibHandle;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input, playing,
+};
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::GameOver,
+        FrontendMode {
+            process_events_and_input,
+            draw: playing::draw,
+            overlay: false,
+        },
+    );
+}
+
+pub fn process_events_and_input(_rl: &mut RaylibHandle, state: &mut State) {
+    let actions = input::action_state(state);
+    if actions.active(Action::Back) {
+        state.running = false;
+    }
+
+    if actions.active(Action::Confirm) {
+        game_over::finish(state);
+    }
+}
@@ -0,0 +1,84 @@
+use ecsteroids::{
+    actions::Action,
+    high_scores::{self, HighScores, HighScoresInputs, HighScoresView},
+    state::{GameMode, State},
+    DIMS,
+};
+use glam::Vec2;
+use raylib::prelude::*;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input,
+    rendering::LowResDrawHandle,
+};
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::HighScores,
+        FrontendMode {
+            process_events_and_input,
+            draw,
+            overlay: false,
+        },
+    );
+}
+
+pub fn process_events_and_input(_rl: &mut RaylibHandle, state: &mut State) {
+    let actions = input::action_state(state);
+    let inputs = HighScoresInputs {
+        confirm: actions.active(Action::Confirm),
+        back: actions.active(Action::Back),
+    };
+    high_scores::handle_inputs(state, inputs);
+}
+
+pub fn draw(state: &State, d: &mut LowResDrawHandle) {
+    let mut cursor = Vec2::new(DIMS.x as f32 * 0.1, 4.0);
+    let size = 20;
+    d.draw_text(
+        "HIGH SCORES",
+        cursor.x as i32,
+        cursor.y as i32,
+        size,
+        Color::WHITE,
+    );
+    cursor.y += size as f32 * 1.5;
+
+    let highlighted = state
+        .resources
+        .get::<HighScoresView>()
+        .and_then(|view| view.highlighted);
+    let high_scores = match state.resources.get::<HighScores>() {
+        Some(high_scores) => high_scores.0.clone(),
+        None => Vec::new(),
+    };
+
+    let size = 10;
+    if high_scores.is_empty() {
+        d.draw_text(
+            "no scores yet",
+            cursor.x as i32,
+            cursor.y as i32,
+            size,
+            Color::GRAY,
+        );
+    }
+    for (i, high_score) in high_scores.iter().enumerate() {
+        let color = if Some(i) == highlighted {
+            Color::GOLD
+        } else {
+            Color::WHITE
+        };
+        let text = format!(
+            "{:>2}. {}  {:>6}  {}  {:>5}",
+            i + 1,
+            high_score.name,
+            high_score.score,
+            high_score.date_label(),
+            high_score.duration_label()
+        );
+        d.draw_text(&text, cursor.x as i32, cursor.y as i32, size, color);
+        cursor.y += size as f32 * 1.2;
+    }
+}
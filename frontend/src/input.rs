@@ -0,0 +1,231 @@
+use ecsteroids::{
+    actions::{self, Action, ActionState, Binding, InputDevice, ACTIONS},
+    playing::{PlayerInputs, PlayingInputs},
+    settings::Settings,
+    state::{RunConfig, State},
+};
+use raylib::core::input::key_from_i32;
+use raylib::ffi;
+use raylib::prelude::*;
+
+// raylib reads at most this many gamepads
+const MAX_GAMEPADS: i32 = 4;
+// the stick axes, triggers rest at -1 so they would always look pushed to the rebinding screen
+const CAPTURED_AXES: u32 = 4;
+// left, right and middle
+const CAPTURED_MOUSE_BUTTONS: u32 = 3;
+
+// resource, which gamepads are plugged in, polled every frame so they can come and go during a run
+#[derive(Default)]
+pub struct Gamepads {
+    connected: Vec<i32>,
+    // raylib reports held buttons rather than new presses, so rebinding waits for a release first
+    capture_armed: bool,
+}
+
+impl Gamepads {
+    pub fn poll(&mut self, rl: &RaylibHandle) {
+        for gamepad in 0..MAX_GAMEPADS {
+            let available = rl.is_gamepad_available(gamepad);
+            let known = self.connected.contains(&gamepad);
+            if available && !known {
+                let name = rl.get_gamepad_name(gamepad).unwrap_or_default();
+                println!("Gamepad {} connected: {}", gamepad, name);
+                self.connected.push(gamepad);
+            } else if !available && known {
+                println!("Gamepad {} disconnected", gamepad);
+                self.connected.retain(|connected| *connected != gamepad);
+            }
+        }
+    }
+}
+
+/** turns this frame's raw input into the ActionState resource, through the bindings in the settings */
+pub fn update_action_state(rl: &RaylibHandle, state: &mut State) {
+    let mut gamepads = state.resources.remove::<Gamepads>().unwrap_or_default();
+    gamepads.poll(rl);
+
+    // menus and pausing answer to everyone
+    let mut action_state = action_state(state);
+    if let Some(settings) = state.resources.get::<Settings>() {
+        for action in ACTIONS.iter() {
+            let amount = action_amount(rl, &gamepads, &settings, *action, InputDevice::Any);
+            action_state.update(*action, amount);
+        }
+    }
+    state.resources.insert(action_state);
+    state.resources.insert(gamepads);
+}
+
+/** each player's inputs for this step, read from their own device */
+pub fn player_inputs(rl: &RaylibHandle, state: &State) -> PlayerInputs {
+    let mut player_inputs = PlayerInputs::default();
+    let (settings, gamepads) = match (
+        state.resources.get::<Settings>(),
+        state.resources.get::<Gamepads>(),
+    ) {
+        (Some(settings), Some(gamepads)) => (settings, gamepads),
+        _ => return player_inputs,
+    };
+    let players = state
+        .resources
+        .get::<RunConfig>()
+        .map(|run_config| run_config.players)
+        .unwrap_or(1);
+
+    for (player, inputs) in player_inputs.0.iter_mut().enumerate().take(players) {
+        let device = if players == 1 {
+            InputDevice::Any
+        } else {
+            settings.gameplay.player_devices[player]
+        };
+        *inputs = device_inputs(rl, &gamepads, &settings, device);
+    }
+    player_inputs
+}
+
+/** one player's inputs from every device, for netplay where each peer has a single player */
+pub fn local_inputs(rl: &RaylibHandle, state: &State) -> PlayingInputs {
+    match (
+        state.resources.get::<Settings>(),
+        state.resources.get::<Gamepads>(),
+    ) {
+        (Some(settings), Some(gamepads)) => {
+            device_inputs(rl, &gamepads, &settings, InputDevice::Any)
+        }
+        _ => PlayingInputs::default(),
+    }
+}
+
+fn device_inputs(
+    rl: &RaylibHandle,
+    gamepads: &Gamepads,
+    settings: &Settings,
+    device: InputDevice,
+) -> PlayingInputs {
+    let amount = |action| action_amount(rl, gamepads, settings, action, device);
+    PlayingInputs {
+        turn: amount(Action::RotateRight) - amount(Action::RotateLeft),
+        thrust: amount(Action::Thrust) - amount(Action::Reverse),
+        shoot: amount(Action::Fire) > 0.0,
+    }
+}
+
+// the furthest any of the action's bindings on the device is pushed
+fn action_amount(
+    rl: &RaylibHandle,
+    gamepads: &Gamepads,
+    settings: &Settings,
+    action: Action,
+    device: InputDevice,
+) -> f32 {
+    settings
+        .controls
+        .bindings
+        .get(&action)
+        .into_iter()
+        .flatten()
+        .filter(|binding| device.reads(binding))
+        .map(|binding| binding_amount(rl, gamepads, device, binding, settings.controls.deadzone))
+        .fold(0.0, f32::max)
+}
+
+/** the first binding pressed this frame, for rebinding */
+pub fn captured_binding(rl: &mut RaylibHandle, state: &State) -> Option<Binding> {
+    if let Some(key) = rl.get_key_pressed() {
+        return Some(Binding::Key(key as u32));
+    }
+    for button in 0..CAPTURED_MOUSE_BUTTONS {
+        if unsafe { ffi::IsMouseButtonPressed(button as i32) } {
+            return Some(Binding::MouseButton(button));
+        }
+    }
+
+    let mut gamepads = state.resources.get_mut::<Gamepads>()?;
+    let held = held_gamepad_binding(rl, &gamepads);
+    match held {
+        Some(_) if gamepads.capture_armed => {
+            gamepads.capture_armed = false;
+            held
+        }
+        Some(_) => None,
+        None => {
+            gamepads.capture_armed = true;
+            None
+        }
+    }
+}
+
+/** called while not rebinding, so a button held when rebinding starts is not captured */
+pub fn disarm_capture(state: &State) {
+    if let Some(mut gamepads) = state.resources.get_mut::<Gamepads>() {
+        gamepads.capture_armed = false;
+    }
+}
+
+fn held_gamepad_binding(rl: &RaylibHandle, gamepads: &Gamepads) -> Option<Binding> {
+    if let Some(button) = rl.get_gamepad_button_pressed() {
+        return Some(Binding::GamepadButton(button as u32));
+    }
+    for gamepad in gamepads.connected.iter() {
+        for axis in 0..CAPTURED_AXES {
+            let raw = unsafe { ffi::GetGamepadAxisMovement(*gamepad, axis as i32) };
+            if raw.abs() > 0.5 {
+                return Some(Binding::GamepadAxis {
+                    axis,
+                    positive: raw > 0.0,
+                });
+            }
+        }
+    }
+    None
+}
+
+pub fn action_state(state: &State) -> ActionState {
+    state
+        .resources
+        .get::<ActionState>()
+        .map(|action_state| action_state.clone())
+        .unwrap_or_default()
+}
+
+// how far the binding is pushed, 0 to 1, keys and buttons are all or nothing
+fn binding_amount(
+    rl: &RaylibHandle,
+    gamepads: &Gamepads,
+    device: InputDevice,
+    binding: &Binding,
+    deadzone: f32,
+) -> f32 {
+    // Any reads every gamepad, a player with a gamepad only reads theirs
+    let pads = gamepads
+        .connected
+        .iter()
+        .copied()
+        .filter(|gamepad| match device {
+            InputDevice::Gamepad(own) => *gamepad == own as i32,
+            _ => true,
+        });
+    match *binding {
+        Binding::Key(code) => {
+            let down = key_from_i32(code as i32).is_some_and(|key| rl.is_key_down(key));
+            down as u8 as f32
+        }
+        Binding::MouseButton(button) => {
+            let down = unsafe { ffi::IsMouseButtonDown(button as i32) };
+            down as u8 as f32
+        }
+        Binding::GamepadButton(button) => {
+            let mut pads = pads;
+            let down =
+                pads.any(|gamepad| unsafe { ffi::IsGamepadButtonDown(gamepad, button as i32) });
+            down as u8 as f32
+        }
+        Binding::GamepadAxis { axis, positive } => pads
+            .map(|gamepad| {
+                let raw = unsafe { ffi::GetGamepadAxisMovement(gamepad, axis as i32) };
+                actions::axis_amount(raw, positive, deadzone)
+            })
+            .fold(0.0, f32::max),
+    }
+}
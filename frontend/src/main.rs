@@ -0,0 +1,210 @@
+use audio::Song;
+use audio_playing::execute_audio_command_buffer;
+use ecsteroids::{
+    audio_playing::AudioCommandBuffer,
+    high_scores::{HighScores, HighScoresPath},
+    modes,
+    replay::ReplayRecordPath,
+    rng::AudioRng,
+    settings::{self, Settings, SettingsPath},
+    snapshot::{self, QuickSavePath, Snapshot},
+    state,
+};
+use frontend_modes::FrontendModeRegistry;
+use raylib::prelude::*;
+use raylib::{ffi::SetTraceLogLevel, prelude::TraceLogLevel};
+use window_helpers::{
+    apply_video_settings, load_render_texture, scale_and_blit_render_texture_to_window, window_dims,
+};
+
+mod audio;
+mod audio_playing;
+mod cli;
+mod controls_menu;
+mod frontend_modes;
+mod game_over;
+mod high_scores;
+mod input;
+mod name_entry;
+mod netplay;
+mod pause;
+mod playing;
+mod rendering;
+mod replay;
+mod settings_menu;
+mod title;
+mod window_helpers;
+
+const TIMESTEP: f32 = 1.0 / state::FRAMES_PER_SECOND as f32;
+// past this many steps in one frame the game gives up catching up instead of spiralling
+const MAX_STEPS_PER_FRAME: u32 = 5;
+fn main() {
+    let args = cli::Args::parse();
+
+    let (mut rl, rlt) = raylib::init().title("raylib-rs-lowres-template").build();
+    unsafe {
+        SetTraceLogLevel(TraceLogLevel::LOG_WARNING as i32);
+    }
+    // escape is bound to back and pause, quitting goes through the menus instead
+    rl.set_exit_key(None);
+
+    ////////////////    INIT SETTINGS    ////////////////
+    let settings_path = settings::default_path();
+    let settings = match &settings_path {
+        Some(path) => settings::load_or_default(path),
+        None => Settings::default(),
+    };
+    // what the window and audio currently reflect, compared against every frame
+    let mut applied_settings = settings.clone();
+
+    ////////////////    INIT GRAPHICS    ////////////////
+    apply_video_settings(&mut rl, &settings.video);
+    let mut render_texture = load_render_texture(&mut rl, &rlt, &settings.video);
+
+    ////////////////    INIT AUDIO    ////////////////
+    let mut audio = audio::Audio::new(&mut rl, &rlt);
+    audio.apply_settings(&settings.audio);
+    audio
+        .rl_audio_device
+        .play_music_stream(&mut audio.songs[Song::Playing as usize]);
+
+    ////////////////    INIT MODES    ////////////////
+    let mut frontend_mode_registry = FrontendModeRegistry::new();
+    title::register(&mut frontend_mode_registry);
+    playing::register(&mut frontend_mode_registry);
+    game_over::register(&mut frontend_mode_registry);
+    replay::register(&mut frontend_mode_registry);
+    pause::register(&mut frontend_mode_registry);
+    settings_menu::register(&mut frontend_mode_registry);
+    controls_menu::register(&mut frontend_mode_registry);
+    netplay::register(&mut frontend_mode_registry);
+    high_scores::register(&mut frontend_mode_registry);
+    name_entry::register(&mut frontend_mode_registry);
+
+    ////////////////    INIT STATE    ////////////////
+    let mut state = state::State::new();
+    state.seed = args.seed.or(settings.gameplay.seed);
+    state.resources.insert(settings);
+    if let Some(settings_path) = settings_path {
+        state.resources.insert(SettingsPath(settings_path));
+    }
+    if let Some(record_path) = &args.record {
+        state
+            .resources
+            .insert(ReplayRecordPath(record_path.clone()));
+    }
+    state
+        .resources
+        .insert(QuickSavePath(snapshot::default_quick_save_path()));
+    match ecsteroids::high_scores::default_path() {
+        Some(path) => {
+            state
+                .resources
+                .insert(ecsteroids::high_scores::load_or_default(&path));
+            state.resources.insert(HighScoresPath(path));
+        }
+        None => state.resources.insert(HighScores::default()),
+    }
+    if let Some(snapshot_path) = &args.snapshot {
+        let loaded = Snapshot::load(snapshot_path).unwrap_or_else(|e| {
+            println!("Error loading snapshot {}: {}", snapshot_path.display(), e);
+            std::process::exit(1);
+        });
+        snapshot::start_from_snapshot(&mut state, loaded);
+    }
+    if let Some(replay_path) = &args.replay {
+        replay::start_replay(&mut state, replay_path);
+    }
+    if let Some(port) = args.net.host {
+        netplay::host(&mut state, port, args.net.conditions);
+    } else if let Some(host) = args.net.join {
+        netplay::join(&mut state, host, args.net.conditions);
+    }
+
+    ////////////////    MAIN LOOP    ////////////////
+    while state.running && !rl.window_should_close() {
+        modes::apply_transition(&mut state);
+        input::update_action_state(&rl, &mut state);
+        frontend_modes::process_events_and_input(&frontend_mode_registry, &mut rl, &mut state);
+
+        if let Some(settings) = state
+            .resources
+            .get::<Settings>()
+            .filter(|settings| **settings != applied_settings)
+        {
+            if settings.video != applied_settings.video {
+                apply_video_settings(&mut rl, &settings.video);
+                if settings.video.render_scale != applied_settings.video.render_scale {
+                    render_texture = load_render_texture(&mut rl, &rlt, &settings.video);
+                }
+            }
+            if settings.audio != applied_settings.audio {
+                audio.apply_settings(&settings.audio);
+            }
+            applied_settings = settings.clone();
+        }
+
+        let dt = rl.get_frame_time();
+        state.time_since_last_update += dt;
+        let mut steps = 0;
+        while state.time_since_last_update >= TIMESTEP {
+            if steps == MAX_STEPS_PER_FRAME {
+                state.time_since_last_update = 0.0;
+                break;
+            }
+            // the next mode has to be entered before stepping any further
+            if modes::is_transition_pending(&state) {
+                break;
+            }
+            state.time_since_last_update -= TIMESTEP;
+            steps += 1;
+
+            modes::step(&mut state);
+        }
+
+        let alpha = (state.time_since_last_update / TIMESTEP).min(1.0);
+        modes::render(&mut state, alpha);
+
+        ////////////////    AUDIO STEP  ////////////////
+        if let (Some(mut audio_command_buffer), Some(mut audio_rng)) = (
+            state.resources.get_mut::<AudioCommandBuffer>(),
+            state.resources.get_mut::<AudioRng>(),
+        ) {
+            execute_audio_command_buffer(
+                &mut rl,
+                &mut audio,
+                &mut audio_command_buffer,
+                &mut audio_rng,
+            );
+            audio_command_buffer.clear();
+        }
+
+        audio // UNMUTE THIS TO HEAR THE MUSIC
+            .rl_audio_device
+            .update_music_stream(&mut audio.songs[Song::Playing as usize]);
+
+        ////////////////    DRAWING  ////////////////
+        let mut draw_handle = rl.begin_drawing(&rlt);
+        {
+            let texture_draw_handle =
+                &mut draw_handle.begin_texture_mode(&rlt, &mut render_texture);
+            texture_draw_handle.clear_background(Color::BLACK);
+
+            let camera = Camera2D {
+                offset: Vector2::zero(),
+                target: Vector2::zero(),
+                rotation: 0.0,
+                zoom: applied_settings.video.render_scale as f32,
+            };
+            let low_res_draw_handle = &mut texture_draw_handle.begin_mode2D(camera);
+            frontend_modes::draw(&frontend_mode_registry, &state, low_res_draw_handle);
+        }
+        scale_and_blit_render_texture_to_window(
+            &mut draw_handle,
+            &mut render_texture,
+            window_dims(&applied_settings.video),
+        );
+    }
+
+    modes::exit_all(&mut state);
+}
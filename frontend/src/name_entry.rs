@@ -0,0 +1,109 @@
+use ecsteroids::{
+    actions::Action,
+    high_scores::INITIALS_LEN,
+    name_entry::{self, NameEntry, NameEntryInputs},
+    state::{GameMode, State},
+    DIMS,
+};
+use glam::Vec2;
+use raylib::prelude::*;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input,
+    rendering::LowResDrawHandle,
+};
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::NameEntry,
+        FrontendMode {
+            process_events_and_input,
+            draw,
+            overlay: true,
+        },
+    );
+}
+
+pub fn process_events_and_input(rl: &mut RaylibHandle, state: &mut State) {
+    // letters typed on a keyboard go straight in, keys bound to menu actions would fight over them
+    let typed = rl
+        .get_key_pressed()
+        .map(|key| key as u32)
+        .filter(|code| (b'A' as u32..=b'Z' as u32).contains(code))
+        .map(|code| code as u8 as char);
+    let inputs = match typed {
+        Some(_) => NameEntryInputs {
+            typed,
+            ..Default::default()
+        },
+        None => {
+            let actions = input::action_state(state);
+            NameEntryInputs {
+                up: actions.active(Action::MenuUp),
+                down: actions.active(Action::MenuDown),
+                left: actions.active(Action::MenuLeft),
+                right: actions.active(Action::MenuRight),
+                confirm: actions.active(Action::Confirm),
+                back: actions.active(Action::Back),
+                typed: None,
+            }
+        }
+    };
+    name_entry::handle_inputs(state, inputs);
+}
+
+pub fn draw(state: &State, d: &mut LowResDrawHandle) {
+    // dim the finished run underneath
+    d.draw_rectangle(0, 0, DIMS.x as i32, DIMS.y as i32, Color::new(0, 0, 0, 150));
+
+    let name_entry = match state.resources.get::<NameEntry>() {
+        Some(name_entry) => name_entry,
+        None => return,
+    };
+    let pending = match name_entry.pending.first() {
+        Some(pending) => *pending,
+        None => return,
+    };
+
+    let mut cursor = Vec2::new(DIMS.x as f32 * 0.2, DIMS.y as f32 * 0.25);
+    let size = 20;
+    d.draw_text(
+        "NEW HIGH SCORE",
+        cursor.x as i32,
+        cursor.y as i32,
+        size,
+        Color::GOLD,
+    );
+    cursor.y += size as f32 * 1.5;
+
+    let size = 10;
+    let text = format!("player {}: {}", pending.player + 1, pending.score);
+    d.draw_text(&text, cursor.x as i32, cursor.y as i32, size, Color::WHITE);
+    cursor.y += size as f32 * 2.0;
+
+    let size = 20;
+    for i in 0..INITIALS_LEN {
+        let color = if i == name_entry.cursor {
+            Color::GOLD
+        } else {
+            Color::WHITE
+        };
+        let letter = (name_entry.initials[i] as char).to_string();
+        let x = cursor.x as i32 + i as i32 * size;
+        d.draw_text(&letter, x, cursor.y as i32, size, color);
+        if i == name_entry.cursor {
+            d.draw_rectangle(x, cursor.y as i32 + size, size / 2 + 2, 1, color);
+        }
+    }
+    cursor.y += size as f32 * 1.5;
+
+    let size = 1;
+    d.draw_text(
+        "type or pick letters, confirm on the last",
+        cursor.x as i32,
+        cursor.y as i32,
+        size,
+        Color::WHITE,
+    );
+}
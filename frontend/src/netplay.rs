@@ -0,0 +1,82 @@
+use std::net::SocketAddr;
+
+use ecsteroids::{
+    actions::Action,
+    modes::{self, Transition},
+    netplay::{self, LinkConditions, NetInput, NetSession},
+    state::{GameMode, State},
+    DIMS,
+};
+use glam::Vec2;
+use raylib::prelude::*;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input, playing,
+    rendering::LowResDrawHandle,
+};
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::Netplay,
+        FrontendMode {
+            process_events_and_input,
+            draw,
+            overlay: false,
+        },
+    );
+}
+
+pub fn process_events_and_input(rl: &mut RaylibHandle, state: &mut State) {
+    // a netplay run can't be paused, leaving ends it for both peers
+    let actions = input::action_state(state);
+    if actions.active(Action::Back) || actions.active(Action::Pause) {
+        modes::request(state, Transition::Reset(GameMode::Title));
+    }
+
+    let mouse_pos_rl = rl.get_mouse_position();
+    let net_input = NetInput {
+        inputs: input::local_inputs(rl, state),
+        mouse_pos: Vec2::new(mouse_pos_rl.x, mouse_pos_rl.y),
+    };
+    state.resources.insert(net_input);
+}
+
+pub fn draw(state: &State, d: &mut LowResDrawHandle) {
+    playing::draw(state, d);
+
+    let connected = state
+        .resources
+        .get::<NetSession>()
+        .map(|session| session.is_connected())
+        .unwrap_or(false);
+    if !connected {
+        let cursor = Vec2::new(DIMS.x as f32 * 0.28, DIMS.y as f32 * 0.45);
+        d.draw_text(
+            "waiting for peer",
+            cursor.x as i32,
+            cursor.y as i32,
+            10,
+            Color::WHITE,
+        );
+    }
+}
+
+pub fn host(state: &mut State, port: u16, conditions: LinkConditions) {
+    match NetSession::host(state, port, conditions) {
+        Ok(session) => netplay::start(state, session),
+        Err(e) => exit_with_error(e),
+    }
+}
+
+pub fn join(state: &mut State, host: SocketAddr, conditions: LinkConditions) {
+    match NetSession::join(host, conditions) {
+        Ok(session) => netplay::start(state, session),
+        Err(e) => exit_with_error(e),
+    }
+}
+
+fn exit_with_error(e: std::io::Error) {
+    println!("Error starting netplay: {}", e);
+    std::process::exit(1);
+}
@@ -0,0 +1,71 @@
+use ecsteroids::{
+    actions::Action,
+    pause::{self, PauseInputs, PauseMenu, PAUSE_MENU_ITEMS},
+    state::{GameMode, State},
+    DIMS,
+};
+use glam::Vec2;
+use raylib::prelude::*;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input,
+    rendering::LowResDrawHandle,
+};
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::Pause,
+        FrontendMode {
+            process_events_and_input,
+            draw,
+            overlay: true,
+        },
+    );
+}
+
+pub fn process_events_and_input(_rl: &mut RaylibHandle, state: &mut State) {
+    let actions = input::action_state(state);
+    let inputs = PauseInputs {
+        up: actions.active(Action::MenuUp),
+        down: actions.active(Action::MenuDown),
+        confirm: actions.active(Action::Confirm),
+        // pause toggles, so the key that opened the menu also closes it
+        back: actions.active(Action::Back) || actions.active(Action::Pause),
+    };
+    pause::handle_inputs(state, inputs);
+}
+
+pub fn draw(state: &State, d: &mut LowResDrawHandle) {
+    // dim the frozen game underneath
+    d.draw_rectangle(0, 0, DIMS.x as i32, DIMS.y as i32, Color::new(0, 0, 0, 150));
+
+    let mut cursor = Vec2::new(DIMS.x as f32 * 0.28, DIMS.y as f32 * 0.3);
+    let size = 20;
+    d.draw_text(
+        "PAUSED",
+        cursor.x as i32,
+        cursor.y as i32,
+        size,
+        Color::WHITE,
+    );
+    cursor.y += size as f32 * 1.5;
+
+    let selected = state
+        .resources
+        .get::<PauseMenu>()
+        .map(|menu| menu.selected)
+        .unwrap_or(0);
+    let size = 10;
+    for (i, item) in PAUSE_MENU_ITEMS.iter().enumerate() {
+        let color = if i == selected {
+            Color::GOLD
+        } else {
+            Color::WHITE
+        };
+        let marker = if i == selected { "> " } else { "  " };
+        let text = format!("{}{}", marker, item.label());
+        d.draw_text(&text, cursor.x as i32, cursor.y as i32, size, color);
+        cursor.y += size as f32 * 1.5;
+    }
+}
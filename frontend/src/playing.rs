@@ -0,0 +1,51 @@
+use ecsteroids::{
+    actions::Action,
+    modes::{self, Transition},
+    rendering::RenderCommandBuffer,
+    snapshot,
+    state::{GameMode, State},
+};
+use glam::Vec2;
+use raylib::prelude::*;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input,
+    rendering::{execute_render_command_buffer, LowResDrawHandle},
+};
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::Playing,
+        FrontendMode {
+            process_events_and_input,
+            draw,
+            overlay: false,
+        },
+    );
+}
+
+pub fn process_events_and_input(rl: &mut RaylibHandle, state: &mut State) {
+    let actions = input::action_state(state);
+    if actions.active(Action::Pause) {
+        modes::request(state, Transition::Push(GameMode::Pause));
+    }
+    if actions.active(Action::QuickSave) {
+        snapshot::quick_save(state);
+    }
+    if actions.active(Action::QuickLoad) {
+        snapshot::quick_load(state);
+    }
+
+    let player_inputs = input::player_inputs(rl, state);
+    state.resources.insert(player_inputs);
+
+    let mouse_pos_rl = rl.get_mouse_position();
+    let mouse_pos = Vec2::new(mouse_pos_rl.x, mouse_pos_rl.y);
+    state.resources.insert(mouse_pos);
+}
+
+pub fn draw(state: &State, d: &mut LowResDrawHandle) {
+    let mut render_command_buffer = state.resources.get_mut::<RenderCommandBuffer>().unwrap();
+    execute_render_command_buffer(d, &mut render_command_buffer);
+}
@@ -0,0 +1,172 @@
+use ecsteroids::{
+    components::{ASTEROID_OUTLINE, MAX_POLYGON_POINTS},
+    rendering::{Color, DrawCommand, RenderCommandBuffer},
+};
+use glam::Vec2;
+use raylib::prelude::{RaylibDraw, RaylibDrawHandle, RaylibMode2D, RaylibTextureMode, Vector2};
+
+// draws into the DIMS sized world, the camera zooms it up to the render texture size
+pub type LowResDrawHandle<'a, 'b, 'c> =
+    RaylibMode2D<'a, RaylibTextureMode<'b, RaylibDrawHandle<'c>>>;
+
+// the simulation only knows about its own colors, convert at the draw boundary
+fn rl_color(color: Color) -> raylib::prelude::Color {
+    raylib::prelude::Color::new(color.r, color.g, color.b, color.a)
+}
+
+// defualt entity size
+const SIZE: i32 = 1;
+const HEALTH_BAR_HEIGHT: i32 = 2;
+const PICKUP_SIZE: i32 = 5;
+
+pub fn execute_render_command_buffer(
+    d: &mut LowResDrawHandle,
+    render_command_buffer: &mut RenderCommandBuffer,
+) {
+    for command in render_command_buffer.iter() {
+        match command {
+            DrawCommand::ColoredSquare { pos, color } => {
+                d.draw_rectangle(pos.x as i32, pos.y as i32, SIZE, SIZE, rl_color(*color));
+            }
+            DrawCommand::Ship { pos, dir, color } => {
+                let center = Vec2::new(pos.x, pos.y);
+
+                // Check if dir is close to zero vector
+                if dir.length() > 1e-10 {
+                    let dir = dir.normalize() * 10.0;
+                    d.draw_circle_lines(center.x as i32, center.y as i32, 3.0, rl_color(*color));
+                    d.draw_line(
+                        center.x as i32,
+                        center.y as i32,
+                        (center.x + dir.x) as i32,
+                        (center.y + dir.y) as i32,
+                        rl_color(*color),
+                    );
+                } else {
+                    // Just draw the circle without the line, or handle the case where dir is a zero vector
+                    d.draw_circle_lines(center.x as i32, center.y as i32, 3.0, rl_color(*color));
+                }
+            }
+
+            DrawCommand::Asteroid { pos, size, dir } => {
+                let mut points: Vec<Vec2> = Vec::new();
+                let base_angle = 2.0 * std::f32::consts::PI / MAX_POLYGON_POINTS as f32;
+
+                // Convert the direction vector into an angle
+                let rot_angle = dir.y.atan2(dir.x);
+
+                // Generate points for the asteroid using the outline its collider is the hull of
+                for (i, segment) in ASTEROID_OUTLINE.iter().enumerate() {
+                    let angle = base_angle * i as f32;
+                    let r = *size as f32 * segment;
+
+                    let point = Vec2 {
+                        x: r * angle.cos(),
+                        y: r * angle.sin(),
+                    };
+
+                    // Rotate the point around the asteroid's center using glam's Mat2
+                    let rotation_matrix = glam::Mat2::from_angle(rot_angle);
+                    let rotated_point = rotation_matrix * point + *pos;
+
+                    points.push(rotated_point);
+                }
+
+                d.draw_circle(
+                    pos.x as i32,
+                    pos.y as i32,
+                    *size as f32 * 0.8,
+                    rl_color(Color::new(255, 255, 255, 30)),
+                );
+
+                // Draw each line segment for the asteroid
+                for i in 0..MAX_POLYGON_POINTS {
+                    let start_point = points[i];
+                    let end_point = points[(i + 1) % MAX_POLYGON_POINTS];
+                    d.draw_line_v(
+                        Vector2::new(start_point.x, start_point.y),
+                        Vector2::new(end_point.x, end_point.y),
+                        rl_color(Color::WHITE),
+                    );
+                }
+
+                // also draw a debug circle at the center of the asteroid
+            }
+            DrawCommand::Text {
+                pos,
+                text,
+                size,
+                color,
+            } => {
+                d.draw_text(text, pos.x as i32, pos.y as i32, *size, rl_color(*color));
+            }
+            DrawCommand::Gun { pos, dir } => {
+                let scale = 0.5;
+                let base_width = 10.0 * scale; // width of the triangle base
+                let length = 5.0 * scale; // length of the triangle (from tip to base)
+
+                // Convert the direction vector into an angle for rotation
+                let rot_angle = dir.y.atan2(dir.x) + std::f32::consts::FRAC_PI_2;
+
+                // Define the vertices of the triangle representing the gun
+                let tip = Vec2::new(0.0, -length / 2.0);
+                let base1 = Vec2::new(-base_width / 2.0, length / 2.0);
+                let base2 = Vec2::new(base_width / 2.0, length / 2.0);
+
+                // Rotate these points based on the 'dir' direction using glam's Mat2
+                let rotation_matrix = glam::Mat2::from_angle(rot_angle);
+
+                let tip = rotation_matrix * tip + *pos;
+                let base1 = rotation_matrix * base1 + *pos;
+                let base2 = rotation_matrix * base2 + *pos;
+
+                // Draw the triangle for the gun using raylib's draw_triangle function
+                d.draw_triangle(
+                    Vector2 { x: tip.x, y: tip.y },
+                    Vector2 {
+                        x: base1.x,
+                        y: base1.y,
+                    },
+                    Vector2 {
+                        x: base2.x,
+                        y: base2.y,
+                    },
+                    rl_color(Color::WHITE),
+                );
+            }
+            DrawCommand::Line { start, end, color } => {
+                d.draw_line_v(
+                    Vector2::new(start.x, start.y),
+                    Vector2::new(end.x, end.y),
+                    rl_color(*color),
+                );
+            }
+            DrawCommand::Circle { pos, radius, color } => {
+                d.draw_circle(pos.x as i32, pos.y as i32, *radius, rl_color(*color));
+            }
+            DrawCommand::HealthBar {
+                pos,
+                width,
+                fraction,
+            } => {
+                let left = (pos.x - width / 2.0) as i32;
+                let top = pos.y as i32 - HEALTH_BAR_HEIGHT / 2;
+                let filled = (width * fraction.clamp(0.0, 1.0)).round() as i32;
+                d.draw_rectangle(
+                    left,
+                    top,
+                    *width as i32,
+                    HEALTH_BAR_HEIGHT,
+                    rl_color(Color::MAROON),
+                );
+                d.draw_rectangle(left, top, filled, HEALTH_BAR_HEIGHT, rl_color(Color::LIME));
+            }
+            DrawCommand::Pickup { pos, color } => {
+                let left = pos.x as i32 - PICKUP_SIZE / 2;
+                let top = pos.y as i32 - PICKUP_SIZE / 2;
+                d.draw_rectangle_lines(left, top, PICKUP_SIZE, PICKUP_SIZE, rl_color(*color));
+                d.draw_rectangle(pos.x as i32, pos.y as i32, SIZE, SIZE, rl_color(*color));
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use ecsteroids::{
+    actions::Action,
+    modes::{self, Transition},
+    replay::{Replay, ReplayPlayback},
+    state::{GameMode, State},
+};
+use raylib::RaylibHandle;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input, playing,
+};
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::Replay,
+        FrontendMode {
+            process_events_and_input,
+            draw: playing::draw,
+            overlay: false,
+        },
+    );
+}
+
+pub fn process_events_and_input(_rl: &mut RaylibHandle, state: &mut State) {
+    let actions = input::action_state(state);
+    if actions.active(Action::Back) {
+        state.running = false;
+    }
+
+    // skip the rest of the replay
+    if actions.active(Action::Confirm) {
+        modes::request(state, Transition::Reset(GameMode::Title));
+    }
+}
+
+pub fn start_replay(state: &mut State, path: &Path) {
+    let replay = Replay::load(path).unwrap_or_else(|e| {
+        println!("Error loading replay {}: {}", path.display(), e);
+        std::process::exit(1);
+    });
+    state.resources.insert(ReplayPlayback::new(replay));
+    modes::request(state, Transition::Reset(GameMode::Replay));
+}
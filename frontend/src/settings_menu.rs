@@ -0,0 +1,83 @@
+use ecsteroids::{
+    actions::Action,
+    settings::Settings,
+    settings_menu::{self, SettingsInputs, SettingsMenu, SETTINGS_MENU_ITEMS},
+    state::{GameMode, State},
+    DIMS,
+};
+use glam::Vec2;
+use raylib::prelude::*;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input,
+    rendering::LowResDrawHandle,
+};
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::Settings,
+        FrontendMode {
+            process_events_and_input,
+            draw,
+            overlay: false,
+        },
+    );
+}
+
+pub fn process_events_and_input(_rl: &mut RaylibHandle, state: &mut State) {
+    let actions = input::action_state(state);
+    let inputs = SettingsInputs {
+        up: actions.active(Action::MenuUp),
+        down: actions.active(Action::MenuDown),
+        left: actions.active(Action::MenuLeft),
+        right: actions.active(Action::MenuRight),
+        confirm: actions.active(Action::Confirm),
+        back: actions.active(Action::Back),
+    };
+    settings_menu::handle_inputs(state, inputs);
+}
+
+pub fn draw(state: &State, d: &mut LowResDrawHandle) {
+    let mut cursor = Vec2::new(DIMS.x as f32 * 0.15, 4.0);
+    let size = 20;
+    d.draw_text(
+        "SETTINGS",
+        cursor.x as i32,
+        cursor.y as i32,
+        size,
+        Color::WHITE,
+    );
+    cursor.y += size as f32 * 1.2;
+
+    let selected = state
+        .resources
+        .get::<SettingsMenu>()
+        .map(|menu| menu.selected)
+        .unwrap_or(0);
+    let settings = match state.resources.get::<Settings>() {
+        Some(settings) => settings.clone(),
+        None => return,
+    };
+    let size = 10;
+    let value_x = DIMS.x as f32 * 0.6;
+    for (i, item) in SETTINGS_MENU_ITEMS.iter().enumerate() {
+        let color = if i == selected {
+            Color::GOLD
+        } else {
+            Color::WHITE
+        };
+        let marker = if i == selected { "> " } else { "  " };
+        let text = format!("{}{}", marker, item.label());
+        d.draw_text(&text, cursor.x as i32, cursor.y as i32, size, color);
+        if let Some(value) = item.value(&settings) {
+            let value = if i == selected {
+                format!("< {} >", value)
+            } else {
+                value
+            };
+            d.draw_text(&value, value_x as i32, cursor.y as i32, size, color);
+        }
+        cursor.y += size as f32;
+    }
+}
@@ -0,0 +1,65 @@
+use ecsteroids::{
+    actions::Action,
+    state::{GameMode, State},
+    title::{self, TitleMenu, TitleMenuInputs, TITLE_MENU_ITEMS},
+    DIMS,
+};
+use glam::Vec2;
+use raylib::prelude::*;
+
+use crate::{
+    frontend_modes::{FrontendMode, FrontendModeRegistry},
+    input,
+    rendering::LowResDrawHandle,
+};
+
+pub fn register(frontend_modes: &mut FrontendModeRegistry) {
+    frontend_modes.insert(
+        GameMode::Title,
+        FrontendMode {
+            process_events_and_input,
+            draw,
+            overlay: false,
+        },
+    );
+}
+
+pub fn process_events_and_input(_rl: &mut RaylibHandle, state: &mut State) {
+    let actions = input::action_state(state);
+    let inputs = TitleMenuInputs {
+        up: actions.active(Action::MenuUp),
+        down: actions.active(Action::MenuDown),
+        confirm: actions.active(Action::Confirm),
+        back: actions.active(Action::Back),
+    };
+    title::handle_inputs(state, inputs);
+}
+
+pub fn draw(state: &State, d: &mut LowResDrawHandle) {
+    // draw the title screen
+    // name is ecsstroids
+
+    let mut cursor = Vec2::new(DIMS.x as f32 * 0.28, DIMS.y as f32 * 0.4);
+    let title = "ECStroids!";
+    let size = 20;
+    d.draw_text(title, cursor.x as i32, cursor.y as i32, size, Color::WHITE);
+    cursor.y += size as f32 * 1.5;
+
+    let selected = state
+        .resources
+        .get::<TitleMenu>()
+        .map(|menu| menu.selected)
+        .unwrap_or(0);
+    let size = 10;
+    for (i, item) in TITLE_MENU_ITEMS.iter().enumerate() {
+        let color = if i == selected {
+            Color::GOLD
+        } else {
+            Color::WHITE
+        };
+        let marker = if i == selected { "> " } else { "  " };
+        let text = format!("{}{}", marker, item.label());
+        d.draw_text(&text, cursor.x as i32, cursor.y as i32, size, color);
+        cursor.y += size as f32 * 1.5;
+    }
+}
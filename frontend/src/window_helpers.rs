@@ -0,0 +1,86 @@
+use ecsteroids::{settings::VideoSettings, DIMS};
+use glam::UVec2;
+use raylib::core::window::{get_current_monitor, get_monitor_height, get_monitor_width};
+use raylib::prelude::*;
+
+pub fn center_window(rl: &mut raylib::RaylibHandle, window_dims: UVec2) {
+    let monitor = get_current_monitor();
+    let screen_dims = UVec2::new(
+        get_monitor_width(monitor) as u32,
+        get_monitor_height(monitor) as u32,
+    );
+    let offset = (screen_dims.as_ivec2() - window_dims.as_ivec2()) / 2;
+    rl.set_window_position(offset.x.max(0), offset.y.max(0));
+}
+
+/** the size the render texture is stretched to, the whole monitor when fullscreen */
+pub fn window_dims(video: &VideoSettings) -> UVec2 {
+    if video.fullscreen {
+        let monitor = get_current_monitor();
+        UVec2::new(
+            get_monitor_width(monitor) as u32,
+            get_monitor_height(monitor) as u32,
+        )
+    } else {
+        UVec2::new(video.window_width, video.window_height)
+    }
+}
+
+pub fn apply_video_settings(rl: &mut raylib::RaylibHandle, video: &VideoSettings) {
+    let window_dims = window_dims(video);
+    if video.fullscreen {
+        // fullscreen takes the window size as the video mode, so resize first
+        rl.set_window_size(window_dims.x as i32, window_dims.y as i32);
+        if !rl.is_window_fullscreen() {
+            rl.toggle_fullscreen();
+        }
+    } else {
+        if rl.is_window_fullscreen() {
+            rl.toggle_fullscreen();
+        }
+        rl.set_window_size(window_dims.x as i32, window_dims.y as i32);
+        center_window(rl, window_dims);
+    }
+
+    let mouse_scale = DIMS.as_vec2() / window_dims.as_vec2();
+    rl.set_mouse_scale(mouse_scale.x, mouse_scale.y);
+    rl.set_target_fps(video.target_fps);
+}
+
+pub fn load_render_texture(
+    rl: &mut raylib::RaylibHandle,
+    rlt: &RaylibThread,
+    video: &VideoSettings,
+) -> RenderTexture2D {
+    let dims = DIMS * video.render_scale;
+    rl.load_render_texture(rlt, dims.x, dims.y)
+        .unwrap_or_else(|e| {
+            println!("Error creating render texture: {}", e);
+            std::process::exit(1);
+        })
+}
+
+pub fn scale_and_blit_render_texture_to_window(
+    draw_handle: &mut RaylibDrawHandle,
+    render_texture: &mut RenderTexture2D,
+    window_dims: UVec2,
+) {
+    let source_rec = Rectangle::new(
+        0.0,
+        0.0,
+        render_texture.texture.width as f32,
+        -render_texture.texture.height as f32,
+    );
+    let dest_rec = Rectangle::new(0.0, 0.0, window_dims.x as f32, window_dims.y as f32);
+
+    let origin = Vector2::new(0.0, 0.0);
+
+    draw_handle.draw_texture_pro(
+        render_texture,
+        source_rec,
+        dest_rec,
+        origin,
+        0.0,
+        Color::WHITE,
+    );
+}
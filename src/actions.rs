@@ -0,0 +1,591 @@
+use std::collections::{HashMap, HashSet};
+
+pub const MAX_BINDINGS_PER_ACTION: usize = 4;
+// how far an analog binding has to be pushed to count as down for pressed actions
+const PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Thrust,
+    Reverse,
+    RotateLeft,
+    RotateRight,
+    Fire,
+    Confirm,
+    Back,
+    Pause,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    QuickSave,
+    QuickLoad,
+}
+
+pub const ACTIONS: [Action; 14] = [
+    Action::Thrust,
+    Action::Reverse,
+    Action::RotateLeft,
+    Action::RotateRight,
+    Action::Fire,
+    Action::Confirm,
+    Action::Back,
+    Action::Pause,
+    Action::MenuUp,
+    Action::MenuDown,
+    Action::MenuLeft,
+    Action::MenuRight,
+    Action::QuickSave,
+    Action::QuickLoad,
+];
+
+// whether an action is active while its binding is down, or only on the frame it goes down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    Held,
+    Pressed,
+}
+
+impl Action {
+    // the key used in the settings file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Thrust => "thrust",
+            Action::Reverse => "reverse",
+            Action::RotateLeft => "rotate_left",
+            Action::RotateRight => "rotate_right",
+            Action::Fire => "fire",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+            Action::Pause => "pause",
+            Action::MenuUp => "menu_up",
+            Action::MenuDown => "menu_down",
+            Action::MenuLeft => "menu_left",
+            Action::MenuRight => "menu_right",
+            Action::QuickSave => "quick_save",
+            Action::QuickLoad => "quick_load",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Thrust => "thrust",
+            Action::Reverse => "reverse",
+            Action::RotateLeft => "rotate left",
+            Action::RotateRight => "rotate right",
+            Action::Fire => "fire",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+            Action::Pause => "pause",
+            Action::MenuUp => "menu up",
+            Action::MenuDown => "menu down",
+            Action::MenuLeft => "menu left",
+            Action::MenuRight => "menu right",
+            Action::QuickSave => "quick save",
+            Action::QuickLoad => "quick load",
+        }
+    }
+
+    pub fn trigger(&self) -> Trigger {
+        match self {
+            Action::Thrust
+            | Action::Reverse
+            | Action::RotateLeft
+            | Action::RotateRight
+            | Action::Fire => Trigger::Held,
+            _ => Trigger::Pressed,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTIONS.iter().copied().find(|action| action.name() == name)
+    }
+}
+
+// a physical input, keys, mouse buttons and gamepad buttons and axes use the same codes as raylib
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(u32),
+    MouseButton(u32),
+    GamepadButton(u32),
+    // one direction of a stick, so each half can drive a different action
+    GamepadAxis { axis: u32, positive: bool },
+}
+
+// the keys that are not a single letter or digit
+const KEY_NAMES: [(u32, &str); 33] = [
+    (32, "SPACE"),
+    (39, "APOSTROPHE"),
+    (44, "COMMA"),
+    (45, "MINUS"),
+    (46, "PERIOD"),
+    (47, "SLASH"),
+    (59, "SEMICOLON"),
+    (256, "ESCAPE"),
+    (257, "ENTER"),
+    (258, "TAB"),
+    (259, "BACKSPACE"),
+    (262, "RIGHT"),
+    (263, "LEFT"),
+    (264, "DOWN"),
+    (265, "UP"),
+    (290, "F1"),
+    (291, "F2"),
+    (292, "F3"),
+    (293, "F4"),
+    (294, "F5"),
+    (295, "F6"),
+    (296, "F7"),
+    (297, "F8"),
+    (298, "F9"),
+    (299, "F10"),
+    (300, "F11"),
+    (301, "F12"),
+    (340, "LEFT_SHIFT"),
+    (341, "LEFT_CONTROL"),
+    (342, "LEFT_ALT"),
+    (344, "RIGHT_SHIFT"),
+    (345, "RIGHT_CONTROL"),
+    (346, "RIGHT_ALT"),
+];
+
+const MOUSE_BUTTON_NAMES: [(u32, &str); 3] =
+    [(0, "MOUSE_LEFT"), (1, "MOUSE_RIGHT"), (2, "MOUSE_MIDDLE")];
+
+// face buttons are named after their xbox layout
+const GAMEPAD_BUTTON_NAMES: [(u32, &str); 17] = [
+    (1, "PAD_UP"),
+    (2, "PAD_RIGHT"),
+    (3, "PAD_DOWN"),
+    (4, "PAD_LEFT"),
+    (5, "PAD_Y"),
+    (6, "PAD_B"),
+    (7, "PAD_A"),
+    (8, "PAD_X"),
+    (9, "PAD_LB"),
+    (10, "PAD_LT"),
+    (11, "PAD_RB"),
+    (12, "PAD_RT"),
+    (13, "PAD_SELECT"),
+    (14, "PAD_HOME"),
+    (15, "PAD_START"),
+    (16, "PAD_LEFT_STICK"),
+    (17, "PAD_RIGHT_STICK"),
+];
+
+const GAMEPAD_AXIS_NAMES: [(u32, &str); 6] = [
+    (0, "PAD_LEFT_X"),
+    (1, "PAD_LEFT_Y"),
+    (2, "PAD_RIGHT_X"),
+    (3, "PAD_RIGHT_Y"),
+    (4, "PAD_LEFT_TRIGGER"),
+    (5, "PAD_RIGHT_TRIGGER"),
+];
+
+impl Binding {
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(code) => {
+                if let Some(name) = find_name(&KEY_NAMES, *code) {
+                    return name.to_string();
+                }
+                match char::from_u32(*code) {
+                    Some(c) if c.is_ascii_uppercase() || c.is_ascii_digit() => c.to_string(),
+                    _ => format!("KEY_{}", code),
+                }
+            }
+            Binding::MouseButton(code) => match find_name(&MOUSE_BUTTON_NAMES, *code) {
+                Some(name) => name.to_string(),
+                None => format!("MOUSE_{}", code),
+            },
+            Binding::GamepadButton(code) => match find_name(&GAMEPAD_BUTTON_NAMES, *code) {
+                Some(name) => name.to_string(),
+                None => format!("PAD_BUTTON_{}", code),
+            },
+            Binding::GamepadAxis { axis, positive } => {
+                let sign = if *positive { '+' } else { '-' };
+                match find_name(&GAMEPAD_AXIS_NAMES, *axis) {
+                    Some(name) => format!("{}{}", name, sign),
+                    None => format!("PAD_AXIS_{}{}", axis, sign),
+                }
+            }
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(code) = find_code(&KEY_NAMES, name) {
+            return Some(Binding::Key(code));
+        }
+        if let Some(code) = find_code(&MOUSE_BUTTON_NAMES, name) {
+            return Some(Binding::MouseButton(code));
+        }
+        if let Some(code) = find_code(&GAMEPAD_BUTTON_NAMES, name) {
+            return Some(Binding::GamepadButton(code));
+        }
+        if let Some(code) = name.strip_prefix("KEY_") {
+            return code.parse().ok().map(Binding::Key);
+        }
+        if let Some(code) = name.strip_prefix("MOUSE_") {
+            return code.parse().ok().map(Binding::MouseButton);
+        }
+        if let Some(code) = name.strip_prefix("PAD_BUTTON_") {
+            return code.parse().ok().map(Binding::GamepadButton);
+        }
+        if let Some(axis_name) = name.strip_suffix('+').or_else(|| name.strip_suffix('-')) {
+            let positive = name.ends_with('+');
+            let axis = match axis_name.strip_prefix("PAD_AXIS_") {
+                Some(code) => code.parse().ok(),
+                None => find_code(&GAMEPAD_AXIS_NAMES, axis_name),
+            };
+            return axis.map(|axis| Binding::GamepadAxis { axis, positive });
+        }
+        let mut chars = name.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_uppercase() || c.is_ascii_digit() => {
+                Some(Binding::Key(c as u32))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn find_name(names: &[(u32, &'static str)], code: u32) -> Option<&'static str> {
+    names
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+}
+
+fn find_code(names: &[(u32, &str)], name: &str) -> Option<u32> {
+    names
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(code, _)| *code)
+}
+
+/** how far a raw axis reading in -1 to 1 pushes one half of the axis, rescaled to start at the deadzone */
+pub fn axis_amount(raw: f32, positive: bool, deadzone: f32) -> f32 {
+    let deflection = if positive { raw } else { -raw };
+    if deflection <= deadzone {
+        return 0.0;
+    }
+    ((deflection - deadzone) / (1.0 - deadzone)).min(1.0)
+}
+
+// which physical inputs drive a player, a lone player uses them all
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputDevice {
+    Any,
+    // keys and mouse buttons
+    Keyboard,
+    // raylib's gamepad number
+    Gamepad(u32),
+}
+
+impl InputDevice {
+    // the value used in the settings file
+    pub fn name(&self) -> String {
+        match self {
+            InputDevice::Any => "any".to_string(),
+            InputDevice::Keyboard => "keyboard".to_string(),
+            InputDevice::Gamepad(gamepad) => format!("gamepad{}", gamepad),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "any" => Some(InputDevice::Any),
+            "keyboard" => Some(InputDevice::Keyboard),
+            name => name
+                .strip_prefix("gamepad")?
+                .parse()
+                .ok()
+                .map(InputDevice::Gamepad),
+        }
+    }
+
+    /** whether the binding is read from this device, gamepad bindings are read from every gamepad by Any */
+    pub fn reads(&self, binding: &Binding) -> bool {
+        matches!(
+            (self, binding),
+            (InputDevice::Any, _)
+                | (
+                    InputDevice::Keyboard,
+                    Binding::Key(_) | Binding::MouseButton(_)
+                )
+                | (
+                    InputDevice::Gamepad(_),
+                    Binding::GamepadButton(_) | Binding::GamepadAxis { .. }
+                )
+        )
+    }
+}
+
+pub type Bindings = HashMap<Action, Vec<Binding>>;
+
+pub fn default_bindings() -> Bindings {
+    let bindings = |names: &[&str]| -> Vec<Binding> {
+        names
+            .iter()
+            .filter_map(|name| Binding::from_name(name))
+            .collect()
+    };
+    ACTIONS
+        .iter()
+        .map(|action| {
+            let action_bindings = match action {
+                Action::Thrust => bindings(&["UP", "W", "PAD_LEFT_Y-"]),
+                Action::Reverse => bindings(&["DOWN", "S", "PAD_LEFT_Y+"]),
+                Action::RotateLeft => bindings(&["LEFT", "A", "PAD_LEFT_X-"]),
+                Action::RotateRight => bindings(&["RIGHT", "D", "PAD_LEFT_X+"]),
+                Action::Fire => bindings(&["SPACE", "MOUSE_LEFT", "PAD_A", "PAD_RT"]),
+                Action::Confirm => bindings(&["SPACE", "ENTER", "PAD_A"]),
+                Action::Back => bindings(&["ESCAPE", "PAD_B"]),
+                Action::Pause => bindings(&["ESCAPE", "P", "PAD_START"]),
+                Action::MenuUp => bindings(&["UP", "W", "PAD_UP"]),
+                Action::MenuDown => bindings(&["DOWN", "S", "PAD_DOWN"]),
+                Action::MenuLeft => bindings(&["LEFT", "A", "PAD_LEFT"]),
+                Action::MenuRight => bindings(&["RIGHT", "D", "PAD_RIGHT"]),
+                Action::QuickSave => bindings(&["F5"]),
+                Action::QuickLoad => bindings(&["F9"]),
+            };
+            (*action, action_bindings)
+        })
+        .collect()
+}
+
+/** adds a binding to an action, dropping its oldest one when it already has the most allowed */
+pub fn add_binding(bindings: &mut Bindings, action: Action, binding: Binding) {
+    let action_bindings = bindings.entry(action).or_default();
+    if action_bindings.contains(&binding) {
+        return;
+    }
+    if action_bindings.len() >= MAX_BINDINGS_PER_ACTION {
+        action_bindings.remove(0);
+    }
+    action_bindings.push(binding);
+}
+
+// resource, the actions active this frame, updated by the frontend from the bindings
+#[derive(Clone, Debug, Default)]
+pub struct ActionState {
+    values: HashMap<Action, f32>,
+    // the actions whose bindings are down, to find the frame a pressed action goes down
+    down: HashSet<Action>,
+}
+
+impl ActionState {
+    /** amount is how far the action's bindings are pushed this frame, 0 to 1 */
+    pub fn update(&mut self, action: Action, amount: f32) {
+        let is_down = amount >= PRESS_THRESHOLD;
+        let value = match action.trigger() {
+            Trigger::Held => amount,
+            Trigger::Pressed if is_down && !self.down.contains(&action) => 1.0,
+            Trigger::Pressed => 0.0,
+        };
+        self.values.insert(action, value);
+        if is_down {
+            self.down.insert(action);
+        } else {
+            self.down.remove(&action);
+        }
+    }
+
+    /** held actions give how far they are pushed, pressed ones are 1 only on the frame they went down */
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.0)
+    }
+
+    pub fn active(&self, action: Action) -> bool {
+        self.value(action) > 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trips(binding: Binding) {
+        assert_eq!(
+            Binding::from_name(&binding.name()),
+            Some(binding),
+            "{}",
+            binding.name()
+        );
+    }
+
+    #[test]
+    fn named_bindings_round_trip() {
+        for (code, name) in KEY_NAMES {
+            assert_eq!(Binding::Key(code).name(), name);
+            round_trips(Binding::Key(code));
+        }
+        for (code, name) in MOUSE_BUTTON_NAMES {
+            assert_eq!(Binding::MouseButton(code).name(), name);
+            round_trips(Binding::MouseButton(code));
+        }
+        for (code, name) in GAMEPAD_BUTTON_NAMES {
+            assert_eq!(Binding::GamepadButton(code).name(), name);
+            round_trips(Binding::GamepadButton(code));
+        }
+        for (axis, _) in GAMEPAD_AXIS_NAMES {
+            for positive in [true, false] {
+                round_trips(Binding::GamepadAxis { axis, positive });
+            }
+        }
+        assert_eq!(
+            Binding::GamepadAxis {
+                axis: 1,
+                positive: false
+            }
+            .name(),
+            "PAD_LEFT_Y-"
+        );
+    }
+
+    #[test]
+    fn letters_and_digits_are_their_own_names() {
+        for c in ('A'..='Z').chain('0'..='9') {
+            assert_eq!(Binding::Key(c as u32).name(), c.to_string());
+            round_trips(Binding::Key(c as u32));
+        }
+    }
+
+    #[test]
+    fn unnamed_bindings_fall_back_to_their_codes() {
+        let fallbacks = [
+            (Binding::Key(999), "KEY_999"),
+            // lower case letters aren't raylib key codes
+            (Binding::Key('a' as u32), "KEY_97"),
+            (Binding::MouseButton(5), "MOUSE_5"),
+            (Binding::GamepadButton(0), "PAD_BUTTON_0"),
+            (
+                Binding::GamepadAxis {
+                    axis: 9,
+                    positive: true,
+                },
+                "PAD_AXIS_9+",
+            ),
+        ];
+        for (binding, name) in fallbacks {
+            assert_eq!(binding.name(), name);
+            round_trips(binding);
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_not_bindings() {
+        for name in [
+            "",
+            "w",
+            "AB",
+            "NOPE",
+            "KEY_",
+            "KEY_W",
+            "MOUSE_",
+            "PAD_BUTTON_A",
+            "PAD_LEFT_Y",
+            "PAD_AXIS_+",
+            "-",
+        ] {
+            assert_eq!(Binding::from_name(name), None, "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn pressed_actions_only_fire_on_the_step_they_go_down() {
+        let mut action_state = ActionState::default();
+        let values: Vec<f32> = [1.0, 1.0, 0.0, 1.0, 0.3]
+            .into_iter()
+            .map(|amount| {
+                action_state.update(Action::Confirm, amount);
+                action_state.value(Action::Confirm)
+            })
+            .collect();
+        assert_eq!(values, vec![1.0, 0.0, 0.0, 1.0, 0.0]);
+        assert!(!action_state.active(Action::Confirm));
+    }
+
+    #[test]
+    fn held_actions_last_as_long_as_their_bindings() {
+        let mut action_state = ActionState::default();
+        assert!(!action_state.active(Action::Thrust));
+        for amount in [1.0, 1.0, 0.25] {
+            action_state.update(Action::Thrust, amount);
+            assert_eq!(action_state.value(Action::Thrust), amount);
+            assert!(action_state.active(Action::Thrust));
+        }
+        action_state.update(Action::Thrust, 0.0);
+        assert!(!action_state.active(Action::Thrust));
+    }
+
+    #[test]
+    fn rebinding_a_full_action_replaces_its_oldest_binding() {
+        let mut bindings = default_bindings();
+        let names = |bindings: &Bindings| -> Vec<String> {
+            bindings[&Action::Fire]
+                .iter()
+                .map(|binding| binding.name())
+                .collect()
+        };
+        assert_eq!(bindings[&Action::Fire].len(), MAX_BINDINGS_PER_ACTION);
+
+        add_binding(&mut bindings, Action::Fire, Binding::Key('F' as u32));
+        assert_eq!(names(&bindings), vec!["MOUSE_LEFT", "PAD_A", "PAD_RT", "F"]);
+
+        // binding something already bound changes nothing
+        add_binding(&mut bindings, Action::Fire, Binding::GamepadButton(7));
+        assert_eq!(names(&bindings), vec!["MOUSE_LEFT", "PAD_A", "PAD_RT", "F"]);
+
+        // with room to spare nothing is dropped
+        bindings.insert(Action::QuickSave, Vec::new());
+        add_binding(&mut bindings, Action::QuickSave, Binding::Key(294));
+        assert_eq!(bindings[&Action::QuickSave], vec![Binding::Key(294)]);
+    }
+
+    #[test]
+    fn axes_start_at_the_deadzone_and_stop_at_full_deflection() {
+        let deadzone = 0.2;
+        assert_eq!(axis_amount(0.0, true, deadzone), 0.0);
+        assert_eq!(axis_amount(0.1, true, deadzone), 0.0);
+        assert_eq!(axis_amount(deadzone, true, deadzone), 0.0);
+        let just_past = axis_amount(deadzone + 0.01, true, deadzone);
+        assert!(just_past > 0.0 && just_past < 0.02, "{}", just_past);
+        assert!((axis_amount(0.6, true, deadzone) - 0.5).abs() < 1e-6);
+        assert_eq!(axis_amount(1.0, true, deadzone), 1.0);
+        // sticks can read a little past the edge
+        assert_eq!(axis_amount(1.05, true, deadzone), 1.0);
+        // without a deadzone the reading passes straight through
+        assert_eq!(axis_amount(0.3, true, 0.0), 0.3);
+    }
+
+    #[test]
+    fn each_half_of_an_axis_reads_only_its_own_direction() {
+        let deadzone = 0.2;
+        for raw in [-1.0, -0.6, -0.2, 0.0] {
+            assert_eq!(axis_amount(raw, true, deadzone), 0.0, "{}", raw);
+        }
+        for raw in [0.0, 0.2, 0.6, 1.0] {
+            assert_eq!(axis_amount(raw, false, deadzone), 0.0, "{}", raw);
+        }
+        assert_eq!(axis_amount(-1.0, false, deadzone), 1.0);
+        assert!((axis_amount(-0.6, false, deadzone) - 0.5).abs() < 1e-6);
+
+        // pushing the left stick up thrusts and doesn't reverse
+        let bindings = default_bindings();
+        let stick_half = |action: Action| {
+            bindings[&action]
+                .iter()
+                .find_map(|binding| match binding {
+                    Binding::GamepadAxis { axis: 1, positive } => Some(*positive),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert_eq!(axis_amount(-1.0, stick_half(Action::Thrust), deadzone), 1.0);
+        assert_eq!(
+            axis_amount(-1.0, stick_half(Action::Reverse), deadzone),
+            0.0
+        );
+    }
+}
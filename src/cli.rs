@@ -0,0 +1,99 @@
+use std::{net::SocketAddr, str::FromStr, time::Duration};
+
+use crate::netplay::LinkConditions;
+
+// command line flags that the game and the examples take the same way
+
+/** the value after a flag, or what to tell whoever passed it */
+pub fn parse_value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    match value.as_deref().map(str::parse) {
+        Some(Ok(value)) => Ok(value),
+        _ => Err(format!("Expected a valid value after {}", flag)),
+    }
+}
+
+// how to start a netplay session, if at all
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NetFlags {
+    // start a two player netplay run, waiting on this port for someone to join
+    pub host: Option<u16>,
+    // join a netplay run hosted at this address
+    pub join: Option<SocketAddr>,
+    // loss and latency on outgoing packets, for testing
+    pub conditions: LinkConditions,
+}
+
+impl NetFlags {
+    /** takes the flag and its value from args if it is a netplay one, false if it isn't */
+    pub fn parse(
+        &mut self,
+        flag: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool, String> {
+        match flag {
+            "--host" => self.host = Some(parse_value(flag, args.next())?),
+            "--join" => self.join = Some(parse_value(flag, args.next())?),
+            "--net-loss" => {
+                let loss: f32 = parse_value(flag, args.next())?;
+                if !(0.0..=1.0).contains(&loss) {
+                    return Err(format!("{} has to be from 0 to 1", flag));
+                }
+                self.conditions.loss = loss;
+            }
+            "--net-latency" => {
+                self.conditions.latency = Duration::from_millis(parse_value(flag, args.next())?);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<NetFlags, String> {
+        let mut flags = NetFlags::default();
+        let mut args = args.iter().map(|arg| arg.to_string());
+        while let Some(flag) = args.next() {
+            if !flags.parse(&flag, &mut args)? {
+                return Err(format!("Unknown argument: {}", flag));
+            }
+        }
+        Ok(flags)
+    }
+
+    #[test]
+    fn netplay_flags() {
+        let flags = parse(&[
+            "--join",
+            "127.0.0.1:7000",
+            "--net-loss",
+            "0.25",
+            "--net-latency",
+            "60",
+        ])
+        .unwrap();
+        assert_eq!(flags.host, None);
+        assert_eq!(flags.join, Some("127.0.0.1:7000".parse().unwrap()));
+        assert_eq!(flags.conditions.loss, 0.25);
+        assert_eq!(flags.conditions.latency, Duration::from_millis(60));
+        assert_eq!(parse(&["--host", "7000"]).unwrap().host, Some(7000));
+    }
+
+    #[test]
+    fn bad_netplay_flags() {
+        assert!(parse(&["--host"]).is_err());
+        assert!(parse(&["--host", "70000"]).is_err());
+        assert!(parse(&["--join", "somewhere"]).is_err());
+        assert!(parse(&["--net-latency", "-1"]).is_err());
+        assert!(parse(&["--seed", "1"]).is_err());
+        for loss in ["-0.1", "1.5", "NaN"] {
+            assert!(parse(&["--net-loss", loss]).is_err(), "{}", loss);
+        }
+        for loss in ["0", "1"] {
+            assert!(parse(&["--net-loss", loss]).is_ok(), "{}", loss);
+        }
+    }
+}
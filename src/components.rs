@@ -7,37 +7,278 @@ pub struct CTransform {
     pub rot: Vec2,
 }
 
+// where the transform was at the start of the last step, used to interpolate drawing between steps
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PreviousCTransform(pub CTransform);
+
+#[derive(Clone, Copy)]
 pub struct Player;
 
+#[derive(Clone, Copy)]
 pub struct Gun {
     pub wants_to_shoot: bool,
     pub fire_delay: u32,
     pub cooldown: u32,
 }
 
+#[derive(Clone, Copy)]
 pub struct Bullet;
 
+// a laser shot from the entity's position, everything along it is hit once
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Beam {
+    pub dir: Vec2,
+    pub length: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerupKind {
+    BiggerBullets,
+    LongerRange,
+    FasterShooting,
+    TwoStreams,
+    ThreeStreams,
+    Laser,
+}
+
+// floats around until a player's ship flies over it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pickup {
+    pub kind: PowerupKind,
+}
+
+// on a ship, what it has picked up and kept. bigger bullets, range and fire rate stack up to a cap
+// and last until the ship is lost, extra streams and the laser run out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Powerups {
+    pub bigger_bullets: u32,
+    pub longer_range: u32,
+    pub faster_shooting: u32,
+    // bullets per shot, 0 and 1 both mean a single stream
+    pub streams: u32,
+    pub streams_steps_left: u32,
+    pub laser_steps_left: u32,
+}
+
+#[derive(Clone, Copy)]
 pub struct LifeSpan {
     pub frames_left: u32,
 }
 
+#[derive(Clone, Copy)]
 pub struct Asteroid {
     pub size: u32,
 }
 
-pub struct InputControlled;
+// how far each corner of an asteroid's outline is from its center, times its size, going round
+// evenly from its heading. drawn by the frontend, its collider is the convex hull round it so hits
+// only miss the outline in its dents
+pub const ASTEROID_OUTLINE: [f32; MAX_POLYGON_POINTS] = [
+    0.8, 0.75, 0.9, 0.85, 0.7, 0.88, 0.95, 0.78, 0.92, 0.76, 0.87, 0.8,
+];
 
-pub struct _Health {
+pub const MAX_POLYGON_POINTS: usize = 12;
+
+// the shape something touches other things with, in its own frame around its position and turned
+// with its heading
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColliderShape {
+    Circle {
+        radius: f32,
+    },
+    // corners going round, only the first len are used. the outline has to be convex, which
+    // polygon makes sure of
+    Polygon {
+        points: [Vec2; MAX_POLYGON_POINTS],
+        len: usize,
+    },
+    // from the position out to end
+    Segment {
+        end: Vec2,
+    },
+}
+
+impl ColliderShape {
+    pub const fn circle(radius: f32) -> Self {
+        Self::Circle { radius }
+    }
+
+    /** the convex hull round the corners, anything past MAX_POLYGON_POINTS corners is dropped */
+    pub fn polygon(corners: &[Vec2]) -> Self {
+        let hull = convex_hull(&corners[..corners.len().min(MAX_POLYGON_POINTS)]);
+        let mut points = [Vec2::ZERO; MAX_POLYGON_POINTS];
+        points[..hull.len()].copy_from_slice(&hull);
+        Self::Polygon {
+            points,
+            len: hull.len(),
+        }
+    }
+
+    pub const fn segment(end: Vec2) -> Self {
+        Self::Segment { end }
+    }
+
+    /** the convex hull round the outline the frontend draws for an asteroid this big */
+    pub fn asteroid(size: u32) -> Self {
+        let step = std::f32::consts::TAU / MAX_POLYGON_POINTS as f32;
+        let mut points = [Vec2::ZERO; MAX_POLYGON_POINTS];
+        for (i, (point, scale)) in points.iter_mut().zip(ASTEROID_OUTLINE).enumerate() {
+            *point = Vec2::from_angle(step * i as f32) * size as f32 * scale;
+        }
+        Self::polygon(&points)
+    }
+
+    /** the furthest any part of it gets from the position */
+    pub fn reach(&self) -> f32 {
+        match self {
+            ColliderShape::Circle { radius } => *radius,
+            ColliderShape::Polygon { points, len } => points[..*len]
+                .iter()
+                .map(|point| point.length())
+                .fold(0.0, f32::max),
+            ColliderShape::Segment { end } => end.length(),
+        }
+    }
+}
+
+// the smallest convex outline round the points, going the same way round as the asteroid outline.
+// corners in a straight line with their neighbours are left out
+fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+    // the bottom half left to right then the top half back, each only ever turning the same way
+    let mut hull: Vec<Vec2> = Vec::new();
+    for half in [sorted.clone(), sorted.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in half {
+            while hull.len() >= start + 2
+                && (hull[hull.len() - 1] - hull[hull.len() - 2])
+                    .perp_dot(point - hull[hull.len() - 1])
+                    <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // the last of each half is the first of the other
+        hull.pop();
+    }
+    hull
+}
+
+// which kinds of thing a collider counts as or looks for, any number of them at once
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Layers(pub u16);
+
+impl Layers {
+    pub const NONE: Self = Self(0);
+    pub const PLAYER: Self = Self(1 << 0);
+    pub const ENEMY: Self = Self(1 << 1);
+    pub const PLAYER_BULLET: Self = Self(1 << 2);
+    pub const ENEMY_BULLET: Self = Self(1 << 3);
+    pub const ASTEROID: Self = Self(1 << 4);
+    pub const PICKUP: Self = Self(1 << 5);
+    // guns and anything else that can be attached to a ship
+    pub const MODULE: Self = Self(1 << 6);
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl std::ops::BitOr for Layers {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+// how something touches other things. two colliders touch when either one's mask has the other's
+// layer, the other doesn't have to be looking back
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    // what this counts as
+    pub layer: Layers,
+    // what this looks for
+    pub mask: Layers,
+}
+
+impl Collider {
+    pub fn can_touch(&self, other: &Collider) -> bool {
+        self.mask.intersects(other.layer) || other.mask.intersects(self.layer)
+    }
+}
+
+// driven by this local player's inputs, attached guns take the player of the ship that grabbed them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputControlled {
+    pub player: usize,
+}
+
+// one per local player, outlives their ship so the hud and game over can still find it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlayerSlot {
+    pub index: usize,
+    pub lives: u32,
+}
+
+// on a player's slot while they wait for their next ship
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Respawning {
+    pub steps_left: u32,
+}
+
+// on a player's slot, how many lives their score has earned them so far
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtraLives {
+    pub awarded: u32,
+}
+
+// a ship that just came back or was just hit, asteroids pass through it until this runs out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Invulnerable {
+    pub steps_left: u32,
+}
+
+// ships, enemies and large asteroids take hits until this runs out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Health {
     pub hp: u32,
+    pub max_hp: u32,
+}
+
+impl Health {
+    pub const fn full(max_hp: u32) -> Self {
+        Self { hp: max_hp, max_hp }
+    }
+}
+
+// how much health a bullet takes off whatever it hits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Damage {
+    pub amount: u32,
 }
 
+#[derive(Clone, Copy)]
 pub struct VelocityUncapped;
 
+#[derive(Clone, Copy)]
 pub struct Physics {
     pub vel: Vec2,
     pub rot_vel: f32,
 }
 
+#[derive(Clone, Copy)]
 pub struct CaptureInPlayField;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -46,10 +287,12 @@ pub struct Score {
     pub score: u32,
 }
 
+#[derive(Clone, Copy)]
 pub struct OwnedBy {
     pub owner: Entity,
 }
 
+#[derive(Clone, Copy)]
 pub struct AttachedTo {
     pub entity: Entity,
     pub offset: Vec2,
@@ -60,12 +303,15 @@ pub struct GrabZone {
     pub radius: f32,
 }
 
+#[derive(Clone, Copy)]
 pub struct Attachable;
 
+#[derive(Clone, Copy)]
 pub struct WantsToGoTo {
     pub pos: Vec2,
 }
 
+#[derive(Clone, Copy)]
 pub struct LookAt {
     pub entity: Entity,
 }
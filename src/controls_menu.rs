@@ -0,0 +1,141 @@
+use crate::{
+    actions::{self, Action, Binding, ACTIONS},
+    modes::{self, Mode, ModeRegistry, Transition},
+    settings::Settings,
+    settings_menu,
+    state::{GameMode, State},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlsMenuItem {
+    Rebind(Action),
+    ResetToDefaults,
+    Back,
+}
+
+impl ControlsMenuItem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ControlsMenuItem::Rebind(action) => action.label(),
+            ControlsMenuItem::ResetToDefaults => "reset to defaults",
+            ControlsMenuItem::Back => "back",
+        }
+    }
+}
+
+pub fn controls_menu_items() -> Vec<ControlsMenuItem> {
+    let mut items: Vec<ControlsMenuItem> = ACTIONS
+        .iter()
+        .map(|action| ControlsMenuItem::Rebind(*action))
+        .collect();
+    items.push(ControlsMenuItem::ResetToDefaults);
+    items.push(ControlsMenuItem::Back);
+    items
+}
+
+// without these the menus could no longer be navigated, so they can't be left unbound
+fn is_required(action: Action) -> bool {
+    matches!(
+        action,
+        Action::Confirm | Action::Back | Action::MenuUp | Action::MenuDown
+    )
+}
+
+// resource, only meaningful while the controls screen is open
+#[derive(Default)]
+pub struct ControlsMenu {
+    pub selected: usize,
+    // the next key pressed is bound to the selected action
+    pub capturing: bool,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct ControlsInputs {
+    pub up: bool,
+    pub down: bool,
+    pub confirm: bool,
+    pub back: bool,
+    // removes every binding of the selected action
+    pub clear: bool,
+    // the binding pressed this frame while capturing
+    pub captured: Option<Binding>,
+}
+
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::Controls,
+        Mode {
+            on_enter,
+            on_exit: settings_menu::save,
+            ..Default::default()
+        },
+    );
+}
+
+fn on_enter(state: &mut State) {
+    state.resources.insert(ControlsMenu::default());
+}
+
+pub fn handle_inputs(state: &mut State, inputs: ControlsInputs) {
+    let items = controls_menu_items();
+    let (selected, capturing) = match state.resources.get_mut::<ControlsMenu>() {
+        Some(menu) => (menu.selected, menu.capturing),
+        None => return,
+    };
+
+    if capturing {
+        // back cancels rather than being captured, so the menu can always be left
+        if inputs.back || inputs.captured.is_some() {
+            if let (Some(binding), ControlsMenuItem::Rebind(action)) =
+                (inputs.captured, items[selected])
+            {
+                if let Some(mut settings) = state.resources.get_mut::<Settings>() {
+                    actions::add_binding(&mut settings.controls.bindings, action, binding);
+                }
+            }
+            if let Some(mut menu) = state.resources.get_mut::<ControlsMenu>() {
+                menu.capturing = false;
+            }
+        }
+        return;
+    }
+
+    if inputs.back {
+        modes::request(state, Transition::Pop);
+        return;
+    }
+
+    let selected = match state.resources.get_mut::<ControlsMenu>() {
+        Some(mut menu) => {
+            if inputs.up {
+                menu.selected = (menu.selected + items.len() - 1) % items.len();
+            }
+            if inputs.down {
+                menu.selected = (menu.selected + 1) % items.len();
+            }
+            if inputs.confirm {
+                menu.capturing = matches!(items[menu.selected], ControlsMenuItem::Rebind(_));
+            }
+            menu.selected
+        }
+        None => return,
+    };
+
+    let item = items[selected];
+    if inputs.confirm && item == ControlsMenuItem::Back {
+        modes::request(state, Transition::Pop);
+        return;
+    }
+
+    if let Some(mut settings) = state.resources.get_mut::<Settings>() {
+        match item {
+            ControlsMenuItem::Rebind(action) if inputs.clear && !is_required(action) => {
+                settings.controls.bindings.insert(action, Vec::new());
+            }
+            ControlsMenuItem::ResetToDefaults if inputs.confirm => {
+                settings.controls.bindings = actions::default_bindings();
+            }
+            _ => {}
+        }
+    }
+}
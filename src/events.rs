@@ -0,0 +1,27 @@
+use glam::Vec2;
+use legion::Entity;
+
+// something's health ran out this step, it is still in the world until the end of the step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    // whoever fired the bullet that finished it off
+    pub killer: Option<Entity>,
+}
+
+// resource, filled by the collisions and emptied once the deaths have been dealt with
+pub type DeathEvents = Vec<DeathEvent>;
+
+// two colliders touched this step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CollisionEvent {
+    pub a: Entity,
+    pub b: Entity,
+    // which way b has to move to get clear of a
+    pub normal: Vec2,
+    // how far it has to move, 0.0 when they only just touch or cross without either being inside
+    pub depth: f32,
+}
+
+// resource, refilled by the collision pass every step for the systems that respond to collisions
+pub type CollisionEvents = Vec<CollisionEvent>;
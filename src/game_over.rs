@@ -1,15 +1,24 @@
-use raylib::RaylibHandle;
+use crate::{
+    modes::{self, Mode, ModeRegistry, Transition},
+    name_entry, playing,
+    state::{GameMode, State},
+};
 
-use crate::state::{GameMode, State};
-
-pub fn process_events_and_input(rl: &mut RaylibHandle, state: &mut State) {
-    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_ESCAPE) {
-        state.running = false;
-    }
+// pushed over playing when the last player dies, the world keeps drifting underneath the message
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::GameOver,
+        Mode {
+            step: playing::step,
+            render: playing::render,
+            ..Default::default()
+        },
+    );
+}
 
-    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_SPACE) {
-        if let Some(mut transition_to) = state.resources.get_mut::<Option<GameMode>>() {
-            *transition_to = Option::Some(GameMode::Title);
-        }
+/** players whose score made the table enter their initials first, otherwise it is back to the title */
+pub fn finish(state: &mut State) {
+    if !name_entry::begin(state) {
+        modes::request(state, Transition::Reset(GameMode::Title));
     }
 }
@@ -0,0 +1,404 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    modes::{self, Mode, ModeRegistry, Transition},
+    replay::invalid_data,
+    settings,
+    state::{GameMode, State, FRAMES_PER_SECOND},
+};
+
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const INITIALS_LEN: usize = 3;
+const HIGH_SCORES_VERSION: u32 = 1;
+const FILE_NAME: &str = "high_scores.txt";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    // seconds since the unix epoch when the run ended
+    pub date: u64,
+    // so the run can be played again with --seed
+    pub seed: u64,
+    // how many steps the run lasted
+    pub steps: u32,
+}
+
+// resource, the best scores first, loaded at startup and saved whenever one is added
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HighScores(pub Vec<HighScore>);
+
+// resource, where the high score table is saved
+pub struct HighScoresPath(pub PathBuf);
+
+// resource, only meaningful while the table is shown
+#[derive(Default)]
+pub struct HighScoresView {
+    // the entry that was just added, drawn highlighted
+    pub highlighted: Option<usize>,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct HighScoresInputs {
+    pub confirm: bool,
+    pub back: bool,
+}
+
+impl HighScore {
+    /** year-month-day in utc */
+    pub fn date_label(&self) -> String {
+        let (year, month, day) = civil_from_days((self.date / SECONDS_PER_DAY) as i64);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /** minutes and seconds */
+    pub fn duration_label(&self) -> String {
+        let seconds = self.steps / FRAMES_PER_SECOND;
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+impl HighScores {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    /** one score per line after the version, in the order they are written in to_text */
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        let version = lines
+            .next()
+            .and_then(|line| line.strip_prefix("version"))
+            .and_then(|line| line.trim().strip_prefix('='))
+            .and_then(|version| version.trim().parse::<u32>().ok());
+        match version {
+            Some(version) if version > 0 && version <= HIGH_SCORES_VERSION => {}
+            Some(version) => {
+                return Err(invalid_data(&format!(
+                    "unsupported high score version {}",
+                    version
+                )))
+            }
+            None => return Err(invalid_data("high score file has no valid version")),
+        }
+
+        let mut high_scores = HighScores::default();
+        for line in lines {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let high_score = match fields[..] {
+                [name, score, date, seed, steps] => HighScore {
+                    name: clean_name(name),
+                    score: parse_field(score, line)?,
+                    date: parse_field(date, line)?,
+                    seed: parse_field(seed, line)?,
+                    steps: parse_field(steps, line)?,
+                },
+                _ => return Err(invalid_data(&format!("bad high score line: {}", line))),
+            };
+            high_scores.insert(high_score);
+        }
+        Ok(high_scores)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "version = {}\n\
+             \n\
+             # name, score, date in seconds since 1970, seed, steps lasted\n",
+            HIGH_SCORES_VERSION
+        );
+        for high_score in self.0.iter() {
+            text += &format!(
+                "{}, {}, {}, {}, {}\n",
+                high_score.name,
+                high_score.score,
+                high_score.date,
+                high_score.seed,
+                high_score.steps
+            );
+        }
+        text
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        if score == 0 {
+            return false;
+        }
+        match self.0.get(MAX_HIGH_SCORES - 1) {
+            Some(lowest) => score > lowest.score,
+            None => true,
+        }
+    }
+
+    /** ties go below the scores already there. returns where it went, if it made the table */
+    pub fn insert(&mut self, high_score: HighScore) -> Option<usize> {
+        let rank = self
+            .0
+            .iter()
+            .position(|existing| high_score.score > existing.score)
+            .unwrap_or(self.0.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.0.insert(rank, high_score);
+        self.0.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    Some(settings::config_dir()?.join(FILE_NAME))
+}
+
+/** a missing file means nobody has set a score yet, anything else wrong with it is reported */
+pub fn load_or_default(path: &Path) -> HighScores {
+    match HighScores::load(path) {
+        Ok(high_scores) => high_scores,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => HighScores::default(),
+        Err(e) => {
+            println!("Error loading high scores {}: {}", path.display(), e);
+            HighScores::default()
+        }
+    }
+}
+
+pub fn save(state: &State) {
+    let path = match state.resources.get::<HighScoresPath>() {
+        Some(path) => path.0.clone(),
+        None => return,
+    };
+    if let Some(high_scores) = state.resources.get::<HighScores>() {
+        if let Err(e) = high_scores.save(&path) {
+            println!("Error saving high scores {}: {}", path.display(), e);
+        }
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::HighScores,
+        Mode {
+            on_enter,
+            ..Default::default()
+        },
+    );
+}
+
+fn on_enter(state: &mut State) {
+    // name entry leaves the new entry's view in place before switching over
+    if !state.resources.contains::<HighScoresView>() {
+        state.resources.insert(HighScoresView::default());
+    }
+}
+
+/** shown over the title it goes back there, after a run it ends it */
+pub fn handle_inputs(state: &mut State, inputs: HighScoresInputs) {
+    if !inputs.confirm && !inputs.back {
+        return;
+    }
+    state.resources.remove::<HighScoresView>();
+    let below = state.mode_stack.iter().rev().nth(1).copied();
+    if below == Some(GameMode::Title) {
+        modes::request(state, Transition::Pop);
+    } else {
+        modes::request(state, Transition::Reset(GameMode::Title));
+    }
+}
+
+// initials are letters only, anything else in the file becomes a space
+fn clean_name(name: &str) -> String {
+    name.chars()
+        .chain(std::iter::repeat(' '))
+        .take(INITIALS_LEN)
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                c.to_ascii_uppercase()
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+fn parse_field<T: std::str::FromStr>(field: &str, line: &str) -> io::Result<T> {
+    field
+        .parse()
+        .map_err(|_| invalid_data(&format!("bad high score line: {}", line)))
+}
+
+// days since 1970-01-01 to a date, from Howard Hinnant's date algorithms
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn high_score(name: &str, score: u32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            date: 1_700_000_000,
+            seed: 42,
+            steps: 3600,
+        }
+    }
+
+    fn names(high_scores: &HighScores) -> Vec<&str> {
+        high_scores
+            .0
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    /** a full table scoring 100 down to 10 */
+    fn full_table() -> HighScores {
+        let mut high_scores = HighScores::default();
+        for rank in 0..MAX_HIGH_SCORES as u32 {
+            high_scores.insert(high_score("AAA", 100 - rank * 10));
+        }
+        high_scores
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(high_score("ABC", 30));
+        high_scores.insert(HighScore {
+            name: "X Y".to_string(),
+            score: 12,
+            date: 0,
+            seed: u64::MAX,
+            steps: 0,
+        });
+        let parsed = HighScores::parse(&high_scores.to_text()).unwrap();
+        assert_eq!(parsed, high_scores);
+    }
+
+    #[test]
+    fn parsing_tidies_names_and_sorts() {
+        let text = "version = 1\n\
+                    # a comment\n\
+                    \n\
+                    ab, 5, 1, 2, 3\n\
+                    x1yz, 9, 1, 2, 3\n";
+        let high_scores = HighScores::parse(text).unwrap();
+        assert_eq!(names(&high_scores), vec!["X Y", "AB "]);
+    }
+
+    #[test]
+    fn bad_files_are_rejected() {
+        for text in [
+            "",
+            "ABC, 5, 1, 2, 3\n",
+            "version = 0\n",
+            "version = 2\n",
+            "version = one\n",
+            "version = 1\nABC, 5, 1, 2\n",
+            "version = 1\nABC, 5, 1, 2, 3, 4\n",
+            "version = 1\nABC, -5, 1, 2, 3\n",
+            "version = 1\nABC, 5, yesterday, 2, 3\n",
+        ] {
+            assert!(HighScores::parse(text).is_err(), "{:?}", text);
+        }
+        assert_eq!(
+            HighScores::parse("version = 1\n").unwrap(),
+            HighScores::default()
+        );
+    }
+
+    #[test]
+    fn inserting_keeps_the_best_first_and_ties_go_below() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.insert(high_score("MID", 20)), Some(0));
+        assert_eq!(high_scores.insert(high_score("TOP", 30)), Some(0));
+        assert_eq!(high_scores.insert(high_score("LOW", 10)), Some(2));
+        assert_eq!(high_scores.insert(high_score("TIE", 20)), Some(2));
+        assert_eq!(names(&high_scores), vec!["TOP", "MID", "TIE", "LOW"]);
+    }
+
+    #[test]
+    fn the_table_stays_capped() {
+        let mut high_scores = full_table();
+        assert_eq!(high_scores.0.len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.insert(high_score("NEW", 55)), Some(5));
+        assert_eq!(high_scores.0.len(), MAX_HIGH_SCORES);
+        // the old lowest fell off
+        assert_eq!(high_scores.0.last().unwrap().score, 20);
+        assert_eq!(high_scores.insert(high_score("OUT", 5)), None);
+        assert_eq!(high_scores.0.len(), MAX_HIGH_SCORES);
+    }
+
+    #[test]
+    fn qualifying_needs_more_than_the_lowest_of_a_full_table() {
+        assert!(!HighScores::default().qualifies(0));
+        assert!(HighScores::default().qualifies(1));
+
+        let mut high_scores = full_table();
+        assert!(high_scores.qualifies(11));
+        assert!(!high_scores.qualifies(10));
+        assert!(!high_scores.qualifies(9));
+        // a tie with the lowest wouldn't make it in either
+        assert_eq!(high_scores.insert(high_score("TIE", 10)), None);
+
+        // one short of full, anything scoring at all gets in
+        high_scores.0.pop();
+        assert!(high_scores.qualifies(1));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        let entry = HighScore {
+            date: 19_782 * SECONDS_PER_DAY + SECONDS_PER_DAY - 1,
+            steps: 125 * FRAMES_PER_SECOND,
+            ..high_score("ABC", 1)
+        };
+        assert_eq!(entry.date_label(), "2024-02-29");
+        assert_eq!(entry.duration_label(), "2:05");
+    }
+}
@@ -0,0 +1,33 @@
+use glam::UVec2;
+
+pub mod actions;
+pub mod audio_playing;
+pub mod cli;
+pub mod components;
+pub mod controls_menu;
+pub mod events;
+pub mod game_over;
+pub mod high_scores;
+pub mod message_stream;
+pub mod modes;
+pub mod name_entry;
+pub mod narrowphase;
+pub mod netplay;
+pub mod pause;
+pub mod playing;
+pub mod rendering;
+pub mod replay;
+pub mod rng;
+pub mod schedules;
+pub mod settings;
+pub mod settings_menu;
+pub mod snapshot;
+pub mod spatial_hash;
+pub mod state;
+pub mod systems;
+pub mod timer;
+pub mod title;
+pub mod topology;
+pub mod torus;
+
+pub const DIMS: UVec2 = UVec2::new(240, 160);
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use crate::state::{GameMode, State};
+
+// resource, systems and frontends request mode changes through this and they are applied between frames
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    Push(GameMode),
+    Pop,
+    Replace(GameMode),
+    // clear the whole stack, then push
+    Reset(GameMode),
+}
+
+pub type ModeRegistry = HashMap<GameMode, Mode>;
+
+#[derive(Clone, Copy)]
+pub struct Mode {
+    pub step: fn(&mut State),
+    pub render: fn(&mut State, f32),
+    pub on_enter: fn(&mut State),
+    pub on_exit: fn(&mut State),
+    pub on_pause: fn(&mut State),
+    pub on_resume: fn(&mut State),
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Self {
+            step: |_| {},
+            render: |_, _| {},
+            on_enter: |_| {},
+            on_exit: |_| {},
+            on_pause: |_| {},
+            on_resume: |_| {},
+        }
+    }
+}
+
+pub fn request(state: &mut State, transition: Transition) {
+    state.resources.insert(Some(transition));
+}
+
+pub fn current(state: &State) -> Option<GameMode> {
+    state.mode_stack.last().copied()
+}
+
+pub fn is_transition_pending(state: &State) -> bool {
+    matches!(
+        state.resources.get::<Option<Transition>>().map(|t| *t),
+        Some(Some(_))
+    )
+}
+
+/** applies the pending transition if there is one. pushing the mode that is already on top does nothing */
+pub fn apply_transition(state: &mut State) {
+    let transition = match state.resources.get_mut::<Option<Transition>>() {
+        Some(mut transition) => transition.take(),
+        None => None,
+    };
+
+    match transition {
+        Some(Transition::Push(game_mode)) => {
+            if current(state) == Some(game_mode) {
+                return;
+            }
+            if let Some(top) = current(state) {
+                (mode(state, top).on_pause)(state);
+            }
+            push(state, game_mode);
+        }
+        Some(Transition::Pop) => {
+            pop(state);
+            if let Some(top) = current(state) {
+                (mode(state, top).on_resume)(state);
+            }
+        }
+        Some(Transition::Replace(game_mode)) => {
+            pop(state);
+            push(state, game_mode);
+        }
+        Some(Transition::Reset(game_mode)) => {
+            exit_all(state);
+            push(state, game_mode);
+        }
+        None => {}
+    }
+}
+
+pub fn step(state: &mut State) {
+    if let Some(top) = current(state) {
+        (mode(state, top).step)(state);
+    }
+}
+
+pub fn render(state: &mut State, alpha: f32) {
+    if let Some(top) = current(state) {
+        (mode(state, top).render)(state, alpha);
+    }
+}
+
+pub fn exit_all(state: &mut State) {
+    while !state.mode_stack.is_empty() {
+        pop(state);
+    }
+}
+
+fn push(state: &mut State, game_mode: GameMode) {
+    state.mode_stack.push(game_mode);
+    (mode(state, game_mode).on_enter)(state);
+}
+
+fn pop(state: &mut State) {
+    if let Some(top) = state.mode_stack.pop() {
+        (mode(state, top).on_exit)(state);
+    }
+}
+
+fn mode(state: &State, game_mode: GameMode) -> Mode {
+    state.modes.get(&game_mode).copied().unwrap_or_default()
+}
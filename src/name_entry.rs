@@ -0,0 +1,174 @@
+use legion::IntoQuery;
+
+use crate::{
+    components::{PlayerSlot, Score},
+    high_scores::{self, HighScore, HighScores, HighScoresView, INITIALS_LEN},
+    modes::{self, Mode, ModeRegistry, Transition},
+    state::{GameMode, RunConfig, RunSteps, State},
+};
+
+// a player whose score made the table and who still has to enter their initials
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingHighScore {
+    pub player: usize,
+    pub score: u32,
+}
+
+// resource, only meaningful while initials are being entered
+pub struct NameEntry {
+    // the first one is entering their initials now
+    pub pending: Vec<PendingHighScore>,
+    pub initials: [u8; INITIALS_LEN],
+    pub cursor: usize,
+    // the rank of the last entry added, highlighted on the table afterwards
+    pub last_rank: Option<usize>,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct NameEntryInputs {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub confirm: bool,
+    pub back: bool,
+    // a letter typed straight in, sets the current one and moves on
+    pub typed: Option<char>,
+}
+
+impl NameEntry {
+    fn new(pending: Vec<PendingHighScore>) -> Self {
+        Self {
+            pending,
+            initials: [b'A'; INITIALS_LEN],
+            cursor: 0,
+            last_rank: None,
+        }
+    }
+
+    pub fn initials(&self) -> String {
+        self.initials.iter().map(|c| *c as char).collect()
+    }
+}
+
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::NameEntry,
+        Mode {
+            on_exit,
+            ..Default::default()
+        },
+    );
+}
+
+fn on_exit(state: &mut State) {
+    state.resources.remove::<NameEntry>();
+}
+
+/** starts name entry for every player whose score made the table, returns false if nobody's did */
+pub fn begin(state: &mut State) -> bool {
+    let mut pending: Vec<PendingHighScore> = <(&PlayerSlot, &Score)>::query()
+        .iter(&state.ecs)
+        .map(|(player_slot, score)| PendingHighScore {
+            player: player_slot.index,
+            score: score.score,
+        })
+        .collect();
+    pending.sort_by_key(|pending| pending.player);
+    if let Some(high_scores) = state.resources.get::<HighScores>() {
+        pending.retain(|pending| high_scores.qualifies(pending.score));
+    }
+    if pending.is_empty() {
+        return false;
+    }
+    state.resources.insert(NameEntry::new(pending));
+    modes::request(state, Transition::Replace(GameMode::NameEntry));
+    true
+}
+
+pub fn handle_inputs(state: &mut State, inputs: NameEntryInputs) {
+    let done = match state.resources.get_mut::<NameEntry>() {
+        Some(mut name_entry) => {
+            let cursor = name_entry.cursor;
+            let typed = inputs.typed.filter(char::is_ascii_alphabetic);
+            let letter = &mut name_entry.initials[cursor];
+            if inputs.up {
+                *letter = cycle_letter(*letter, 1);
+            }
+            if inputs.down {
+                *letter = cycle_letter(*letter, 25);
+            }
+            if let Some(typed) = typed {
+                *letter = typed.to_ascii_uppercase() as u8;
+            }
+
+            if inputs.left || inputs.back {
+                name_entry.cursor = cursor.saturating_sub(1);
+                false
+            } else if inputs.right || typed.is_some() || inputs.confirm {
+                name_entry.cursor = (cursor + 1).min(INITIALS_LEN - 1);
+                inputs.confirm && cursor == INITIALS_LEN - 1
+            } else {
+                false
+            }
+        }
+        None => return,
+    };
+    if done {
+        add_entry(state);
+    }
+}
+
+// adds the current player's entry, then moves on to the next player or the table
+fn add_entry(state: &mut State) {
+    let seed = state
+        .resources
+        .get::<RunConfig>()
+        .map(|run_config| run_config.seed)
+        .unwrap_or_default();
+    let steps = state
+        .resources
+        .get::<RunSteps>()
+        .map(|run_steps| run_steps.0)
+        .unwrap_or_default();
+
+    let finished = {
+        let (mut name_entry, mut high_scores) = match (
+            state.resources.get_mut::<NameEntry>(),
+            state.resources.get_mut::<HighScores>(),
+        ) {
+            (Some(name_entry), Some(high_scores)) => (name_entry, high_scores),
+            _ => return,
+        };
+        let pending = name_entry.pending.remove(0);
+        let rank = high_scores.insert(HighScore {
+            name: name_entry.initials(),
+            score: pending.score,
+            date: high_scores::now(),
+            seed,
+            steps,
+        });
+        name_entry.last_rank = rank.or(name_entry.last_rank);
+
+        // an earlier player's entry may have pushed a later one off the table
+        name_entry
+            .pending
+            .retain(|pending| high_scores.qualifies(pending.score));
+        name_entry.initials = [b'A'; INITIALS_LEN];
+        name_entry.cursor = 0;
+        name_entry
+            .pending
+            .is_empty()
+            .then_some(name_entry.last_rank)
+    };
+    high_scores::save(state);
+
+    if let Some(highlighted) = finished {
+        state.resources.insert(HighScoresView { highlighted });
+        modes::request(state, Transition::Replace(GameMode::HighScores));
+    }
+}
+
+fn cycle_letter(letter: u8, offset: u8) -> u8 {
+    b'A' + (letter.saturating_sub(b'A') + offset) % 26
+}
@@ -0,0 +1,688 @@
+use glam::Vec2;
+
+use crate::components::{CTransform, Collider, ColliderShape, MAX_POLYGON_POINTS};
+
+// two parallel lines never meet, and nearly parallel ones meet too far away to matter
+const PARALLEL_EPSILON: f32 = 1e-6;
+
+// a collider placed in the world
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    Polygon {
+        center: Vec2,
+        points: [Vec2; MAX_POLYGON_POINTS],
+        len: usize,
+    },
+    Segment {
+        start: Vec2,
+        end: Vec2,
+    },
+}
+
+// how two shapes overlap
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    // which way the second shape has to move to get clear of the first
+    pub normal: Vec2,
+    // how far it has to move, 0.0 when they only just touch or cross without either being inside
+    pub depth: f32,
+}
+
+impl Shape {
+    pub fn new(collider: &Collider, transform: &CTransform) -> Self {
+        let to_world =
+            |local: Vec2| transform.pos + transform.rot * local.x + transform.rot.perp() * local.y;
+        match &collider.shape {
+            ColliderShape::Circle { radius } => Shape::Circle {
+                center: transform.pos,
+                radius: *radius,
+            },
+            ColliderShape::Polygon { points, len } => {
+                let mut world_points = [Vec2::ZERO; MAX_POLYGON_POINTS];
+                for (world_point, point) in world_points.iter_mut().zip(&points[..*len]) {
+                    *world_point = to_world(*point);
+                }
+                Shape::Polygon {
+                    center: transform.pos,
+                    points: world_points,
+                    len: *len,
+                }
+            }
+            ColliderShape::Segment { end } => Shape::Segment {
+                start: transform.pos,
+                end: to_world(*end),
+            },
+        }
+    }
+
+    /** a point, for anything that has no collider */
+    pub fn point(pos: Vec2) -> Self {
+        Shape::Circle {
+            center: pos,
+            radius: 0.0,
+        }
+    }
+
+    /** the corners of a box around all of it */
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        match self {
+            Shape::Circle { center, radius } => (
+                *center - Vec2::splat(*radius),
+                *center + Vec2::splat(*radius),
+            ),
+            Shape::Polygon {
+                center,
+                points,
+                len,
+            } => points[..*len]
+                .iter()
+                .fold((*center, *center), |(min, max), point| {
+                    (min.min(*point), max.max(*point))
+                }),
+            Shape::Segment { start, end } => (start.min(*end), start.max(*end)),
+        }
+    }
+
+    /** the same shape moved over, for the copies of it across the edges of the play field */
+    pub fn translated(&self, by: Vec2) -> Self {
+        match *self {
+            Shape::Circle { center, radius } => Shape::Circle {
+                center: center + by,
+                radius,
+            },
+            Shape::Polygon {
+                center,
+                mut points,
+                len,
+            } => {
+                for point in points[..len].iter_mut() {
+                    *point += by;
+                }
+                Shape::Polygon {
+                    center: center + by,
+                    points,
+                    len,
+                }
+            }
+            Shape::Segment { start, end } => Shape::Segment {
+                start: start + by,
+                end: end + by,
+            },
+        }
+    }
+
+    fn center(&self) -> Vec2 {
+        match self {
+            Shape::Circle { center, .. } | Shape::Polygon { center, .. } => *center,
+            Shape::Segment { start, end } => (*start + *end) / 2.0,
+        }
+    }
+}
+
+/** whether the two shapes overlap, and how to pull them apart if they do */
+pub fn contact(a: &Shape, b: &Shape) -> Option<Contact> {
+    match (a, b) {
+        (
+            Shape::Circle {
+                center: a_center,
+                radius: a_radius,
+            },
+            Shape::Circle {
+                center: b_center,
+                radius: b_radius,
+            },
+        ) => {
+            let offset = *b_center - *a_center;
+            let distance = offset.length();
+            (distance <= a_radius + b_radius).then(|| Contact {
+                normal: offset.try_normalize().unwrap_or(Vec2::X),
+                depth: a_radius + b_radius - distance,
+            })
+        }
+        (Shape::Circle { center, radius }, Shape::Segment { start, end }) => {
+            let offset = closest_on_segment(*center, *start, *end) - *center;
+            let distance = offset.length();
+            (distance <= *radius).then(|| Contact {
+                normal: offset.try_normalize().unwrap_or(Vec2::X),
+                depth: radius - distance,
+            })
+        }
+        (Shape::Circle { center, radius }, Shape::Polygon { points, len, .. }) => {
+            let outline = &points[..*len];
+            let closest = closest_on_outline(*center, outline);
+            let offset = closest - *center;
+            let distance = offset.length();
+            if contains(outline, *center) {
+                // the circle has to come back out through the nearest edge
+                Some(Contact {
+                    normal: (-offset).try_normalize().unwrap_or(Vec2::X),
+                    depth: radius + distance,
+                })
+            } else {
+                (distance <= *radius).then(|| Contact {
+                    normal: offset.try_normalize().unwrap_or(Vec2::X),
+                    depth: radius - distance,
+                })
+            }
+        }
+        (
+            Shape::Segment {
+                start: a_start,
+                end: a_end,
+            },
+            Shape::Segment {
+                start: b_start,
+                end: b_end,
+            },
+        ) => {
+            if segment_intersection(*a_start, *a_end, *b_start, *b_end).is_none()
+                && !overlap_along_line(*a_start, *a_end, *b_start, *b_end)
+            {
+                return None;
+            }
+            Some(Contact {
+                normal: away_from_line(*a_start, *a_end, b.center()),
+                depth: 0.0,
+            })
+        }
+        (
+            Shape::Segment { start, end },
+            Shape::Polygon {
+                points,
+                len,
+                center,
+            },
+        ) => {
+            let outline = &points[..*len];
+            let crosses = edges(outline)
+                .any(|(from, to)| segment_intersection(*start, *end, from, to).is_some());
+            let depth = [*start, *end]
+                .into_iter()
+                .filter(|point| contains(outline, *point))
+                .map(|point| (closest_on_outline(point, outline) - point).length())
+                .fold(None, |deepest: Option<f32>, depth| {
+                    Some(deepest.map_or(depth, |deepest| deepest.max(depth)))
+                });
+            if !crosses && depth.is_none() {
+                return None;
+            }
+            let nearest = closest_on_segment(*center, *start, *end);
+            Some(Contact {
+                normal: (*center - nearest).try_normalize().unwrap_or(Vec2::X),
+                depth: depth.unwrap_or(0.0),
+            })
+        }
+        (
+            Shape::Polygon {
+                points: a_points,
+                len: a_len,
+                ..
+            },
+            Shape::Polygon {
+                points: b_points,
+                len: b_len,
+                ..
+            },
+        ) => {
+            let (a_outline, b_outline) = (&a_points[..*a_len], &b_points[..*b_len]);
+            // both are convex, so they overlap unless one of their edges has a line square to it
+            // that they don't both cover, and they come apart quickest along the one they cover
+            // the least of
+            let mut shallowest: Option<Contact> = None;
+            for (from, to) in edges(a_outline).chain(edges(b_outline)) {
+                let axis = match (to - from).perp().try_normalize() {
+                    Some(axis) => axis,
+                    None => continue,
+                };
+                let (a_min, a_max) = project(a_outline, axis);
+                let (b_min, b_max) = project(b_outline, axis);
+                // b out past a's far side, or back past its near side
+                let (depth, normal) = if a_max - b_min <= b_max - a_min {
+                    (a_max - b_min, axis)
+                } else {
+                    (b_max - a_min, -axis)
+                };
+                if depth < 0.0 {
+                    return None;
+                }
+                if shallowest.is_none_or(|shallowest| depth < shallowest.depth) {
+                    shallowest = Some(Contact { normal, depth });
+                }
+            }
+            shallowest
+        }
+        // the rest are the ones above the other way round
+        (Shape::Segment { .. }, Shape::Circle { .. })
+        | (Shape::Polygon { .. }, Shape::Circle { .. })
+        | (Shape::Polygon { .. }, Shape::Segment { .. }) => contact(b, a).map(|contact| Contact {
+            normal: -contact.normal,
+            depth: contact.depth,
+        }),
+    }
+}
+
+/** how far from start to end, 0.0 to 1.0, a circle moving between them first touches the shape */
+pub fn sweep(start: Vec2, end: Vec2, radius: f32, shape: &Shape) -> Option<f32> {
+    match shape {
+        Shape::Circle {
+            center,
+            radius: shape_radius,
+        } => sweep_circle(start, end, *center, radius + shape_radius),
+        Shape::Segment {
+            start: from,
+            end: to,
+        } => sweep_capsule(start, end, *from, *to, radius),
+        Shape::Polygon { points, len, .. } => {
+            let outline = &points[..*len];
+            if contains(outline, start) {
+                return Some(0.0);
+            }
+            edges(outline)
+                .filter_map(|(from, to)| sweep_capsule(start, end, from, to, radius))
+                .reduce(f32::min)
+        }
+    }
+}
+
+/** how far from start to end, 0.0 to 1.0, the moving point first comes within radius of center */
+fn sweep_circle(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let to_start = start - center;
+    // already touching at the start of the step
+    let c = to_start.length_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+    let path = end - start;
+    let a = path.length_squared();
+    let b = to_start.dot(path);
+    // standing still or moving away
+    if a == 0.0 || b >= 0.0 {
+        return None;
+    }
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / a;
+    (t <= 1.0).then_some(t)
+}
+
+// the moving point against everything within radius of the line from from to to
+fn sweep_capsule(start: Vec2, end: Vec2, from: Vec2, to: Vec2, radius: f32) -> Option<f32> {
+    if (closest_on_segment(start, from, to) - start).length() <= radius {
+        return Some(0.0);
+    }
+    let side = (to - from).perp().try_normalize().unwrap_or(Vec2::ZERO) * radius;
+    [
+        sweep_circle(start, end, from, radius),
+        sweep_circle(start, end, to, radius),
+        segment_intersection(start, end, from + side, to + side).map(|(t, _)| t),
+        segment_intersection(start, end, from - side, to - side).map(|(t, _)| t),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(f32::min)
+}
+
+/** how far along each, 0.0 to 1.0, the two segments cross, if they do */
+fn segment_intersection(
+    a_start: Vec2,
+    a_end: Vec2,
+    b_start: Vec2,
+    b_end: Vec2,
+) -> Option<(f32, f32)> {
+    let a = a_end - a_start;
+    let b = b_end - b_start;
+    let denominator = a.perp_dot(b);
+    if denominator.abs() < PARALLEL_EPSILON {
+        return None;
+    }
+    let between = b_start - a_start;
+    let t = between.perp_dot(b) / denominator;
+    let u = between.perp_dot(a) / denominator;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some((t, u))
+}
+
+// whether the segments lie along the same line and share some of it, which crossing doesn't find
+fn overlap_along_line(a_start: Vec2, a_end: Vec2, b_start: Vec2, b_end: Vec2) -> bool {
+    let line = a_end - a_start;
+    let length_squared = line.length_squared();
+    if length_squared == 0.0 {
+        return false;
+    }
+    let off_line = |point: Vec2| line.perp_dot(point - a_start).abs() / length_squared.sqrt();
+    if off_line(b_start) > PARALLEL_EPSILON || off_line(b_end) > PARALLEL_EPSILON {
+        return false;
+    }
+    let along = |point: Vec2| (point - a_start).dot(line) / length_squared;
+    let (b_start, b_end) = (along(b_start), along(b_end));
+    b_start.min(b_end) <= 1.0 && b_start.max(b_end) >= 0.0
+}
+
+// from how far along the axis the outline starts to how far it ends
+fn project(outline: &[Vec2], axis: Vec2) -> (f32, f32) {
+    outline
+        .iter()
+        .map(|point| point.dot(axis))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), along| {
+            (min.min(along), max.max(along))
+        })
+}
+
+fn closest_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let line = end - start;
+    let length_squared = line.length_squared();
+    if length_squared == 0.0 {
+        return start;
+    }
+    let t = ((point - start).dot(line) / length_squared).clamp(0.0, 1.0);
+    start + line * t
+}
+
+fn closest_on_outline(point: Vec2, outline: &[Vec2]) -> Vec2 {
+    edges(outline)
+        .map(|(from, to)| closest_on_segment(point, from, to))
+        .min_by(|a, b| {
+            (*a - point)
+                .length_squared()
+                .total_cmp(&(*b - point).length_squared())
+        })
+        .unwrap_or(point)
+}
+
+// counts the edges a line out to the right crosses, odd is inside
+fn contains(outline: &[Vec2], point: Vec2) -> bool {
+    edges(outline)
+        .filter(|(from, to)| {
+            (from.y > point.y) != (to.y > point.y)
+                && point.x < from.x + (point.y - from.y) / (to.y - from.y) * (to.x - from.x)
+        })
+        .count()
+        % 2
+        == 1
+}
+
+fn edges(outline: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    outline
+        .iter()
+        .zip(outline.iter().cycle().skip(1))
+        .map(|(from, to)| (*from, *to))
+}
+
+// square to the line, on the side the point is
+fn away_from_line(start: Vec2, end: Vec2, point: Vec2) -> Vec2 {
+    let normal = (end - start).perp().try_normalize().unwrap_or(Vec2::X);
+    if normal.dot(point - start) < 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Layers, ASTEROID_OUTLINE};
+
+    fn at(collider_shape: ColliderShape, pos: Vec2) -> Shape {
+        let collider = Collider {
+            shape: collider_shape,
+            layer: Layers::NONE,
+            mask: Layers::NONE,
+        };
+        Shape::new(&collider, &CTransform { pos, rot: Vec2::X })
+    }
+
+    fn circle(pos: Vec2, radius: f32) -> Shape {
+        at(ColliderShape::circle(radius), pos)
+    }
+
+    fn square(pos: Vec2, half: f32) -> Shape {
+        at(
+            ColliderShape::polygon(&[
+                Vec2::new(-half, -half),
+                Vec2::new(half, -half),
+                Vec2::new(half, half),
+                Vec2::new(-half, half),
+            ]),
+            pos,
+        )
+    }
+
+    fn segment(start: Vec2, end: Vec2) -> Shape {
+        at(ColliderShape::segment(end - start), start)
+    }
+
+    fn assert_contact(a: &Shape, b: &Shape, normal: Vec2, depth: f32) {
+        let contact = contact(a, b).expect("they touch");
+        assert!(
+            (contact.normal - normal).length() < 1e-5 && (contact.depth - depth).abs() < 1e-5,
+            "{:?} rather than {:?} by {}",
+            contact,
+            normal,
+            depth
+        );
+        // the same the other way round, pushed back the other way
+        let other_way = super::contact(b, a).expect("they touch the other way round");
+        assert!((other_way.normal + normal).length() < 1e-5);
+        assert!((other_way.depth - depth).abs() < 1e-5);
+    }
+
+    fn assert_apart(a: &Shape, b: &Shape) {
+        assert_eq!(contact(a, b), None);
+        assert_eq!(contact(b, a), None);
+    }
+
+    fn assert_swept(t: Option<f32>, expected: f32) {
+        let t = t.expect("it hits");
+        assert!(
+            (t - expected).abs() < 1e-5,
+            "{} rather than {}",
+            t,
+            expected
+        );
+    }
+
+    #[test]
+    fn circles() {
+        let a = circle(Vec2::ZERO, 4.0);
+        assert_contact(&a, &circle(Vec2::new(6.0, 0.0), 2.0), Vec2::X, 0.0);
+        assert_apart(&a, &circle(Vec2::new(0.0, 7.0), 2.0));
+        // one inside the other comes out the nearer way
+        assert_contact(&a, &circle(Vec2::new(0.0, 1.0), 2.0), Vec2::Y, 5.0);
+    }
+
+    #[test]
+    fn circles_and_segments() {
+        let a = circle(Vec2::ZERO, 4.0);
+        assert_contact(
+            &a,
+            &segment(Vec2::new(4.0, -3.0), Vec2::new(4.0, 3.0)),
+            Vec2::X,
+            0.0,
+        );
+        assert_apart(&a, &segment(Vec2::new(5.0, -3.0), Vec2::new(5.0, 3.0)));
+        // all of it inside
+        assert_contact(
+            &a,
+            &segment(Vec2::new(-1.0, 1.0), Vec2::new(1.0, 1.0)),
+            Vec2::Y,
+            3.0,
+        );
+    }
+
+    #[test]
+    fn circles_and_polygons() {
+        let a = square(Vec2::ZERO, 5.0);
+        assert_contact(&circle(Vec2::new(8.0, 0.0), 3.0), &a, -Vec2::X, 0.0);
+        assert_apart(&circle(Vec2::new(0.0, -9.0), 3.0), &a);
+        // over the corner, pushed away from it
+        assert_contact(
+            &circle(Vec2::new(6.0, 6.0), 2.0),
+            &a,
+            -Vec2::ONE.normalize(),
+            2.0 - 2.0f32.sqrt(),
+        );
+        // inside, it goes back out through the nearest edge
+        assert_contact(&circle(Vec2::new(4.0, 0.0), 1.0), &a, -Vec2::X, 2.0);
+    }
+
+    #[test]
+    fn segments() {
+        // crossing lines only ever touch, and push away from the first on the side the second is
+        let a = segment(Vec2::ZERO, Vec2::new(4.0, 0.0));
+        let touching = Some(Contact {
+            normal: Vec2::Y,
+            depth: 0.0,
+        });
+        assert_eq!(
+            contact(&a, &segment(Vec2::new(4.0, 0.0), Vec2::new(4.0, 4.0))),
+            touching
+        );
+        assert_apart(&a, &segment(Vec2::new(5.0, 0.0), Vec2::new(5.0, 4.0)));
+        assert_apart(&a, &segment(Vec2::new(0.0, 1.0), Vec2::new(4.0, 1.0)));
+        assert_eq!(
+            contact(&a, &segment(Vec2::new(2.0, -1.0), Vec2::new(2.0, 3.0))),
+            touching
+        );
+        // lying along the other
+        assert_eq!(
+            contact(&a, &segment(Vec2::new(1.0, 0.0), Vec2::new(3.0, 0.0))),
+            touching
+        );
+        assert_apart(&a, &segment(Vec2::new(5.0, 0.0), Vec2::new(7.0, 0.0)));
+    }
+
+    #[test]
+    fn segments_and_polygons() {
+        let a = square(Vec2::ZERO, 5.0);
+        assert_contact(
+            &segment(Vec2::new(5.0, 0.0), Vec2::new(9.0, 0.0)),
+            &a,
+            -Vec2::X,
+            0.0,
+        );
+        assert_apart(&segment(Vec2::new(6.0, 0.0), Vec2::new(9.0, 0.0)), &a);
+        // right across it, with neither end inside
+        assert_contact(
+            &segment(Vec2::new(-9.0, 8.0), Vec2::new(9.0, 2.0)),
+            &a,
+            -Vec2::new(1.0, 3.0).normalize(),
+            0.0,
+        );
+        // all of it inside, as deep as its deepest end
+        assert_contact(
+            &segment(Vec2::new(-1.0, 3.0), Vec2::new(1.0, 3.0)),
+            &a,
+            -Vec2::Y,
+            2.0,
+        );
+    }
+
+    #[test]
+    fn polygons() {
+        let a = square(Vec2::ZERO, 5.0);
+        assert_contact(&a, &square(Vec2::new(10.0, 3.0), 5.0), Vec2::X, 0.0);
+        assert_apart(&a, &square(Vec2::new(3.0, 11.0), 5.0));
+        // mostly beside each other but further apart on the diagonal, they come apart sideways
+        assert_contact(&a, &square(Vec2::new(9.0, 4.0), 5.0), Vec2::X, 1.0);
+        assert_contact(&a, &square(Vec2::new(-3.0, -9.5), 5.0), -Vec2::Y, 0.5);
+        // a corner pushed into an edge
+        let diamond = at(
+            ColliderShape::polygon(&[
+                Vec2::new(5.0, 0.0),
+                Vec2::new(0.0, 5.0),
+                Vec2::new(-5.0, 0.0),
+                Vec2::new(0.0, -5.0),
+            ]),
+            Vec2::new(-9.0, 0.0),
+        );
+        assert_contact(&a, &diamond, -Vec2::X, 1.0);
+        // one inside the other comes out the nearer way
+        assert_contact(&a, &square(Vec2::new(0.0, 3.0), 1.0), Vec2::Y, 3.0);
+    }
+
+    #[test]
+    fn asteroids_are_convex_round_their_outline() {
+        let (points, len) = match ColliderShape::asteroid(10) {
+            ColliderShape::Polygon { points, len } => (points, len),
+            _ => unreachable!("asteroids are polygons"),
+        };
+        let hull = &points[..len];
+        assert!(len >= 3);
+        for (i, (from, to)) in edges(hull).enumerate() {
+            let next = hull[(i + 2) % len];
+            assert!((to - from).perp_dot(next - to) > 0.0);
+        }
+        let step = std::f32::consts::TAU / MAX_POLYGON_POINTS as f32;
+        for (i, scale) in ASTEROID_OUTLINE.iter().enumerate() {
+            let corner = Vec2::from_angle(step * i as f32) * 10.0 * *scale;
+            assert!(
+                hull.contains(&corner) || contains(hull, corner),
+                "{:?} is outside",
+                corner
+            );
+        }
+    }
+
+    #[test]
+    fn sweeping_circles() {
+        let target = circle(Vec2::ZERO, 4.0);
+        let (start, end) = (Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0));
+        assert_swept(sweep(start, end, 1.0, &target), 0.25);
+        assert_swept(sweep(start, Vec2::new(-5.0, 0.0), 1.0, &target), 1.0);
+        assert_eq!(sweep(start, Vec2::new(-6.0, 0.0), 1.0, &target), None);
+        assert_eq!(
+            sweep(Vec2::new(-10.0, 6.0), Vec2::new(10.0, 6.0), 1.0, &target),
+            None
+        );
+        // going away
+        assert_eq!(sweep(end, end * 2.0, 1.0, &target), None);
+        assert_swept(sweep(Vec2::new(1.0, 1.0), end, 1.0, &target), 0.0);
+    }
+
+    #[test]
+    fn sweeping_segments() {
+        let target = segment(Vec2::new(0.0, -5.0), Vec2::new(0.0, 5.0));
+        assert_swept(
+            sweep(Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0), 1.0, &target),
+            0.45,
+        );
+        // past the end, only the rounded tip is in the way
+        assert_swept(
+            sweep(Vec2::new(-10.0, 6.0), Vec2::new(10.0, 6.0), 2.0, &target),
+            (10.0 - 3.0f32.sqrt()) / 20.0,
+        );
+        assert_eq!(
+            sweep(Vec2::new(-10.0, 8.0), Vec2::new(10.0, 8.0), 2.0, &target),
+            None
+        );
+        assert_swept(
+            sweep(Vec2::new(0.5, 4.0), Vec2::new(10.0, 4.0), 1.0, &target),
+            0.0,
+        );
+    }
+
+    #[test]
+    fn sweeping_polygons() {
+        let target = square(Vec2::ZERO, 5.0);
+        assert_swept(sweep(Vec2::new(-20.0, 0.0), Vec2::ZERO, 1.0, &target), 0.7);
+        assert_swept(
+            sweep(Vec2::new(-20.0, 0.0), Vec2::new(-6.0, 0.0), 1.0, &target),
+            1.0,
+        );
+        assert_eq!(
+            sweep(Vec2::new(-20.0, 7.0), Vec2::new(20.0, 7.0), 1.0, &target),
+            None
+        );
+        // already inside
+        assert_swept(
+            sweep(Vec2::new(1.0, 1.0), Vec2::new(20.0, 1.0), 0.0, &target),
+            0.0,
+        );
+    }
+}
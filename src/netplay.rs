@@ -0,0 +1,833 @@
+use std::{
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::{Hash, Hasher},
+    io::{self, Read},
+    net::{SocketAddr, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use glam::Vec2;
+use legion::IntoQuery;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    audio_playing::AudioCommandBuffer,
+    components::{CTransform, Physics},
+    modes::{self, Mode, ModeRegistry, Transition},
+    playing::{self, ControlScheme, PlayerInputs, PlayingInputs},
+    replay::{
+        control_scheme_byte, control_scheme_from_byte, invalid_data, read_array, read_u8,
+        topology_byte, topology_from_byte,
+    },
+    rng,
+    settings::Settings,
+    snapshot::Snapshot,
+    state::{GameMode, RunConfig, State},
+    systems,
+};
+
+const MAGIC: &[u8; 4] = b"ECSN";
+// peers on different versions ignore each other
+const PROTOCOL_VERSION: u8 = 1;
+
+const HELLO: u8 = 0;
+const WELCOME: u8 = 1;
+const INPUTS: u8 = 2;
+
+// the host is player one, whoever joins is player two
+pub const NET_PLAYERS: usize = 2;
+// local inputs are applied this many steps after they are read, which hides most of the round trip
+const INPUT_DELAY: u32 = 2;
+// how many steps the simulation may run past the peer's last known input before it waits for them
+const MAX_ROLLBACK: u32 = 8;
+// unacknowledged inputs are sent again with every packet, up to this many
+const MAX_INPUTS_PER_PACKET: u32 = 32;
+const HELLO_INTERVAL: Duration = Duration::from_millis(250);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// resource, what the local player does this step, filled in by the frontend
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetInput {
+    pub inputs: PlayingInputs,
+    // only the host's is used, player one aims with the mouse
+    pub mouse_pos: Vec2,
+}
+
+// simulated loss and latency on everything sent, to try out bad connections over loopback
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinkConditions {
+    // the fraction of packets dropped, 0 to 1
+    pub loss: f32,
+    pub latency: Duration,
+}
+
+#[derive(Debug, PartialEq)]
+enum Packet {
+    // sent by the joining peer until the host answers
+    Hello,
+    Welcome {
+        run_config: RunConfig,
+    },
+    Inputs {
+        // how many of the receiver's inputs the sender has
+        ack: u32,
+        start: u32,
+        inputs: Vec<NetInput>,
+        // the sender's checksum of a frame that can no longer be rolled back
+        checksum: Option<(u32, u64)>,
+    },
+}
+
+// resource, present while a netplay session is running
+pub struct NetSession {
+    socket: UdpSocket,
+    peer: Option<SocketAddr>,
+    local_player: usize,
+    // the host knows it from the start, whoever joins is told when they connect
+    run_config: Option<RunConfig>,
+    started: bool,
+    last_hello: Option<Instant>,
+    last_received: Instant,
+    outbox: Outbox,
+
+    // the next frame to simulate
+    frame: u32,
+    local_inputs: Vec<NetInput>,
+    // the peer's inputs as far as they have arrived without gaps
+    remote_inputs: Vec<NetInput>,
+    // what each frame was simulated with for the peer, predictions included
+    simulated_remote_inputs: Vec<NetInput>,
+    // how many of the local inputs the peer has
+    peer_ack: u32,
+    // the earliest frame that was simulated with a wrong prediction
+    rollback_to: Option<u32>,
+    // the state before each frame that could still be rolled back
+    snapshots: VecDeque<(u32, Snapshot)>,
+    checksums: Vec<u64>,
+    desync_reported: bool,
+    rollbacks: u32,
+}
+
+impl NetSession {
+    /** waits on the port for a peer, the run is set up from the seed and settings like a local one */
+    pub fn host(state: &State, port: u16, conditions: LinkConditions) -> io::Result<Self> {
+        let gameplay = state
+            .resources
+            .get::<Settings>()
+            .map(|settings| settings.gameplay.clone())
+            .unwrap_or_default();
+        let run_config = RunConfig {
+            seed: state.seed.unwrap_or_else(rng::random_seed),
+            control_scheme: gameplay.control_scheme,
+            players: NET_PLAYERS,
+            friendly_fire: gameplay.friendly_fire,
+            topology: gameplay.topology,
+        };
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        println!("Hosting netplay on port {}", socket.local_addr()?.port());
+        Self::new(socket, None, 0, Some(run_config), conditions)
+    }
+
+    pub fn join(host: SocketAddr, conditions: LinkConditions) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        println!("Joining netplay at {}", host);
+        Self::new(socket, Some(host), 1, None, conditions)
+    }
+
+    fn new(
+        socket: UdpSocket,
+        peer: Option<SocketAddr>,
+        local_player: usize,
+        run_config: Option<RunConfig>,
+        conditions: LinkConditions,
+    ) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            peer,
+            local_player,
+            run_config,
+            started: false,
+            last_hello: None,
+            last_received: Instant::now(),
+            outbox: Outbox::new(conditions),
+            frame: 0,
+            local_inputs: Vec::new(),
+            remote_inputs: Vec::new(),
+            simulated_remote_inputs: Vec::new(),
+            peer_ack: 0,
+            rollback_to: None,
+            snapshots: VecDeque::new(),
+            checksums: Vec::new(),
+            desync_reported: false,
+            rollbacks: 0,
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.started
+    }
+
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    pub fn rollbacks(&self) -> u32 {
+        self.rollbacks
+    }
+
+    /** frames before this were simulated with both peers' real inputs and will not change */
+    pub fn confirmed_frames(&self) -> u32 {
+        let confirmed = self.frame.min(self.remote_inputs.len() as u32);
+        self.rollback_to
+            .map_or(confirmed, |rollback_to| confirmed.min(rollback_to))
+    }
+
+    pub fn checksum(&self, frame: u32) -> Option<u64> {
+        if frame >= self.confirmed_frames() {
+            return None;
+        }
+        self.checksums.get(frame as usize).copied()
+    }
+
+    fn update(&mut self, state: &mut State, local_input: NetInput) -> io::Result<()> {
+        self.receive()?;
+
+        if !self.started {
+            match self.run_config.filter(|_| self.peer.is_some()) {
+                Some(run_config) => self.start(state, run_config),
+                None => {
+                    self.say_hello();
+                    return self.flush();
+                }
+            }
+        }
+        if self.last_received.elapsed() > TIMEOUT {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "lost connection to the peer",
+            ));
+        }
+
+        if let Some(frame) = self.rollback_to.take() {
+            self.rollback(state, frame);
+        }
+        // confirmed frames never have to be rolled back to
+        let confirmed = self.remote_inputs.len() as u32;
+        while self
+            .snapshots
+            .front()
+            .is_some_and(|(frame, _)| *frame < confirmed)
+        {
+            self.snapshots.pop_front();
+        }
+
+        // waits rather than guessing too far ahead of the peer
+        if self.frame < confirmed + MAX_ROLLBACK {
+            self.local_inputs.push(local_input);
+            self.simulate(state, self.frame);
+            self.frame += 1;
+        }
+
+        self.send_inputs();
+        self.flush()
+    }
+
+    fn start(&mut self, state: &mut State, run_config: RunConfig) {
+        println!(
+            "Netplay connected, playing as player {}",
+            self.local_player + 1
+        );
+        systems::playing::init_state::init_with_config(state, run_config);
+        // both peers know nobody does anything before the first delayed input lands
+        self.local_inputs = vec![NetInput::default(); INPUT_DELAY as usize];
+        self.last_received = Instant::now();
+        self.started = true;
+    }
+
+    fn simulate(&mut self, state: &mut State, frame: u32) {
+        self.snapshots.push_back((frame, Snapshot::capture(state)));
+
+        let index = frame as usize;
+        // the peer is guessed to keep doing whatever they did last
+        let remote_input = match self.remote_inputs.get(index) {
+            Some(input) => *input,
+            None => self.remote_inputs.last().copied().unwrap_or_default(),
+        };
+        set_frame(&mut self.simulated_remote_inputs, index, remote_input);
+        let local_input = self.local_inputs[index];
+
+        let mut player_inputs = PlayerInputs::default();
+        player_inputs.0[self.local_player] = local_input.inputs;
+        player_inputs.0[NET_PLAYERS - 1 - self.local_player] = remote_input.inputs;
+        let host_input = if self.local_player == 0 {
+            local_input
+        } else {
+            remote_input
+        };
+        state.resources.insert(player_inputs);
+        state.resources.insert(host_input.mouse_pos);
+        state
+            .playing_schedule
+            .execute(&mut state.ecs, &mut state.resources);
+
+        set_frame(&mut self.checksums, index, checksum(state));
+
+        // the run keeps going for both peers after game over, leaving is up to them
+        if let Some(mut transition) = state.resources.get_mut::<Option<Transition>>() {
+            if let Some(Transition::Push(GameMode::GameOver)) = *transition {
+                *transition = None;
+            }
+        }
+    }
+
+    /** rewinds to before the frame and simulates back up to the present with what is now known */
+    fn rollback(&mut self, state: &mut State, frame: u32) {
+        let index = match self.snapshots.iter().position(|(f, _)| *f == frame) {
+            Some(index) => index,
+            None => {
+                println!("Netplay can't roll back to frame {}, it is too old", frame);
+                return;
+            }
+        };
+        let audio_len = state
+            .resources
+            .get::<AudioCommandBuffer>()
+            .map(|audio_command_buffer| audio_command_buffer.len())
+            .unwrap_or(0);
+
+        self.snapshots[index].1.restore(state);
+        self.snapshots.truncate(index);
+        for frame in frame..self.frame {
+            self.simulate(state, frame);
+        }
+        self.rollbacks += 1;
+
+        // the frames being simulated again already made their sounds
+        if let Some(mut audio_command_buffer) = state.resources.get_mut::<AudioCommandBuffer>() {
+            audio_command_buffer.truncate(audio_len);
+        }
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        let mut buffer = [0u8; 2048];
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((len, from)) => {
+                    // anything that doesn't parse isn't from a peer
+                    if let Ok(packet) = Packet::decode(&buffer[..len]) {
+                        self.handle(packet, from);
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                // an earlier packet found nobody listening, the peer may not be up yet
+                Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {}
+                Err(e) if e.kind() == io::ErrorKind::ConnectionReset => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn handle(&mut self, packet: Packet, from: SocketAddr) {
+        match packet {
+            Packet::Hello => {
+                // only the host answers, and only to the first peer to say hello
+                let run_config = match self.run_config {
+                    Some(run_config) if self.local_player == 0 => run_config,
+                    _ => return,
+                };
+                if self.peer.is_some_and(|peer| peer != from) {
+                    return;
+                }
+                self.peer = Some(from);
+                self.last_received = Instant::now();
+                self.outbox
+                    .send(Packet::Welcome { run_config }.encode(), from);
+            }
+            Packet::Welcome { run_config } => {
+                if self.local_player == 1 && self.peer == Some(from) && self.run_config.is_none() {
+                    self.run_config = Some(run_config);
+                    self.last_received = Instant::now();
+                }
+            }
+            Packet::Inputs {
+                ack,
+                start,
+                inputs,
+                checksum,
+            } => {
+                if self.peer != Some(from) || !self.started {
+                    return;
+                }
+                self.last_received = Instant::now();
+                self.receive_inputs(ack, start, inputs);
+                if let Some((frame, checksum)) = checksum {
+                    self.compare_checksum(frame, checksum);
+                }
+            }
+        }
+    }
+
+    fn receive_inputs(&mut self, ack: u32, start: u32, inputs: Vec<NetInput>) {
+        self.peer_ack = self.peer_ack.max(ack);
+        for (frame, input) in (start..).zip(inputs) {
+            // already known, or there is a gap that a later packet will fill
+            if frame as usize != self.remote_inputs.len() {
+                continue;
+            }
+            let mispredicted = self
+                .simulated_remote_inputs
+                .get(frame as usize)
+                .is_some_and(|simulated| *simulated != input);
+            if mispredicted {
+                self.rollback_to = Some(self.rollback_to.map_or(frame, |f| f.min(frame)));
+            }
+            self.remote_inputs.push(input);
+        }
+    }
+
+    fn compare_checksum(&mut self, frame: u32, peer_checksum: u64) {
+        match self.checksum(frame) {
+            Some(checksum) if checksum != peer_checksum && !self.desync_reported => {
+                println!("Netplay desync at frame {}", frame);
+                self.desync_reported = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn say_hello(&mut self) {
+        let peer = match self.peer {
+            Some(peer) if self.local_player == 1 => peer,
+            _ => return,
+        };
+        if self
+            .last_hello
+            .is_some_and(|last_hello| last_hello.elapsed() < HELLO_INTERVAL)
+        {
+            return;
+        }
+        self.last_hello = Some(Instant::now());
+        self.outbox.send(Packet::Hello.encode(), peer);
+    }
+
+    fn send_inputs(&mut self) {
+        let peer = match self.peer {
+            Some(peer) => peer,
+            None => return,
+        };
+        let end = self.local_inputs.len() as u32;
+        let start = self.peer_ack.min(end);
+        let inputs = self.local_inputs
+            [start as usize..end.min(start + MAX_INPUTS_PER_PACKET) as usize]
+            .to_vec();
+        let checksum = self
+            .confirmed_frames()
+            .checked_sub(1)
+            .map(|frame| (frame, self.checksums[frame as usize]));
+        let packet = Packet::Inputs {
+            ack: self.remote_inputs.len() as u32,
+            start,
+            inputs,
+            checksum,
+        };
+        self.outbox.send(packet.encode(), peer);
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.outbox.flush(&self.socket)
+    }
+}
+
+// holds outgoing packets back to apply the link conditions
+struct Outbox {
+    conditions: LinkConditions,
+    // separate from the simulation's rng so a bad connection can't change what happens in the world
+    rng: StdRng,
+    queue: VecDeque<(Instant, Vec<u8>, SocketAddr)>,
+}
+
+impl Outbox {
+    fn new(conditions: LinkConditions) -> Self {
+        Self {
+            conditions,
+            rng: StdRng::from_entropy(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    fn send(&mut self, packet: Vec<u8>, to: SocketAddr) {
+        if self.rng.gen::<f32>() < self.conditions.loss {
+            return;
+        }
+        self.queue
+            .push_back((Instant::now() + self.conditions.latency, packet, to));
+    }
+
+    fn flush(&mut self, socket: &UdpSocket) -> io::Result<()> {
+        let now = Instant::now();
+        while self.queue.front().is_some_and(|(due, _, _)| *due <= now) {
+            if let Some((_, packet, to)) = self.queue.pop_front() {
+                match socket.send_to(&packet, to) {
+                    Ok(_) => {}
+                    // dropped like any other udp packet, the next one carries the same inputs
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Packet {
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(PROTOCOL_VERSION);
+        match self {
+            Packet::Hello => bytes.push(HELLO),
+            Packet::Welcome { run_config } => {
+                bytes.push(WELCOME);
+                bytes.extend_from_slice(&run_config.seed.to_le_bytes());
+                bytes.push(control_scheme_byte(run_config.control_scheme));
+                bytes.push(run_config.friendly_fire as u8);
+                bytes.push(topology_byte(run_config.topology));
+            }
+            Packet::Inputs {
+                ack,
+                start,
+                inputs,
+                checksum,
+            } => {
+                bytes.push(INPUTS);
+                bytes.extend_from_slice(&ack.to_le_bytes());
+                bytes.extend_from_slice(&start.to_le_bytes());
+                bytes.push(inputs.len() as u8);
+                for input in inputs.iter() {
+                    bytes.push(input.inputs.shoot as u8);
+                    bytes.extend_from_slice(&input.inputs.turn.to_le_bytes());
+                    bytes.extend_from_slice(&input.inputs.thrust.to_le_bytes());
+                    bytes.extend_from_slice(&input.mouse_pos.x.to_le_bytes());
+                    bytes.extend_from_slice(&input.mouse_pos.y.to_le_bytes());
+                }
+                match checksum {
+                    Some((frame, checksum)) => {
+                        bytes.push(1);
+                        bytes.extend_from_slice(&frame.to_le_bytes());
+                        bytes.extend_from_slice(&checksum.to_le_bytes());
+                    }
+                    None => bytes.push(0),
+                }
+            }
+        }
+        bytes
+    }
+
+    fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+        let reader = &mut bytes;
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u8(reader)? != PROTOCOL_VERSION {
+            return Err(invalid_data("not a netplay packet"));
+        }
+        match read_u8(reader)? {
+            HELLO => Ok(Packet::Hello),
+            WELCOME => {
+                let seed = u64::from_le_bytes(read_array(reader)?);
+                let control_scheme: ControlScheme = control_scheme_from_byte(read_u8(reader)?)?;
+                let friendly_fire = read_u8(reader)? != 0;
+                let topology = topology_from_byte(read_u8(reader)?)?;
+                Ok(Packet::Welcome {
+                    run_config: RunConfig {
+                        seed,
+                        control_scheme,
+                        players: NET_PLAYERS,
+                        friendly_fire,
+                        topology,
+                    },
+                })
+            }
+            INPUTS => {
+                let ack = u32::from_le_bytes(read_array(reader)?);
+                let start = u32::from_le_bytes(read_array(reader)?);
+                let count = read_u8(reader)?;
+                let mut inputs = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    inputs.push(NetInput {
+                        inputs: PlayingInputs {
+                            shoot: read_u8(reader)? != 0,
+                            turn: f32::from_le_bytes(read_array(reader)?),
+                            thrust: f32::from_le_bytes(read_array(reader)?),
+                        },
+                        mouse_pos: Vec2::new(
+                            f32::from_le_bytes(read_array(reader)?),
+                            f32::from_le_bytes(read_array(reader)?),
+                        ),
+                    });
+                }
+                let checksum = match read_u8(reader)? {
+                    0 => None,
+                    _ => Some((
+                        u32::from_le_bytes(read_array(reader)?),
+                        u64::from_le_bytes(read_array(reader)?),
+                    )),
+                };
+                Ok(Packet::Inputs {
+                    ack,
+                    start,
+                    inputs,
+                    checksum,
+                })
+            }
+            kind => Err(invalid_data(&format!("unknown packet kind {}", kind))),
+        }
+    }
+}
+
+// frames are simulated in order, so this either overwrites a rolled back frame or appends the next one
+fn set_frame<T>(values: &mut Vec<T>, index: usize, value: T) {
+    if index < values.len() {
+        values[index] = value;
+    } else {
+        values.push(value);
+    }
+}
+
+// where everything is and how it moves, peers compare these to spot a desync
+fn checksum(state: &State) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for (transform, physics) in <(&CTransform, Option<&Physics>)>::query().iter(&state.ecs) {
+        let mut values = vec![
+            transform.pos.x,
+            transform.pos.y,
+            transform.rot.x,
+            transform.rot.y,
+        ];
+        if let Some(physics) = physics {
+            values.extend([physics.vel.x, physics.vel.y, physics.rot_vel]);
+        }
+        for value in values {
+            value.to_bits().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::Netplay,
+        Mode {
+            step,
+            render: playing::render,
+            on_enter,
+            on_exit,
+            ..Default::default()
+        },
+    );
+}
+
+/** the session has to be made first, the run starts once the peers find each other */
+pub fn start(state: &mut State, session: NetSession) {
+    state.resources.insert(session);
+    modes::request(state, Transition::Reset(GameMode::Netplay));
+}
+
+fn on_enter(state: &mut State) {
+    // nothing to show until the peer connects
+    state.ecs.clear();
+}
+
+fn on_exit(state: &mut State) {
+    state.resources.remove::<NetSession>();
+}
+
+pub fn step(state: &mut State) {
+    // taken out while stepping so the session and the rest of the state can both be borrowed
+    let mut session = match state.resources.remove::<NetSession>() {
+        Some(session) => session,
+        None => return,
+    };
+    let local_input = state
+        .resources
+        .get::<NetInput>()
+        .map(|input| *input)
+        .unwrap_or_default();
+    match session.update(state, local_input) {
+        Ok(()) => {
+            state.resources.insert(session);
+        }
+        Err(e) => {
+            println!("Netplay stopped: {}", e);
+            modes::request(state, Transition::Reset(GameMode::Title));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::Topology;
+
+    fn run_config() -> RunConfig {
+        RunConfig {
+            seed: 5,
+            control_scheme: ControlScheme::TwinStick,
+            players: NET_PLAYERS,
+            friendly_fire: true,
+            topology: Topology::Arena,
+        }
+    }
+
+    fn shooting() -> NetInput {
+        NetInput {
+            inputs: PlayingInputs {
+                turn: -0.5,
+                thrust: 1.0,
+                shoot: true,
+            },
+            mouse_pos: Vec2::new(30.0, 40.0),
+        }
+    }
+
+    /** a host whose peer never sends anything, started straight away */
+    fn started_session(state: &mut State) -> NetSession {
+        let socket = UdpSocket::bind(("127.0.0.1", 0)).unwrap();
+        let mut session = NetSession::new(
+            socket,
+            None,
+            0,
+            Some(run_config()),
+            LinkConditions::default(),
+        )
+        .unwrap();
+        session.start(state, run_config());
+        session
+    }
+
+    fn packets() -> Vec<Packet> {
+        vec![
+            Packet::Hello,
+            Packet::Welcome {
+                run_config: run_config(),
+            },
+            Packet::Inputs {
+                ack: 3,
+                start: 7,
+                inputs: vec![NetInput::default(), shooting()],
+                checksum: Some((6, 0x0123_4567_89ab_cdef)),
+            },
+            Packet::Inputs {
+                ack: 0,
+                start: 0,
+                inputs: Vec::new(),
+                checksum: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn packets_round_trip() {
+        for packet in packets() {
+            assert_eq!(Packet::decode(&packet.encode()).unwrap(), packet);
+        }
+    }
+
+    #[test]
+    fn other_versions_and_cut_off_packets_are_ignored() {
+        for packet in packets() {
+            let bytes = packet.encode();
+            let mut other_version = bytes.clone();
+            other_version[MAGIC.len()] = PROTOCOL_VERSION + 1;
+            assert!(Packet::decode(&other_version).is_err());
+            let mut other_magic = bytes.clone();
+            other_magic[0] = b'X';
+            assert!(Packet::decode(&other_magic).is_err());
+            for len in 0..bytes.len() {
+                assert!(
+                    Packet::decode(&bytes[..len]).is_err(),
+                    "{:?} cut to {}",
+                    packet,
+                    len
+                );
+            }
+        }
+        let mut unknown_kind = Packet::Hello.encode();
+        *unknown_kind.last_mut().unwrap() = 9;
+        assert!(Packet::decode(&unknown_kind).is_err());
+    }
+
+    #[test]
+    fn local_inputs_land_after_the_delay() {
+        let mut state = State::new();
+        let mut session = started_session(&mut state);
+        for step in 0..INPUT_DELAY + 2 {
+            let input = if step == 0 {
+                shooting()
+            } else {
+                NetInput::default()
+            };
+            session.update(&mut state, input).unwrap();
+            let applied = state.resources.get::<PlayerInputs>().unwrap().0[0];
+            let expected = if step == INPUT_DELAY {
+                shooting().inputs
+            } else {
+                PlayingInputs::default()
+            };
+            assert_eq!(applied, expected, "step {}", step);
+        }
+    }
+
+    #[test]
+    fn wrong_guesses_roll_back_to_the_first_one() {
+        let mut state = State::new();
+        let mut session = started_session(&mut state);
+        for _ in 0..4 {
+            session.update(&mut state, NetInput::default()).unwrap();
+        }
+
+        // the peer did nothing on the first frame, as guessed, then shot
+        let remote = vec![NetInput::default(), shooting(), shooting()];
+        session.receive_inputs(0, 0, remote.clone());
+        assert_eq!(session.rollback_to, Some(1));
+        assert_eq!(session.confirmed_frames(), 1);
+
+        session.update(&mut state, NetInput::default()).unwrap();
+        assert_eq!(session.rollbacks(), 1);
+        assert_eq!(session.rollback_to, None);
+        assert_eq!(&session.simulated_remote_inputs[..3], &remote[..]);
+        // the last frame is still guessed from the last known input
+        assert_eq!(session.simulated_remote_inputs[4], shooting());
+
+        // the same as a session that knew all along
+        let mut known_state = State::new();
+        let mut known = started_session(&mut known_state);
+        known.receive_inputs(0, 0, remote);
+        for _ in 0..5 {
+            known.update(&mut known_state, NetInput::default()).unwrap();
+        }
+        assert_eq!(known.rollbacks(), 0);
+        assert_eq!(session.checksums, known.checksums);
+    }
+
+    #[test]
+    fn nothing_runs_or_rolls_back_further_than_max_rollback() {
+        let mut state = State::new();
+        let mut session = started_session(&mut state);
+        for _ in 0..MAX_ROLLBACK * 2 {
+            session.update(&mut state, NetInput::default()).unwrap();
+        }
+        // it waits for the peer rather than guessing further ahead
+        assert_eq!(session.frame(), MAX_ROLLBACK);
+
+        session.receive_inputs(0, 0, vec![NetInput::default(); MAX_ROLLBACK as usize]);
+        session.update(&mut state, NetInput::default()).unwrap();
+        assert_eq!(session.frame(), MAX_ROLLBACK + 1);
+        assert_eq!(session.confirmed_frames(), MAX_ROLLBACK);
+
+        // confirmed frames have been let go of
+        let checksums = session.checksums.clone();
+        session.rollback(&mut state, 0);
+        assert_eq!(session.rollbacks(), 0);
+        assert_eq!(session.checksums, checksums);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    audio_playing::{AudioCommand, AudioCommandBuffer},
+    modes::{self, Mode, ModeRegistry, Transition},
+    state::{GameMode, State},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseMenuItem {
+    Resume,
+    Restart,
+    Settings,
+    QuitToTitle,
+}
+
+pub const PAUSE_MENU_ITEMS: [PauseMenuItem; 4] = [
+    PauseMenuItem::Resume,
+    PauseMenuItem::Restart,
+    PauseMenuItem::Settings,
+    PauseMenuItem::QuitToTitle,
+];
+
+impl PauseMenuItem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PauseMenuItem::Resume => "resume",
+            PauseMenuItem::Restart => "restart",
+            PauseMenuItem::Settings => "settings",
+            PauseMenuItem::QuitToTitle => "quit to title",
+        }
+    }
+}
+
+// resource, only meaningful while paused
+#[derive(Default)]
+pub struct PauseMenu {
+    pub selected: usize,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct PauseInputs {
+    pub up: bool,
+    pub down: bool,
+    pub confirm: bool,
+    pub back: bool,
+}
+
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::Pause,
+        Mode {
+            on_enter,
+            on_exit,
+            ..Default::default()
+        },
+    );
+}
+
+fn on_enter(state: &mut State) {
+    state.resources.insert(PauseMenu::default());
+    push_audio_command(state, AudioCommand::PauseMusic);
+}
+
+fn on_exit(state: &mut State) {
+    push_audio_command(state, AudioCommand::ResumeMusic);
+}
+
+/** menu navigation happens per frame rather than per step so presses are never missed */
+pub fn handle_inputs(state: &mut State, inputs: PauseInputs) {
+    if inputs.back {
+        modes::request(state, Transition::Pop);
+        return;
+    }
+
+    let selected = match state.resources.get_mut::<PauseMenu>() {
+        Some(mut menu) => {
+            if inputs.up {
+                menu.selected =
+                    (menu.selected + PAUSE_MENU_ITEMS.len() - 1) % PAUSE_MENU_ITEMS.len();
+            }
+            if inputs.down {
+                menu.selected = (menu.selected + 1) % PAUSE_MENU_ITEMS.len();
+            }
+            menu.selected
+        }
+        None => return,
+    };
+
+    if !inputs.confirm {
+        return;
+    }
+    match PAUSE_MENU_ITEMS[selected] {
+        PauseMenuItem::Resume => modes::request(state, Transition::Pop),
+        PauseMenuItem::Restart => modes::request(state, Transition::Reset(GameMode::Playing)),
+        PauseMenuItem::Settings => modes::request(state, Transition::Push(GameMode::Settings)),
+        PauseMenuItem::QuitToTitle => modes::request(state, Transition::Reset(GameMode::Title)),
+    }
+}
+
+fn push_audio_command(state: &mut State, audio_command: AudioCommand) {
+    if let Some(mut audio_command_buffer) = state.resources.get_mut::<AudioCommandBuffer>() {
+        audio_command_buffer.push(audio_command);
+    }
+}
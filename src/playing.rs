@@ -1,20 +1,73 @@
-use glam::Vec2;
 pub use legion::*;
-use raylib::prelude::*;
 
 use crate::{
-    rendering::{execute_render_command_buffer, RenderCommandBuffer},
-    state::State,
+    modes::{Mode, ModeRegistry},
+    rendering::{Interpolation, RenderCommandBuffer},
+    replay,
+    state::{GameMode, State},
 };
 
+pub const MAX_PLAYERS: usize = 4;
+
+// one player's inputs for a step
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct PlayingInputs {
-    pub left: bool,
-    pub right: bool,
-    pub up: bool,
-    pub down: bool,
+    // -1 turns left at full speed, 1 turns right, sticks give anything in between.
+    // with the twin stick scheme it moves left and right instead
+    pub turn: f32,
+    // -1 reverses at full strength, 1 thrusts.
+    // with the twin stick scheme it moves down and up instead
+    pub thrust: f32,
     pub shoot: bool,
 }
 
+// resource, every local player's inputs for a step, indexed by InputControlled::player
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlayerInputs(pub [PlayingInputs; MAX_PLAYERS]);
+
+impl PlayerInputs {
+    pub fn any_shoot(&self) -> bool {
+        self.0.iter().any(|inputs| inputs.shoot)
+    }
+}
+
+// how inputs steer the player, fixed for the length of a run
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlScheme {
+    // rotate and thrust along the heading
+    #[default]
+    Classic,
+    // move along the screen axes while the ship and its guns face the cursor,
+    // only player one has the mouse so the others face the way they move
+    TwinStick,
+}
+
+pub const CONTROL_SCHEMES: [ControlScheme; 2] = [ControlScheme::Classic, ControlScheme::TwinStick];
+
+impl ControlScheme {
+    // the value used in settings and replay files
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlScheme::Classic => "classic",
+            ControlScheme::TwinStick => "twin_stick",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ControlScheme::Classic => "classic",
+            ControlScheme::TwinStick => "twin stick",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        CONTROL_SCHEMES
+            .iter()
+            .copied()
+            .find(|scheme| scheme.name() == name)
+    }
+}
+
 // // add a player
 // state.ecs.push((
 //     CTransform {
@@ -34,56 +87,35 @@ pub struct PlayingInputs {
 //     },
 // ));
 
-pub fn process_events_and_input(rl: &mut RaylibHandle, state: &mut State) {
-    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_ESCAPE) {
-        state.running = false;
-    }
+pub fn step(state: &mut State) {
+    replay::record(state);
 
-    let mut inputs = PlayingInputs {
-        left: false,
-        right: false,
-        up: false,
-        down: false,
-        shoot: false,
-    };
-    if rl.is_key_down(raylib::consts::KeyboardKey::KEY_LEFT)
-        || rl.is_key_down(raylib::consts::KeyboardKey::KEY_A)
-    {
-        inputs.left = true;
-    }
-    if rl.is_key_down(raylib::consts::KeyboardKey::KEY_RIGHT)
-        || rl.is_key_down(raylib::consts::KeyboardKey::KEY_D)
-    {
-        inputs.right = true;
-    }
-    if rl.is_key_down(raylib::consts::KeyboardKey::KEY_UP)
-        || rl.is_key_down(raylib::consts::KeyboardKey::KEY_W)
-    {
-        inputs.up = true;
-    }
-    if rl.is_key_down(raylib::consts::KeyboardKey::KEY_DOWN)
-        || rl.is_key_down(raylib::consts::KeyboardKey::KEY_S)
-    {
-        inputs.down = true;
-    }
-    if rl.is_key_down(raylib::consts::KeyboardKey::KEY_SPACE) {
-        inputs.shoot = true;
-    }
-
-    state.resources.insert(inputs);
+    state
+        .playing_schedule
+        .execute(&mut state.ecs, &mut state.resources);
 }
 
-pub fn step(rl: &mut RaylibHandle, state: &mut State) {
-    let mouse_pos_rl = rl.get_mouse_position();
-    let mouse_pos = Vec2::new(mouse_pos_rl.x, mouse_pos_rl.y);
-    state.resources.insert(mouse_pos);
+/** rebuilds the render command buffer, alpha is how far the frame is between the last step and the next */
+pub fn render(state: &mut State, alpha: f32) {
+    state.resources.insert(Interpolation { alpha });
+    if let Some(mut render_command_buffer) = state.resources.get_mut::<RenderCommandBuffer>() {
+        render_command_buffer.clear();
+    }
 
     state
-        .playing_schedule
+        .playing_render_schedule
         .execute(&mut state.ecs, &mut state.resources);
 }
 
-pub fn draw(state: &State, d: &mut RaylibTextureMode<RaylibDrawHandle>) {
-    let mut render_command_buffer = state.resources.get_mut::<RenderCommandBuffer>().unwrap();
-    execute_render_command_buffer(d, &mut render_command_buffer);
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::Playing,
+        Mode {
+            step,
+            render,
+            on_enter: crate::systems::playing::init_state::init,
+            on_exit: replay::save_recording,
+            ..Default::default()
+        },
+    );
 }
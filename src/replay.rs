@@ -0,0 +1,370 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use glam::Vec2;
+
+use crate::{
+    modes::{self, Mode, ModeRegistry, Transition},
+    playing::{self, ControlScheme, PlayerInputs, PlayingInputs, MAX_PLAYERS},
+    state::{GameMode, RunConfig, State, FRAMES_PER_SECOND},
+    systems,
+    topology::Topology,
+};
+
+const MAGIC: &[u8; 4] = b"ECSR";
+const VERSION: u8 = 1;
+// ten hours of steps, longer runs stop being recorded so a broken file can't ask for more than this
+const MAX_FRAMES: usize = FRAMES_PER_SECOND as usize * 60 * 60 * 10;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReplayFrame {
+    // only the run's players are saved, the rest stay neutral
+    pub inputs: PlayerInputs,
+    pub mouse_pos: Vec2,
+}
+
+// one frame per playing step, and the config the run was started with
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub run_config: RunConfig,
+    pub frames: Vec<ReplayFrame>,
+}
+
+// resource, present while a run is being recorded
+pub struct ReplayRecorder(pub Replay);
+
+// resource, when present each run is saved here as it ends
+pub struct ReplayRecordPath(pub PathBuf);
+
+// resource, present while a replay is being watched
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    pub cursor: usize,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, cursor: 0 }
+    }
+}
+
+impl Replay {
+    pub fn new(run_config: RunConfig) -> Self {
+        Self {
+            run_config,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /** inputs rarely change between steps, so frames are stored as runs of identical frames */
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut runs: Vec<(u16, ReplayFrame)> = Vec::new();
+        for frame in self.frames.iter() {
+            match runs.last_mut() {
+                Some((count, last)) if last == frame && *count < u16::MAX => *count += 1,
+                _ => runs.push((1, *frame)),
+            }
+        }
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&self.run_config.seed.to_le_bytes())?;
+        writer.write_all(&[control_scheme_byte(self.run_config.control_scheme)])?;
+        writer.write_all(&[self.run_config.players as u8])?;
+        writer.write_all(&[self.run_config.friendly_fire as u8])?;
+        writer.write_all(&[topology_byte(self.run_config.topology)])?;
+        writer.write_all(&(runs.len() as u32).to_le_bytes())?;
+        for (count, frame) in runs {
+            writer.write_all(&count.to_le_bytes())?;
+            for inputs in frame.inputs.0.iter().take(self.run_config.players) {
+                writer.write_all(&[inputs.shoot as u8])?;
+                writer.write_all(&inputs.turn.to_le_bytes())?;
+                writer.write_all(&inputs.thrust.to_le_bytes())?;
+            }
+            writer.write_all(&frame.mouse_pos.x.to_le_bytes())?;
+            writer.write_all(&frame.mouse_pos.y.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a replay file"));
+        }
+        let version = read_u8(reader)?;
+        if version != VERSION {
+            return Err(invalid_data(&format!(
+                "unsupported replay version {}",
+                version
+            )));
+        }
+
+        let seed = u64::from_le_bytes(read_array(reader)?);
+        let control_scheme = control_scheme_from_byte(read_u8(reader)?)?;
+        let players = read_u8(reader)? as usize;
+        let friendly_fire = read_u8(reader)? != 0;
+        let topology = topology_from_byte(read_u8(reader)?)?;
+        if players == 0 || players > MAX_PLAYERS {
+            return Err(invalid_data(&format!(
+                "unsupported player count {}",
+                players
+            )));
+        }
+        let mut replay = Replay::new(RunConfig {
+            seed,
+            control_scheme,
+            players,
+            friendly_fire,
+            topology,
+        });
+        // every run is at least a frame
+        let run_count = u32::from_le_bytes(read_array(reader)?) as usize;
+        if run_count > MAX_FRAMES {
+            return Err(invalid_data(&format!("{} runs is too many", run_count)));
+        }
+        for _ in 0..run_count {
+            let count = u16::from_le_bytes(read_array(reader)?) as usize;
+            if count == 0 || replay.frames.len() + count > MAX_FRAMES {
+                return Err(invalid_data("replay is longer than it can be"));
+            }
+            let mut inputs = PlayerInputs::default();
+            for player_inputs in inputs.0.iter_mut().take(players) {
+                *player_inputs = PlayingInputs {
+                    shoot: read_u8(reader)? != 0,
+                    turn: f32::from_le_bytes(read_array(reader)?),
+                    thrust: f32::from_le_bytes(read_array(reader)?),
+                };
+            }
+            let mouse_pos = Vec2::new(
+                f32::from_le_bytes(read_array(reader)?),
+                f32::from_le_bytes(read_array(reader)?),
+            );
+            let frame = ReplayFrame { inputs, mouse_pos };
+            replay.frames.extend(std::iter::repeat_n(frame, count));
+        }
+        Ok(replay)
+    }
+}
+
+/** feeds the next recorded frame into the playing schedule, goes back to the title when it runs out */
+pub fn step(state: &mut State) {
+    let frame = match state.resources.get_mut::<ReplayPlayback>() {
+        Some(mut playback) => {
+            let frame = playback.replay.frames.get(playback.cursor).copied();
+            playback.cursor += 1;
+            frame
+        }
+        None => None,
+    };
+
+    let frame = match frame {
+        Some(frame) => frame,
+        None => {
+            modes::request(state, Transition::Reset(GameMode::Title));
+            return;
+        }
+    };
+
+    state.resources.insert(frame.inputs);
+    state.resources.insert(frame.mouse_pos);
+    playing::step(state);
+
+    // the recorded run kept going after game over, so keep watching until the inputs run out
+    if let Some(mut transition) = state.resources.get_mut::<Option<Transition>>() {
+        if let Some(Transition::Push(GameMode::GameOver)) = *transition {
+            *transition = None;
+        }
+    }
+}
+
+pub fn save_recording(state: &mut State) {
+    let path = match state.resources.get::<ReplayRecordPath>() {
+        Some(path) => path.0.clone(),
+        None => return,
+    };
+    if let Some(recorder) = state.resources.get::<ReplayRecorder>() {
+        match recorder.0.save(&path) {
+            Ok(()) => println!("Saved replay to {}", path.display()),
+            Err(e) => println!("Error saving replay {}: {}", path.display(), e),
+        }
+    }
+}
+
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::Replay,
+        Mode {
+            step,
+            render: playing::render,
+            on_enter: systems::replay::init_state::init,
+            ..Default::default()
+        },
+    );
+}
+
+pub fn record(state: &mut State) {
+    let frame = ReplayFrame {
+        inputs: state
+            .resources
+            .get::<PlayerInputs>()
+            .map(|inputs| *inputs)
+            .unwrap_or_default(),
+        mouse_pos: state
+            .resources
+            .get::<Vec2>()
+            .map(|mouse_pos| *mouse_pos)
+            .unwrap_or_default(),
+    };
+    if let Some(mut recorder) = state.resources.get_mut::<ReplayRecorder>() {
+        if recorder.0.frames.len() < MAX_FRAMES {
+            recorder.0.frames.push(frame);
+        }
+    }
+}
+
+pub(crate) fn control_scheme_byte(control_scheme: ControlScheme) -> u8 {
+    match control_scheme {
+        ControlScheme::Classic => 0,
+        ControlScheme::TwinStick => 1,
+    }
+}
+
+pub(crate) fn control_scheme_from_byte(byte: u8) -> io::Result<ControlScheme> {
+    match byte {
+        0 => Ok(ControlScheme::Classic),
+        1 => Ok(ControlScheme::TwinStick),
+        _ => Err(invalid_data(&format!("unknown control scheme {}", byte))),
+    }
+}
+
+pub(crate) fn topology_byte(topology: Topology) -> u8 {
+    match topology {
+        Topology::Wrap => 0,
+        Topology::Arena => 1,
+        Topology::Open => 2,
+    }
+}
+
+pub(crate) fn topology_from_byte(byte: u8) -> io::Result<Topology> {
+    match byte {
+        0 => Ok(Topology::Wrap),
+        1 => Ok(Topology::Arena),
+        2 => Ok(Topology::Open),
+        _ => Err(invalid_data(&format!("unknown topology {}", byte))),
+    }
+}
+
+pub(crate) fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    Ok(read_array::<1>(reader)?[0])
+}
+
+pub(crate) fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub(crate) fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(shoot: bool, turn: f32) -> ReplayFrame {
+        let mut inputs = PlayerInputs::default();
+        inputs.0[0] = PlayingInputs {
+            turn,
+            thrust: 1.0,
+            shoot,
+        };
+        inputs.0[1].thrust = -0.5;
+        ReplayFrame {
+            inputs,
+            mouse_pos: Vec2::new(12.5, 40.0),
+        }
+    }
+
+    fn to_bytes(replay: &Replay) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        replay.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn round_trips_runs_longer_than_a_count() {
+        let mut replay = Replay::new(RunConfig {
+            seed: 0xDEAD_BEEF,
+            control_scheme: ControlScheme::TwinStick,
+            players: 2,
+            friendly_fire: true,
+            topology: Topology::Open,
+        });
+        // one more than a run can hold, then exactly as many, then a change every frame
+        replay.frames.extend(std::iter::repeat_n(
+            frame(false, 0.0),
+            u16::MAX as usize + 1,
+        ));
+        replay
+            .frames
+            .extend(std::iter::repeat_n(frame(true, -0.25), u16::MAX as usize));
+        replay.frames.extend([frame(false, 1.0), frame(true, 1.0)]);
+
+        let bytes = to_bytes(&replay);
+        let header = 4 + 1 + 8 + 4 + 4;
+        let run = 2 + 2 * 9 + 8;
+        assert_eq!(bytes.len(), header + 5 * run);
+        assert_eq!(Replay::read_from(&mut bytes.as_slice()).unwrap(), replay);
+    }
+
+    #[test]
+    fn round_trips_an_empty_replay() {
+        let replay = Replay::new(RunConfig::default());
+        let bytes = to_bytes(&replay);
+        assert_eq!(Replay::read_from(&mut bytes.as_slice()).unwrap(), replay);
+    }
+
+    #[test]
+    fn rejects_broken_files() {
+        let mut replay = Replay::new(RunConfig::default());
+        replay.frames.push(frame(true, 0.5));
+        let bytes = to_bytes(&replay);
+        let run_count_at = bytes.len() - 4 - (2 + 9 + 8);
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = VERSION + 1;
+        let mut too_many_runs = bytes.clone();
+        too_many_runs[run_count_at..run_count_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut empty_run = bytes.clone();
+        empty_run[run_count_at + 4..run_count_at + 6].copy_from_slice(&0u16.to_le_bytes());
+        let truncated = &bytes[..bytes.len() - 1];
+
+        for broken in [
+            &wrong_version[..],
+            &too_many_runs[..],
+            &empty_run[..],
+            truncated,
+            b"ECSS",
+        ] {
+            assert!(Replay::read_from(&mut &broken[..]).is_err());
+        }
+        let error = Replay::read_from(&mut too_many_runs.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
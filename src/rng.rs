@@ -0,0 +1,33 @@
+use rand::{rngs::StdRng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+// the simulation draws from the SimulationRng resource, which is what StdRng wraps so seeds play out
+// the same, but unlike StdRng it can say where it is in its stream for saving snapshots.
+// cosmetic draws get their own streams so rendering or audio never changes what happens in the world
+pub type SimulationRng = ChaCha12Rng;
+pub struct Seed(pub u64);
+pub struct RenderRng(pub StdRng);
+pub struct AudioRng(pub StdRng);
+
+const RENDER_STREAM: u64 = 1;
+const AUDIO_STREAM: u64 = 2;
+
+pub fn random_seed() -> u64 {
+    rand::random()
+}
+
+pub fn simulation_rng(seed: u64) -> SimulationRng {
+    SimulationRng::seed_from_u64(seed)
+}
+
+pub fn render_rng(seed: u64) -> RenderRng {
+    RenderRng(stream(seed, RENDER_STREAM))
+}
+
+pub fn audio_rng(seed: u64) -> AudioRng {
+    AudioRng(stream(seed, AUDIO_STREAM))
+}
+
+fn stream(seed: u64, stream: u64) -> StdRng {
+    StdRng::seed_from_u64(seed ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
@@ -2,15 +2,28 @@ use legion::Schedule;
 
 use crate::systems::playing::{
     attached::{check_attached_to_null_system, stick_to_attached_system},
-    collision::{attach_to_grab_zone, attach_to_grab_zone_system, collision_system},
-    enemy_behaviour::{enemy_behaviour_system, look_at, look_at_system},
+    broadphase::build_spatial_hash_system,
+    camera::follow_ships_system,
+    collision::{
+        attach_to_grab_zone_system, bounce_asteroids_system, collision_damage_system,
+        detect_collisions_system,
+    },
+    damage::{handle_deaths_system, score_kills_system},
+    enemy_behaviour::{enemy_behaviour_system, look_at_system},
+    input_handling::aim_guns_at_cursor_system,
+    interpolation::store_previous_transform_system,
+    lives::{award_extra_lives_system, respawn_system, step_invulnerability_system},
     ownership::check_owned_by_null_system,
-    physics::{capture_in_play_field_system, physics_system, world_wrap_system},
+    physics::{
+        bounce_off_walls_system, capture_in_play_field_system, drop_far_away_system,
+        physics_system, world_wrap_system,
+    },
+    powerups::{collect_pickups_system, step_powerups_system},
     rendering::{entity_render_system, render_expiring_messages_system, score_render_system},
     shooting::guns_system,
     spawning_enemies::{spawn_asteroids_system, spawn_enemies_system, spawn_guns_system},
-    state_changing::game_over_system,
-    util::step_lifespan_system,
+    state_changing::{count_run_steps_system, game_over_system},
+    util::{step_alerts_system, step_lifespan_system},
 };
 
 pub fn build_title_schedule() -> Schedule {
@@ -21,25 +34,51 @@ pub fn build_title_schedule() -> Schedule {
 
 pub fn build_play_schedule() -> Schedule {
     Schedule::builder()
+        .add_system(store_previous_transform_system())
+        .flush()
         .add_system(crate::systems::playing::input_handling::handle_inputs_system())
         .flush()
+        // once for where things start the step and again once everything has moved
+        .add_system(build_spatial_hash_system())
         .add_system(enemy_behaviour_system())
         .add_system(look_at_system())
         .add_system(physics_system())
         .add_system(check_attached_to_null_system())
         .add_system(check_owned_by_null_system())
         .add_system(stick_to_attached_system())
+        .add_system(aim_guns_at_cursor_system())
         .add_system(guns_system())
-        .add_system(collision_system())
+        .add_system(build_spatial_hash_system())
+        .add_system(detect_collisions_system())
+        .add_system(bounce_asteroids_system())
+        .add_system(collision_damage_system())
+        .add_system(collect_pickups_system())
+        .add_system(score_kills_system())
+        .add_system(handle_deaths_system())
         .add_system(attach_to_grab_zone_system())
         .flush()
         .add_system(spawn_enemies_system())
         .add_system(spawn_asteroids_system())
         .add_system(spawn_guns_system())
+        .add_system(respawn_system())
+        .add_system(step_invulnerability_system())
+        .add_system(award_extra_lives_system())
+        .add_system(step_powerups_system())
+        // only the one for the run's topology does anything
         .add_system(world_wrap_system())
+        .add_system(bounce_off_walls_system())
+        .add_system(drop_far_away_system())
         .add_system(capture_in_play_field_system())
+        .add_system(follow_ships_system())
         .add_system(step_lifespan_system())
-        .flush()
+        .add_system(step_alerts_system())
+        .add_system(count_run_steps_system())
+        .build()
+}
+
+// runs once per drawn frame rather than once per step
+pub fn build_play_render_schedule() -> Schedule {
+    Schedule::builder()
         .add_system(game_over_system())
         .add_system(entity_render_system())
         .add_system(score_render_system())
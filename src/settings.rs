@@ -0,0 +1,620 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    actions::{self, Action, Binding, Bindings, InputDevice, ACTIONS},
+    playing::{ControlScheme, MAX_PLAYERS},
+    topology::Topology,
+    DIMS,
+};
+
+pub const SETTINGS_VERSION: u32 = 1;
+const FILE_NAME: &str = "settings.cfg";
+
+// the entry at index i upgrades the raw entries of a version i + 1 file to version i + 2, so bumping
+// the version means adding one here
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize - 1] = [];
+
+pub const WINDOW_SIZES: [(u32, u32); 5] = [
+    (960, 540),
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+];
+pub const TARGET_FPS_OPTIONS: [u32; 5] = [30, 60, 120, 144, 240];
+pub const MAX_RENDER_SCALE: u32 = 4;
+pub const MAX_DEADZONE: f32 = 0.5;
+const MAX_WINDOW_DIMS: (u32, u32) = (7680, 4320);
+
+// "section.key" -> raw value, as read from the file
+type Entries = HashMap<String, String>;
+type Migration = fn(&mut Entries);
+
+// resource, the player's options, edited by the settings screen
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Settings {
+    pub video: VideoSettings,
+    pub audio: AudioSettings,
+    pub gameplay: GameplaySettings,
+    pub controls: ControlsSettings,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoSettings {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub fullscreen: bool,
+    pub target_fps: u32,
+    // the game is drawn at DIMS times this, then scaled to fit the window
+    pub render_scale: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioSettings {
+    pub music_volume: f32,
+    pub sound_effects_volume: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameplaySettings {
+    // when set every run uses this seed, unless one is given on the command line
+    pub seed: Option<u64>,
+    pub control_scheme: ControlScheme,
+    // local players sharing the screen, 1 to MAX_PLAYERS
+    pub players: usize,
+    // what each player plays with when there is more than one of them
+    pub player_devices: [InputDevice; MAX_PLAYERS],
+    // whether players' bullets hurt each other
+    pub friendly_fire: bool,
+    pub topology: Topology,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControlsSettings {
+    pub bindings: Bindings,
+    // how far a stick has to move from the center before it counts, 0 to 1
+    pub deadzone: f32,
+}
+
+// resource, when present the settings are saved here whenever the settings screen closes
+pub struct SettingsPath(pub PathBuf);
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            window_width: 1280,
+            window_height: 720,
+            fullscreen: false,
+            target_fps: 144,
+            render_scale: 1,
+        }
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            music_volume: 1.0,
+            sound_effects_volume: 1.0,
+        }
+    }
+}
+
+impl Default for GameplaySettings {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            control_scheme: ControlScheme::default(),
+            players: 1,
+            player_devices: [
+                InputDevice::Keyboard,
+                InputDevice::Gamepad(0),
+                InputDevice::Gamepad(1),
+                InputDevice::Gamepad(2),
+            ],
+            friendly_fire: false,
+            topology: Topology::default(),
+        }
+    }
+}
+
+impl Default for ControlsSettings {
+    fn default() -> Self {
+        Self {
+            bindings: actions::default_bindings(),
+            deadzone: 0.2,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    /** missing or unreadable values fall back to their defaults, out of range ones are clamped */
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut entries = parse_entries(text)?;
+        migrate(&mut entries, &MIGRATIONS)?;
+
+        let defaults = Settings::default();
+        let mut settings = Settings {
+            video: VideoSettings {
+                window_width: read(&entries, "video.window_width", defaults.video.window_width),
+                window_height: read(
+                    &entries,
+                    "video.window_height",
+                    defaults.video.window_height,
+                ),
+                fullscreen: read(&entries, "video.fullscreen", defaults.video.fullscreen),
+                target_fps: read(&entries, "video.target_fps", defaults.video.target_fps),
+                render_scale: read(&entries, "video.render_scale", defaults.video.render_scale),
+            },
+            audio: AudioSettings {
+                music_volume: read(&entries, "audio.music_volume", defaults.audio.music_volume),
+                sound_effects_volume: read(
+                    &entries,
+                    "audio.sound_effects_volume",
+                    defaults.audio.sound_effects_volume,
+                ),
+            },
+            gameplay: GameplaySettings {
+                seed: read_with(&entries, "gameplay.seed", defaults.gameplay.seed, |value| {
+                    match value {
+                        "random" => Some(None),
+                        value => value.parse().ok().map(Some),
+                    }
+                }),
+                control_scheme: read_with(
+                    &entries,
+                    "gameplay.control_scheme",
+                    defaults.gameplay.control_scheme,
+                    ControlScheme::from_name,
+                ),
+                players: read(&entries, "gameplay.players", defaults.gameplay.players),
+                player_devices: read_with(
+                    &entries,
+                    "gameplay.player_devices",
+                    defaults.gameplay.player_devices,
+                    read_player_devices,
+                ),
+                friendly_fire: read(
+                    &entries,
+                    "gameplay.friendly_fire",
+                    defaults.gameplay.friendly_fire,
+                ),
+                topology: read_with(
+                    &entries,
+                    "gameplay.topology",
+                    defaults.gameplay.topology,
+                    Topology::from_name,
+                ),
+            },
+            controls: ControlsSettings {
+                bindings: ACTIONS
+                    .iter()
+                    .map(|action| (*action, read_bindings(&entries, *action, &defaults)))
+                    .collect(),
+                deadzone: read(&entries, "controls.deadzone", defaults.controls.deadzone),
+            },
+        };
+        settings.validate();
+        Ok(settings)
+    }
+
+    pub fn to_text(&self) -> String {
+        let seed = match self.gameplay.seed {
+            Some(seed) => seed.to_string(),
+            None => "random".to_string(),
+        };
+        let mut text = format!(
+            "version = {}\n\
+             \n\
+             [video]\n\
+             window_width = {}\n\
+             window_height = {}\n\
+             fullscreen = {}\n\
+             target_fps = {}\n\
+             render_scale = {}\n\
+             \n\
+             [audio]\n\
+             music_volume = {}\n\
+             sound_effects_volume = {}\n\
+             \n\
+             [gameplay]\n\
+             seed = {}\n\
+             control_scheme = {}\n\
+             players = {}\n\
+             player_devices = {}\n\
+             friendly_fire = {}\n\
+             topology = {}\n",
+            SETTINGS_VERSION,
+            self.video.window_width,
+            self.video.window_height,
+            self.video.fullscreen,
+            self.video.target_fps,
+            self.video.render_scale,
+            self.audio.music_volume,
+            self.audio.sound_effects_volume,
+            seed,
+            self.gameplay.control_scheme.name(),
+            self.gameplay.players,
+            self.gameplay
+                .player_devices
+                .iter()
+                .map(InputDevice::name)
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.gameplay.friendly_fire,
+            self.gameplay.topology.name(),
+        );
+
+        text.push_str(&format!(
+            "\n[controls]\ndeadzone = {}\n",
+            self.controls.deadzone
+        ));
+        for action in ACTIONS.iter() {
+            let names: Vec<String> = self
+                .controls
+                .bindings
+                .get(action)
+                .map(|bindings| bindings.iter().map(Binding::name).collect())
+                .unwrap_or_default();
+            let line = format!("{} = {}", action.name(), names.join(", "));
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn validate(&mut self) {
+        let video = &mut self.video;
+        video.window_width = video.window_width.clamp(DIMS.x, MAX_WINDOW_DIMS.0);
+        video.window_height = video.window_height.clamp(DIMS.y, MAX_WINDOW_DIMS.1);
+        video.target_fps = video.target_fps.clamp(
+            TARGET_FPS_OPTIONS[0],
+            TARGET_FPS_OPTIONS[TARGET_FPS_OPTIONS.len() - 1],
+        );
+        video.render_scale = video.render_scale.clamp(1, MAX_RENDER_SCALE);
+
+        let audio = &mut self.audio;
+        audio.music_volume = clamp_volume(audio.music_volume);
+        audio.sound_effects_volume = clamp_volume(audio.sound_effects_volume);
+
+        self.gameplay.players = self.gameplay.players.clamp(1, MAX_PLAYERS);
+
+        let controls = &mut self.controls;
+        controls.deadzone = if controls.deadzone.is_nan() {
+            ControlsSettings::default().deadzone
+        } else {
+            controls.deadzone.clamp(0.0, MAX_DEADZONE)
+        };
+        for bindings in controls.bindings.values_mut() {
+            bindings.truncate(actions::MAX_BINDINGS_PER_ACTION);
+        }
+    }
+}
+
+/** $XDG_CONFIG_HOME, %APPDATA% or ~/.config, whichever is set first */
+pub fn default_path() -> Option<PathBuf> {
+    Some(config_dir()?.join(FILE_NAME))
+}
+
+/** where everything the game keeps between runs is saved */
+pub fn config_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| {
+        std::env::var_os(name)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    };
+    let config_dir = env_dir("XDG_CONFIG_HOME")
+        .or_else(|| env_dir("APPDATA"))
+        .or_else(|| env_dir("HOME").map(|home| home.join(".config")))?;
+    Some(config_dir.join("ecsteroids"))
+}
+
+/** a missing file is a first run, anything else wrong with it is reported and replaced by defaults */
+pub fn load_or_default(path: &Path) -> Settings {
+    match Settings::load(path) {
+        Ok(settings) => settings,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Settings::default(),
+        Err(e) => {
+            println!("Error loading settings {}: {}", path.display(), e);
+            Settings::default()
+        }
+    }
+}
+
+fn parse_entries(text: &str) -> io::Result<Entries> {
+    let mut entries = Entries::new();
+    let mut section = String::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = format!("{}.", name.trim());
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) => {
+                entries.insert(
+                    format!("{}{}", section, key.trim()),
+                    value.trim().to_string(),
+                );
+            }
+            None => {
+                return Err(invalid_data(&format!(
+                    "line {}: expected key = value",
+                    line_number + 1
+                )))
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn read<T: std::str::FromStr>(entries: &Entries, key: &str, default: T) -> T {
+    read_with(entries, key, default, |value| value.parse().ok())
+}
+
+fn read_with<T>(entries: &Entries, key: &str, default: T, parse: impl Fn(&str) -> Option<T>) -> T {
+    match entries.get(key).map(|value| parse(value)) {
+        Some(Some(value)) => value,
+        Some(None) => {
+            println!("Ignoring invalid setting {}, using the default", key);
+            default
+        }
+        None => default,
+    }
+}
+
+/** brings entries from any older version up to the one after the last migration */
+fn migrate(entries: &mut Entries, migrations: &[Migration]) -> io::Result<()> {
+    let latest = migrations.len() as u32 + 1;
+    let version = match entries.remove("version").map(|v| v.parse::<u32>()) {
+        Some(Ok(version)) => version,
+        _ => return Err(invalid_data("settings file has no valid version")),
+    };
+    if version == 0 || version > latest {
+        return Err(invalid_data(&format!(
+            "unsupported settings version {}",
+            version
+        )));
+    }
+    for migration in migrations.iter().skip(version as usize - 1) {
+        migration(entries);
+    }
+    Ok(())
+}
+
+// an empty value leaves the action unbound, unknown names are skipped
+fn read_bindings(entries: &Entries, action: Action, defaults: &Settings) -> Vec<Binding> {
+    let key = format!("controls.{}", action.name());
+    match entries.get(&key) {
+        Some(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| {
+                let binding = Binding::from_name(name);
+                if binding.is_none() {
+                    println!("Ignoring unknown binding {} for {}", name, key);
+                }
+                binding
+            })
+            .collect(),
+        None => defaults.controls.bindings[&action].clone(),
+    }
+}
+
+// one device per player, in player order
+fn read_player_devices(value: &str) -> Option<[InputDevice; MAX_PLAYERS]> {
+    let devices: Vec<InputDevice> = value
+        .split(',')
+        .map(|name| InputDevice::from_name(name.trim()))
+        .collect::<Option<_>>()?;
+    devices.try_into().ok()
+}
+
+fn clamp_volume(volume: f32) -> f32 {
+    if volume.is_nan() {
+        return 1.0;
+    }
+    volume.clamp(0.0, 1.0)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(names: &[&str]) -> Vec<Binding> {
+        names
+            .iter()
+            .map(|name| Binding::from_name(name).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parses_a_sample_file() {
+        let text = "\
+            # written by hand\n\
+            version = 1\n\
+            \n\
+            [video]\n\
+            window_width = 1920\n\
+            window_height = 1080\n\
+            fullscreen = true\n\
+            \n\
+            [audio]\n\
+            music_volume = 0.25\n\
+            \n\
+            [gameplay]\n\
+            seed = 42\n\
+            control_scheme = twin_stick\n\
+            players = 2\n\
+            player_devices = gamepad1, keyboard, gamepad0, any\n\
+            friendly_fire = true\n\
+            topology = arena\n\
+            \n\
+            [controls]\n\
+            deadzone = 0.3\n\
+            fire = X, MOUSE_RIGHT\n\
+            pause =\n";
+        let settings = Settings::parse(text).unwrap();
+        let defaults = Settings::default();
+
+        assert_eq!(settings.video.window_width, 1920);
+        assert_eq!(settings.video.window_height, 1080);
+        assert!(settings.video.fullscreen);
+        assert_eq!(settings.video.target_fps, defaults.video.target_fps);
+        assert_eq!(settings.audio.music_volume, 0.25);
+        assert_eq!(settings.audio.sound_effects_volume, 1.0);
+        assert_eq!(settings.gameplay.seed, Some(42));
+        assert_eq!(settings.gameplay.control_scheme, ControlScheme::TwinStick);
+        assert_eq!(settings.gameplay.players, 2);
+        assert_eq!(
+            settings.gameplay.player_devices,
+            [
+                InputDevice::Gamepad(1),
+                InputDevice::Keyboard,
+                InputDevice::Gamepad(0),
+                InputDevice::Any,
+            ]
+        );
+        assert!(settings.gameplay.friendly_fire);
+        assert_eq!(settings.gameplay.topology, Topology::Arena);
+        assert_eq!(settings.controls.deadzone, 0.3);
+        assert_eq!(
+            settings.controls.bindings[&Action::Fire],
+            bindings(&["X", "MOUSE_RIGHT"])
+        );
+        assert!(settings.controls.bindings[&Action::Pause].is_empty());
+        assert_eq!(
+            settings.controls.bindings[&Action::Thrust],
+            defaults.controls.bindings[&Action::Thrust]
+        );
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut settings = Settings::default();
+        settings.video.render_scale = 3;
+        settings.audio.sound_effects_volume = 0.5;
+        settings.gameplay.seed = Some(7);
+        settings.gameplay.players = 3;
+        settings.gameplay.topology = Topology::Open;
+        settings.controls.bindings.insert(Action::Back, Vec::new());
+        assert_eq!(Settings::parse(&settings.to_text()).unwrap(), settings);
+        assert_eq!(
+            Settings::parse(&Settings::default().to_text()).unwrap(),
+            Settings::default()
+        );
+    }
+
+    #[test]
+    fn bad_values_fall_back_to_defaults_or_are_clamped() {
+        let text = "\
+            version = 1\n\
+            [video]\n\
+            window_width = wide\n\
+            window_height = 10\n\
+            render_scale = 100\n\
+            unheard_of = 3\n\
+            [audio]\n\
+            music_volume = 5\n\
+            sound_effects_volume = NaN\n\
+            [gameplay]\n\
+            seed = soon\n\
+            control_scheme = psychic\n\
+            players = 9\n\
+            player_devices = keyboard, gamepad0\n\
+            topology = klein_bottle\n\
+            [controls]\n\
+            deadzone = 0.9\n\
+            fire = SPACE, NOT_A_KEY\n";
+        let settings = Settings::parse(text).unwrap();
+        let defaults = Settings::default();
+
+        assert_eq!(settings.video.window_width, defaults.video.window_width);
+        assert_eq!(settings.video.window_height, DIMS.y);
+        assert_eq!(settings.video.render_scale, MAX_RENDER_SCALE);
+        assert_eq!(settings.audio.music_volume, 1.0);
+        assert_eq!(settings.audio.sound_effects_volume, 1.0);
+        assert_eq!(settings.gameplay.seed, None);
+        assert_eq!(settings.gameplay.control_scheme, ControlScheme::Classic);
+        assert_eq!(settings.gameplay.players, MAX_PLAYERS);
+        assert_eq!(
+            settings.gameplay.player_devices,
+            defaults.gameplay.player_devices
+        );
+        assert_eq!(settings.gameplay.topology, Topology::Wrap);
+        assert_eq!(settings.controls.deadzone, MAX_DEADZONE);
+        assert_eq!(
+            settings.controls.bindings[&Action::Fire],
+            bindings(&["SPACE"])
+        );
+    }
+
+    #[test]
+    fn rejects_files_it_cant_read() {
+        for text in [
+            "",
+            "[video]\nwindow_width = 960\n",
+            "version = one\n",
+            "version = 0\n",
+            "version = 2\n",
+            "version = 1\n[video]\nfullscreen\n",
+        ] {
+            let error = Settings::parse(text).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn migrates_older_files_one_version_at_a_time() {
+        // a made up version 2 that renamed a key, then a version 3 that added one
+        let migrations: [Migration; 2] = [
+            |entries| {
+                if let Some(value) = entries.remove("video.scale") {
+                    entries.insert("video.render_scale".to_string(), value);
+                }
+            },
+            |entries| {
+                entries.insert("gameplay.players".to_string(), "2".to_string());
+            },
+        ];
+
+        let mut entries = parse_entries("version = 1\n[video]\nscale = 2\n").unwrap();
+        migrate(&mut entries, &migrations).unwrap();
+        assert_eq!(entries["video.render_scale"], "2");
+        assert_eq!(entries["gameplay.players"], "2");
+        assert!(!entries.contains_key("video.scale"));
+        assert!(!entries.contains_key("version"));
+
+        // a version 2 file only needs the second step
+        let mut entries = parse_entries("version = 2\n[video]\nscale = 2\n").unwrap();
+        migrate(&mut entries, &migrations).unwrap();
+        assert_eq!(entries["video.scale"], "2");
+        assert_eq!(entries["gameplay.players"], "2");
+
+        let mut entries = parse_entries("version = 4\n").unwrap();
+        assert!(migrate(&mut entries, &migrations).is_err());
+    }
+}
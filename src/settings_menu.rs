@@ -0,0 +1,247 @@
+use crate::{
+    modes::{self, Mode, ModeRegistry, Transition},
+    playing::{CONTROL_SCHEMES, MAX_PLAYERS},
+    settings::{
+        Settings, SettingsPath, MAX_DEADZONE, MAX_RENDER_SCALE, TARGET_FPS_OPTIONS, WINDOW_SIZES,
+    },
+    state::{GameMode, State},
+    topology::TOPOLOGIES,
+};
+
+const VOLUME_STEPS: f32 = 10.0;
+const DEADZONE_STEP: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsMenuItem {
+    WindowSize,
+    Fullscreen,
+    TargetFps,
+    RenderScale,
+    MusicVolume,
+    SoundEffectsVolume,
+    Deadzone,
+    ControlScheme,
+    Players,
+    FriendlyFire,
+    Topology,
+    Controls,
+    Back,
+}
+
+pub const SETTINGS_MENU_ITEMS: [SettingsMenuItem; 13] = [
+    SettingsMenuItem::WindowSize,
+    SettingsMenuItem::Fullscreen,
+    SettingsMenuItem::TargetFps,
+    SettingsMenuItem::RenderScale,
+    SettingsMenuItem::MusicVolume,
+    SettingsMenuItem::SoundEffectsVolume,
+    SettingsMenuItem::Deadzone,
+    SettingsMenuItem::ControlScheme,
+    SettingsMenuItem::Players,
+    SettingsMenuItem::FriendlyFire,
+    SettingsMenuItem::Topology,
+    SettingsMenuItem::Controls,
+    SettingsMenuItem::Back,
+];
+
+impl SettingsMenuItem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SettingsMenuItem::WindowSize => "window size",
+            SettingsMenuItem::Fullscreen => "fullscreen",
+            SettingsMenuItem::TargetFps => "target fps",
+            SettingsMenuItem::RenderScale => "render scale",
+            SettingsMenuItem::MusicVolume => "music volume",
+            SettingsMenuItem::SoundEffectsVolume => "sound volume",
+            SettingsMenuItem::Deadzone => "stick deadzone",
+            SettingsMenuItem::ControlScheme => "control scheme",
+            SettingsMenuItem::Players => "players",
+            SettingsMenuItem::FriendlyFire => "friendly fire",
+            SettingsMenuItem::Topology => "edges",
+            SettingsMenuItem::Controls => "controls",
+            SettingsMenuItem::Back => "back",
+        }
+    }
+
+    pub fn value(&self, settings: &Settings) -> Option<String> {
+        let value = match self {
+            SettingsMenuItem::WindowSize => format!(
+                "{}x{}",
+                settings.video.window_width, settings.video.window_height
+            ),
+            SettingsMenuItem::Fullscreen => on_off(settings.video.fullscreen).to_string(),
+            SettingsMenuItem::TargetFps => settings.video.target_fps.to_string(),
+            SettingsMenuItem::RenderScale => format!("{}x", settings.video.render_scale),
+            SettingsMenuItem::MusicVolume => percent(settings.audio.music_volume),
+            SettingsMenuItem::SoundEffectsVolume => percent(settings.audio.sound_effects_volume),
+            SettingsMenuItem::Deadzone => percent(settings.controls.deadzone),
+            SettingsMenuItem::ControlScheme => settings.gameplay.control_scheme.label().to_string(),
+            SettingsMenuItem::Players => settings.gameplay.players.to_string(),
+            SettingsMenuItem::FriendlyFire => on_off(settings.gameplay.friendly_fire).to_string(),
+            SettingsMenuItem::Topology => settings.gameplay.topology.label().to_string(),
+            SettingsMenuItem::Controls | SettingsMenuItem::Back => return None,
+        };
+        Some(value)
+    }
+
+    /** direction is 1 or -1, options wrap around except for volumes and the deadzone */
+    fn adjust(&self, settings: &mut Settings, direction: i32) {
+        let video = &mut settings.video;
+        let audio = &mut settings.audio;
+        let controls = &mut settings.controls;
+        let gameplay = &mut settings.gameplay;
+        match self {
+            SettingsMenuItem::WindowSize => {
+                let current = (video.window_width, video.window_height);
+                (video.window_width, video.window_height) =
+                    cycle(&WINDOW_SIZES, current, direction);
+            }
+            SettingsMenuItem::Fullscreen => video.fullscreen = !video.fullscreen,
+            SettingsMenuItem::TargetFps => {
+                video.target_fps = cycle(&TARGET_FPS_OPTIONS, video.target_fps, direction);
+            }
+            SettingsMenuItem::RenderScale => {
+                let scales: Vec<u32> = (1..=MAX_RENDER_SCALE).collect();
+                video.render_scale = cycle(&scales, video.render_scale, direction);
+            }
+            SettingsMenuItem::MusicVolume => {
+                audio.music_volume = step_volume(audio.music_volume, direction);
+            }
+            SettingsMenuItem::SoundEffectsVolume => {
+                audio.sound_effects_volume = step_volume(audio.sound_effects_volume, direction);
+            }
+            SettingsMenuItem::Deadzone => {
+                let steps = (controls.deadzone / DEADZONE_STEP).round() + direction as f32;
+                controls.deadzone = (steps * DEADZONE_STEP).clamp(0.0, MAX_DEADZONE);
+            }
+            SettingsMenuItem::ControlScheme => {
+                gameplay.control_scheme =
+                    cycle(&CONTROL_SCHEMES, gameplay.control_scheme, direction);
+            }
+            SettingsMenuItem::Players => {
+                let players: Vec<usize> = (1..=MAX_PLAYERS).collect();
+                gameplay.players = cycle(&players, gameplay.players, direction);
+            }
+            SettingsMenuItem::FriendlyFire => gameplay.friendly_fire = !gameplay.friendly_fire,
+            SettingsMenuItem::Topology => {
+                gameplay.topology = cycle(&TOPOLOGIES, gameplay.topology, direction);
+            }
+            SettingsMenuItem::Controls | SettingsMenuItem::Back => {}
+        }
+    }
+}
+
+// resource, only meaningful while the settings screen is open
+#[derive(Default)]
+pub struct SettingsMenu {
+    pub selected: usize,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct SettingsInputs {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub confirm: bool,
+    pub back: bool,
+}
+
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::Settings,
+        Mode {
+            on_enter,
+            on_exit,
+            ..Default::default()
+        },
+    );
+}
+
+fn on_enter(state: &mut State) {
+    state.resources.insert(SettingsMenu::default());
+}
+
+fn on_exit(state: &mut State) {
+    save(state);
+}
+
+/** edits apply straight away, the frontend picks them up from the Settings resource */
+pub fn handle_inputs(state: &mut State, inputs: SettingsInputs) {
+    if inputs.back {
+        modes::request(state, Transition::Pop);
+        return;
+    }
+
+    let selected = match state.resources.get_mut::<SettingsMenu>() {
+        Some(mut menu) => {
+            if inputs.up {
+                menu.selected =
+                    (menu.selected + SETTINGS_MENU_ITEMS.len() - 1) % SETTINGS_MENU_ITEMS.len();
+            }
+            if inputs.down {
+                menu.selected = (menu.selected + 1) % SETTINGS_MENU_ITEMS.len();
+            }
+            menu.selected
+        }
+        None => return,
+    };
+    let item = SETTINGS_MENU_ITEMS[selected];
+
+    if inputs.confirm && item == SettingsMenuItem::Back {
+        modes::request(state, Transition::Pop);
+        return;
+    }
+    if inputs.confirm && item == SettingsMenuItem::Controls {
+        modes::request(state, Transition::Push(GameMode::Controls));
+        return;
+    }
+
+    let direction = if inputs.left {
+        -1
+    } else if inputs.right || inputs.confirm {
+        1
+    } else {
+        return;
+    };
+    if let Some(mut settings) = state.resources.get_mut::<Settings>() {
+        item.adjust(&mut settings, direction);
+    }
+}
+
+pub fn save(state: &mut State) {
+    let path = match state.resources.get::<SettingsPath>() {
+        Some(path) => path.0.clone(),
+        None => return,
+    };
+    if let Some(settings) = state.resources.get::<Settings>() {
+        if let Err(e) = settings.save(&path) {
+            println!("Error saving settings {}: {}", path.display(), e);
+        }
+    }
+}
+
+// the next option in the given direction, or the first one if the current value is not an option
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, direction: i32) -> T {
+    let index = match options.iter().position(|option| *option == current) {
+        Some(index) => (index as i32 + direction).rem_euclid(options.len() as i32) as usize,
+        None => 0,
+    };
+    options[index]
+}
+
+fn step_volume(volume: f32, direction: i32) -> f32 {
+    ((volume * VOLUME_STEPS).round() + direction as f32).clamp(0.0, VOLUME_STEPS) / VOLUME_STEPS
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn percent(volume: f32) -> String {
+    format!("{}%", (volume * 100.0).round())
+}
@@ -0,0 +1,1200 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use glam::Vec2;
+use legion::{
+    storage::{
+        Archetype, ArchetypeSource, ArchetypeWriter, Component, ComponentSource, ComponentStorage,
+        ComponentTypeId, Components, EntityLayout,
+    },
+    world::{Allocate, Duplicate, Merger},
+    Entity, World,
+};
+use rand::SeedableRng;
+
+use crate::{
+    components::{
+        Asteroid, Attachable, AttachedTo, Beam, Bullet, CTransform, CaptureInPlayField, Collider,
+        ColliderShape, Damage, Enemy, ExtraLives, GrabZone, Gun, Health, InputControlled,
+        Invulnerable, Layers, LifeSpan, LookAt, OwnedBy, Physics, Pickup, Player, PlayerSlot,
+        PowerupKind, Powerups, PreviousCTransform, Respawning, Score, VelocityUncapped,
+        WantsToGoTo, MAX_POLYGON_POINTS,
+    },
+    message_stream::{ExpiringMessage, ExpiringMessages},
+    modes::{self, Transition},
+    playing::MAX_PLAYERS,
+    replay::{
+        control_scheme_byte, control_scheme_from_byte, invalid_data, read_array, read_u8,
+        topology_byte, topology_from_byte, ReplayRecorder,
+    },
+    rng::{self, Seed, SimulationRng},
+    schedules, settings,
+    state::{GameMode, RunConfig, RunSteps, State},
+    timer::{AsteroidSpawnTimer, EnemySpawnTimer, GunSpawnTimer, TypedTimer},
+    topology::Camera,
+};
+
+const MAGIC: &[u8; 4] = b"ECSS";
+const VERSION: u8 = 1;
+const QUICK_SAVE_FILE_NAME: &str = "quicksave.snapshot";
+// how long the quick save and load messages stay up, in steps
+const MESSAGE_LIFETIME: u32 = 90;
+
+// everything a playing step changes, kept in memory so the simulation can be rewound
+pub struct Snapshot {
+    run_config: RunConfig,
+    world: World,
+    rng: SimulationRng,
+    asteroid_spawn_timer: AsteroidSpawnTimer,
+    gun_spawn_timer: GunSpawnTimer,
+    enemy_spawn_timer: EnemySpawnTimer,
+    run_steps: RunSteps,
+    camera: Camera,
+    expiring_messages: ExpiringMessages,
+}
+
+// resource, where quick saves are written to and read from
+pub struct QuickSavePath(pub PathBuf);
+
+// resource, a snapshot the next playing run starts from instead of a fresh world
+pub struct PendingSnapshot(pub Snapshot);
+
+impl Snapshot {
+    pub fn capture(state: &State) -> Self {
+        Self {
+            run_config: cloned_resource(state),
+            world: copy_world(&state.ecs),
+            rng: cloned_resource(state),
+            asteroid_spawn_timer: cloned_resource(state),
+            gun_spawn_timer: cloned_resource(state),
+            enemy_spawn_timer: cloned_resource(state),
+            run_steps: cloned_resource(state),
+            camera: cloned_resource(state),
+            expiring_messages: cloned_resource(state),
+        }
+    }
+
+    /** the world is rebuilt rather than cleared so its archetypes come back in the same order,
+    which keeps query order, and so the rest of the run, the same as if it had never been rewound */
+    pub fn restore(&self, state: &mut State) {
+        state.ecs = copy_world(&self.world);
+        // systems keep a command buffer for every world they have run on
+        state.playing_schedule = schedules::build_play_schedule();
+        state.playing_render_schedule = schedules::build_play_render_schedule();
+
+        state.resources.insert(self.run_config);
+        state.resources.insert(self.rng.clone());
+        state.resources.insert(self.asteroid_spawn_timer.clone());
+        state.resources.insert(self.gun_spawn_timer.clone());
+        state.resources.insert(self.enemy_spawn_timer.clone());
+        state.resources.insert(self.run_steps);
+        state.resources.insert(self.camera);
+        state.resources.insert(self.expiring_messages.clone());
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Self::read_from(&mut bytes.as_slice())
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&self.to_bytes())?;
+        writer.flush()
+    }
+
+    /** entities are saved as their place in the file, so the ones held in components can be pointed at
+    whatever entities they become on loading. archetypes are saved in order, empty ones too, so a loaded
+    world iterates in the same order as the one that was saved and plays on the same way */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut lister = ArchetypeLister::default();
+        World::default().clone_from(&self.world, &legion::any(), &mut lister);
+
+        // written first, the entity count at the top includes entities that are only held in components
+        let mut ids = EntityIds::default();
+        let mut archetypes = Vec::new();
+        put_u32(&mut archetypes, lister.archetypes.len() as u32);
+        for archetype in lister.archetypes.iter() {
+            archetypes.push(archetype.kinds.len() as u8);
+            for kind in archetype.kinds.iter() {
+                archetypes.push(*kind as u8);
+            }
+            put_u32(&mut archetypes, archetype.entities.len() as u32);
+            for entity in archetype.entities.iter() {
+                put_u32(&mut archetypes, ids.index(*entity));
+            }
+            for (kind, column) in archetype.kinds.iter().zip(archetype.columns.iter()) {
+                (COMPONENT_KINDS[*kind].write_column)(column.as_ref(), &mut ids, &mut archetypes);
+            }
+        }
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        put_u64(&mut bytes, self.run_config.seed);
+        bytes.push(control_scheme_byte(self.run_config.control_scheme));
+        bytes.push(self.run_config.players as u8);
+        bytes.push(self.run_config.friendly_fire as u8);
+        bytes.push(topology_byte(self.run_config.topology));
+
+        bytes.extend_from_slice(&self.rng.get_seed());
+        put_u64(&mut bytes, self.rng.get_stream());
+        bytes.extend_from_slice(&self.rng.get_word_pos().to_le_bytes());
+        put_timer(&mut bytes, &self.asteroid_spawn_timer);
+        put_timer(&mut bytes, &self.gun_spawn_timer);
+        put_timer(&mut bytes, &self.enemy_spawn_timer);
+        put_u32(&mut bytes, self.run_steps.0);
+        put_vec2(&mut bytes, self.camera.pos);
+        put_vec2(&mut bytes, self.camera.previous);
+
+        put_u32(&mut bytes, self.expiring_messages.len() as u32);
+        for message in self.expiring_messages.iter() {
+            put_u32(&mut bytes, message.lifetime);
+            put_u32(&mut bytes, message.text.len() as u32);
+            bytes.extend_from_slice(message.text.as_bytes());
+        }
+
+        put_u32(&mut bytes, ids.count);
+        bytes.extend_from_slice(&archetypes);
+        bytes
+    }
+
+    pub fn read_from(reader: &mut &[u8]) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a snapshot file"));
+        }
+        let version = read_u8(reader)?;
+        if version != VERSION {
+            return Err(invalid_data(&format!(
+                "unsupported snapshot version {}",
+                version
+            )));
+        }
+
+        let run_config = RunConfig {
+            seed: read_u64(reader)?,
+            control_scheme: control_scheme_from_byte(read_u8(reader)?)?,
+            players: read_u8(reader)? as usize,
+            friendly_fire: read_u8(reader)? != 0,
+            topology: topology_from_byte(read_u8(reader)?)?,
+        };
+        if run_config.players == 0 || run_config.players > MAX_PLAYERS {
+            return Err(invalid_data(&format!(
+                "unsupported player count {}",
+                run_config.players
+            )));
+        }
+        let mut rng = SimulationRng::from_seed(read_array(reader)?);
+        rng.set_stream(read_u64(reader)?);
+        rng.set_word_pos(u128::from_le_bytes(read_array(reader)?));
+        let asteroid_spawn_timer = read_timer(reader)?;
+        let gun_spawn_timer = read_timer(reader)?;
+        let enemy_spawn_timer = read_timer(reader)?;
+        let run_steps = RunSteps(read_u32(reader)?);
+        let camera = Camera {
+            pos: read_vec2(reader)?,
+            previous: read_vec2(reader)?,
+        };
+
+        let message_count = read_u32(reader)?;
+        let mut expiring_messages = ExpiringMessages::new();
+        for _ in 0..message_count {
+            let lifetime = read_u32(reader)?;
+            let mut text = vec![0u8; read_len(reader)?];
+            reader.read_exact(&mut text)?;
+            let text = String::from_utf8(text).map_err(|_| invalid_data("message is not utf-8"))?;
+            expiring_messages.push(ExpiringMessage { text, lifetime });
+        }
+
+        // entities that are only held in components are never added, so they stay missing like they were
+        let entity_count = read_len(reader)?;
+        let entities: Vec<Entity> = Allocate::new().take(entity_count).collect();
+        let mut world = World::default();
+        let archetype_count = read_u32(reader)?;
+        for _ in 0..archetype_count {
+            let mut archetype = SavedArchetype::default();
+            for _ in 0..read_u8(reader)? {
+                let kind = read_u8(reader)? as usize;
+                if kind >= COMPONENT_KINDS.len() || archetype.kinds.contains(&kind) {
+                    return Err(invalid_data(&format!("bad component kind {}", kind)));
+                }
+                archetype.kinds.push(kind);
+            }
+            let len = read_u32(reader)?;
+            for _ in 0..len {
+                archetype.entities.push(read_entity(reader, &entities)?);
+            }
+            for kind in archetype.kinds.iter() {
+                let column = (COMPONENT_KINDS[*kind].read_column)(reader, len, &entities)?;
+                archetype.columns.push(column);
+            }
+            world.extend(archetype);
+        }
+
+        Ok(Self {
+            run_config,
+            world,
+            rng,
+            asteroid_spawn_timer,
+            gun_spawn_timer,
+            enemy_spawn_timer,
+            run_steps,
+            camera,
+            expiring_messages,
+        })
+    }
+}
+
+/** next to the settings file, or the working directory when there's no config directory */
+pub fn default_quick_save_path() -> PathBuf {
+    match settings::config_dir() {
+        Some(dir) => dir.join(QUICK_SAVE_FILE_NAME),
+        None => PathBuf::from(QUICK_SAVE_FILE_NAME),
+    }
+}
+
+pub fn quick_save(state: &mut State) {
+    let path = match state.resources.get::<QuickSavePath>() {
+        Some(path) => path.0.clone(),
+        None => return,
+    };
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            println!("Error saving snapshot {}: {}", path.display(), e);
+            return;
+        }
+    }
+    match Snapshot::capture(state).save(&path) {
+        Ok(()) => {
+            println!("Saved snapshot to {}", path.display());
+            show_message(state, "quick saved");
+        }
+        Err(e) => println!("Error saving snapshot {}: {}", path.display(), e),
+    }
+}
+
+pub fn quick_load(state: &mut State) {
+    let path = match state.resources.get::<QuickSavePath>() {
+        Some(path) => path.0.clone(),
+        None => return,
+    };
+    match Snapshot::load(&path) {
+        Ok(snapshot) => {
+            load_into(state, &snapshot);
+            show_message(state, "quick loaded");
+        }
+        Err(e) => println!("Error loading snapshot {}: {}", path.display(), e),
+    }
+}
+
+/** the next playing run picks up where the snapshot left off */
+pub fn start_from_snapshot(state: &mut State, snapshot: Snapshot) {
+    state.resources.insert(PendingSnapshot(snapshot));
+    modes::request(state, Transition::Reset(GameMode::Playing));
+}
+
+/** like restoring for a rewind, but the run can come from anywhere so everything derived from it is reset too */
+pub fn load_into(state: &mut State, snapshot: &Snapshot) {
+    snapshot.restore(state);
+    let seed = snapshot.run_config.seed;
+    state.resources.insert(Seed(seed));
+    state.resources.insert(rng::render_rng(seed));
+    state.resources.insert(rng::audio_rng(seed));
+
+    // replays start from a seed, not from the middle of a run
+    if state.resources.remove::<ReplayRecorder>().is_some() {
+        println!("Stopped recording the replay, a loaded snapshot can't be replayed");
+    }
+}
+
+fn show_message(state: &mut State, text: &str) {
+    if let Some(mut expiring_messages) = state.resources.get_mut::<ExpiringMessages>() {
+        expiring_messages.push(ExpiringMessage {
+            text: text.to_string(),
+            lifetime: MESSAGE_LIFETIME,
+        });
+    }
+}
+
+fn cloned_resource<T: Clone + 'static>(state: &State) -> T {
+    state
+        .resources
+        .get::<T>()
+        .map(|resource| resource.clone())
+        .expect("simulation resources are inserted before a run starts")
+}
+
+fn copy_world(source: &World) -> World {
+    let mut world = World::default();
+    world.clone_from(source, &legion::any(), &mut KeepIds(duplicate_components()));
+    world
+}
+
+fn duplicate_components() -> Duplicate {
+    let mut duplicate = Duplicate::default();
+    for kind in COMPONENT_KINDS.iter() {
+        (kind.register_copy)(&mut duplicate);
+    }
+    duplicate
+}
+
+// copies keep their entity ids, so the entities held in components still point at the right ones
+struct KeepIds(Duplicate);
+
+impl Merger for KeepIds {
+    fn assign_id(&mut self, existing: Entity, _allocator: &mut Allocate) -> Entity {
+        existing
+    }
+
+    fn convert_layout(&mut self, source_layout: EntityLayout) -> EntityLayout {
+        self.0.convert_layout(source_layout)
+    }
+
+    fn merge_archetype(
+        &mut self,
+        src_entity_range: Range<usize>,
+        src_arch: &Archetype,
+        src_components: &Components,
+        dst: &mut ArchetypeWriter,
+    ) {
+        self.0
+            .merge_archetype(src_entity_range, src_arch, src_components, dst);
+    }
+}
+
+// a component type that snapshots know how to copy and save
+trait SavedComponent: Component + Copy {
+    fn write(&self, bytes: &mut Vec<u8>, ids: &mut EntityIds);
+    fn read(reader: &mut &[u8], entities: &[Entity]) -> io::Result<Self>;
+}
+
+// a Vec of one component type, for one archetype
+type Column = Box<dyn Any>;
+
+struct ComponentKind {
+    type_id: fn() -> ComponentTypeId,
+    register_copy: fn(&mut Duplicate),
+    register: fn(&mut EntityLayout),
+    copy_column: fn(&Archetype, &Components) -> Column,
+    write_column: fn(&dyn Any, &mut EntityIds, &mut Vec<u8>),
+    read_column: fn(&mut &[u8], u32, &[Entity]) -> io::Result<Column>,
+    push_column: fn(&dyn Any, &mut ArchetypeWriter),
+}
+
+impl ComponentKind {
+    const fn of<T: SavedComponent>() -> Self {
+        Self {
+            type_id: ComponentTypeId::of::<T>,
+            register_copy: |duplicate| duplicate.register_copy::<T>(),
+            register: |layout| layout.register_component::<T>(),
+            copy_column: |archetype, components| {
+                let column: Vec<T> = components
+                    .get_downcast::<T>()
+                    .and_then(|storage| storage.get(archetype.index()))
+                    .map(|slice| slice.into_slice().to_vec())
+                    .unwrap_or_default();
+                Box::new(column)
+            },
+            write_column: |column, ids, bytes| {
+                for component in downcast_column::<T>(column) {
+                    component.write(bytes, ids);
+                }
+            },
+            read_column: |reader, len, entities| {
+                let mut column: Vec<T> = Vec::with_capacity(len as usize);
+                for _ in 0..len {
+                    column.push(T::read(reader, entities)?);
+                }
+                Ok(Box::new(column))
+            },
+            push_column: |column, writer| {
+                let column = downcast_column::<T>(column);
+                // the components are Copy, so copying their bytes is a clone and nothing is dropped twice
+                unsafe {
+                    writer
+                        .claim_components::<T>()
+                        .extend_memcopy(column.as_ptr(), column.len());
+                }
+            },
+        }
+    }
+}
+
+// the order here is the file format, new kinds go at the end. kinds left out of here silently vanish
+// from snapshots
+const COMPONENT_KINDS: [ComponentKind; 29] = [
+    ComponentKind::of::<CTransform>(),
+    ComponentKind::of::<PreviousCTransform>(),
+    ComponentKind::of::<Player>(),
+    ComponentKind::of::<Gun>(),
+    ComponentKind::of::<Bullet>(),
+    ComponentKind::of::<LifeSpan>(),
+    ComponentKind::of::<Asteroid>(),
+    ComponentKind::of::<InputControlled>(),
+    ComponentKind::of::<PlayerSlot>(),
+    ComponentKind::of::<Health>(),
+    ComponentKind::of::<VelocityUncapped>(),
+    ComponentKind::of::<Physics>(),
+    ComponentKind::of::<CaptureInPlayField>(),
+    ComponentKind::of::<Score>(),
+    ComponentKind::of::<OwnedBy>(),
+    ComponentKind::of::<AttachedTo>(),
+    ComponentKind::of::<GrabZone>(),
+    ComponentKind::of::<Attachable>(),
+    ComponentKind::of::<WantsToGoTo>(),
+    ComponentKind::of::<LookAt>(),
+    ComponentKind::of::<Enemy>(),
+    ComponentKind::of::<Respawning>(),
+    ComponentKind::of::<ExtraLives>(),
+    ComponentKind::of::<Invulnerable>(),
+    ComponentKind::of::<Damage>(),
+    ComponentKind::of::<Collider>(),
+    ComponentKind::of::<Beam>(),
+    ComponentKind::of::<Pickup>(),
+    ComponentKind::of::<Powerups>(),
+];
+
+fn downcast_column<T: 'static>(column: &dyn Any) -> &[T] {
+    column
+        .downcast_ref::<Vec<T>>()
+        .expect("columns are made by the kind that reads them")
+}
+
+// one archetype's layout, entities and components, on their way into or out of a file
+#[derive(Default)]
+struct SavedArchetype {
+    kinds: Vec<usize>,
+    entities: Vec<Entity>,
+    columns: Vec<Column>,
+}
+
+impl ArchetypeSource for SavedArchetype {
+    type Filter = EntityLayout;
+
+    fn filter(&self) -> Self::Filter {
+        self.layout_of_kinds()
+    }
+
+    fn layout(&mut self) -> EntityLayout {
+        self.layout_of_kinds()
+    }
+}
+
+impl ComponentSource for SavedArchetype {
+    // the entities were allocated when the file was read, so the ones offered here go unused
+    fn push_components<'a>(
+        &mut self,
+        writer: &mut ArchetypeWriter<'a>,
+        _entities: impl Iterator<Item = Entity>,
+    ) {
+        for entity in self.entities.iter() {
+            writer.push(*entity);
+        }
+        for (kind, column) in self.kinds.iter().zip(self.columns.iter()) {
+            (COMPONENT_KINDS[*kind].push_column)(column.as_ref(), writer);
+        }
+    }
+}
+
+impl legion::storage::IntoComponentSource for SavedArchetype {
+    type Source = Self;
+
+    fn into(self) -> Self::Source {
+        self
+    }
+}
+
+impl SavedArchetype {
+    fn layout_of_kinds(&self) -> EntityLayout {
+        let mut layout = EntityLayout::new();
+        for kind in self.kinds.iter() {
+            (COMPONENT_KINDS[*kind].register)(&mut layout);
+        }
+        layout
+    }
+}
+
+// walks a world's archetypes in order, copying each one out, and leaves nothing behind in the destination
+#[derive(Default)]
+struct ArchetypeLister {
+    archetypes: Vec<SavedArchetype>,
+}
+
+impl Merger for ArchetypeLister {
+    fn assign_id(&mut self, existing: Entity, _allocator: &mut Allocate) -> Entity {
+        existing
+    }
+
+    fn convert_layout(&mut self, _source_layout: EntityLayout) -> EntityLayout {
+        EntityLayout::new()
+    }
+
+    fn merge_archetype(
+        &mut self,
+        src_entity_range: Range<usize>,
+        src_arch: &Archetype,
+        src_components: &Components,
+        _dst: &mut ArchetypeWriter,
+    ) {
+        let mut archetype = SavedArchetype {
+            entities: src_arch.entities()[src_entity_range].to_vec(),
+            ..Default::default()
+        };
+        for type_id in src_arch.layout().component_types() {
+            match COMPONENT_KINDS
+                .iter()
+                .position(|kind| (kind.type_id)() == *type_id)
+            {
+                Some(kind) => {
+                    archetype.kinds.push(kind);
+                    let column = (COMPONENT_KINDS[kind].copy_column)(src_arch, src_components);
+                    archetype.columns.push(column);
+                }
+                None => println!("Component {} can't be saved in snapshots", type_id),
+            }
+        }
+        self.archetypes.push(archetype);
+    }
+}
+
+// numbers entities in the order they are first seen
+#[derive(Default)]
+struct EntityIds {
+    ids: HashMap<Entity, u32>,
+    count: u32,
+}
+
+impl EntityIds {
+    fn index(&mut self, entity: Entity) -> u32 {
+        let count = &mut self.count;
+        *self.ids.entry(entity).or_insert_with(|| {
+            *count += 1;
+            *count - 1
+        })
+    }
+}
+
+impl SavedComponent for CTransform {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_vec2(bytes, self.pos);
+        put_vec2(bytes, self.rot);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            pos: read_vec2(reader)?,
+            rot: read_vec2(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for PreviousCTransform {
+    fn write(&self, bytes: &mut Vec<u8>, ids: &mut EntityIds) {
+        self.0.write(bytes, ids);
+    }
+
+    fn read(reader: &mut &[u8], entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self(CTransform::read(reader, entities)?))
+    }
+}
+
+impl SavedComponent for Gun {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        bytes.push(self.wants_to_shoot as u8);
+        put_u32(bytes, self.fire_delay);
+        put_u32(bytes, self.cooldown);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            wants_to_shoot: read_u8(reader)? != 0,
+            fire_delay: read_u32(reader)?,
+            cooldown: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for LifeSpan {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_u32(bytes, self.frames_left);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            frames_left: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for Asteroid {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_u32(bytes, self.size);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            size: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for InputControlled {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        bytes.push(self.player as u8);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            player: read_u8(reader)? as usize,
+        })
+    }
+}
+
+impl SavedComponent for PlayerSlot {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        bytes.push(self.index as u8);
+        put_u32(bytes, self.lives);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            index: read_u8(reader)? as usize,
+            lives: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for Health {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_u32(bytes, self.hp);
+        put_u32(bytes, self.max_hp);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            hp: read_u32(reader)?,
+            max_hp: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for Damage {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_u32(bytes, self.amount);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            amount: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for Physics {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_vec2(bytes, self.vel);
+        put_f32(bytes, self.rot_vel);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            vel: read_vec2(reader)?,
+            rot_vel: read_f32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for Score {
+    fn write(&self, bytes: &mut Vec<u8>, ids: &mut EntityIds) {
+        put_u32(bytes, ids.index(self.owner));
+        put_u32(bytes, self.score);
+    }
+
+    fn read(reader: &mut &[u8], entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            owner: read_entity(reader, entities)?,
+            score: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for OwnedBy {
+    fn write(&self, bytes: &mut Vec<u8>, ids: &mut EntityIds) {
+        put_u32(bytes, ids.index(self.owner));
+    }
+
+    fn read(reader: &mut &[u8], entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            owner: read_entity(reader, entities)?,
+        })
+    }
+}
+
+impl SavedComponent for AttachedTo {
+    fn write(&self, bytes: &mut Vec<u8>, ids: &mut EntityIds) {
+        put_u32(bytes, ids.index(self.entity));
+        put_vec2(bytes, self.offset);
+    }
+
+    fn read(reader: &mut &[u8], entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            entity: read_entity(reader, entities)?,
+            offset: read_vec2(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for GrabZone {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_f32(bytes, self.radius);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            radius: read_f32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for WantsToGoTo {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_vec2(bytes, self.pos);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            pos: read_vec2(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for LookAt {
+    fn write(&self, bytes: &mut Vec<u8>, ids: &mut EntityIds) {
+        put_u32(bytes, ids.index(self.entity));
+    }
+
+    fn read(reader: &mut &[u8], entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            entity: read_entity(reader, entities)?,
+        })
+    }
+}
+
+impl SavedComponent for Respawning {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_u32(bytes, self.steps_left);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            steps_left: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for ExtraLives {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_u32(bytes, self.awarded);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            awarded: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for Invulnerable {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_u32(bytes, self.steps_left);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            steps_left: read_u32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for Collider {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_collider_shape(bytes, &self.shape);
+        put_u16(bytes, self.layer.0);
+        put_u16(bytes, self.mask.0);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            shape: read_collider_shape(reader)?,
+            layer: Layers(read_u16(reader)?),
+            mask: Layers(read_u16(reader)?),
+        })
+    }
+}
+
+fn put_collider_shape(bytes: &mut Vec<u8>, shape: &ColliderShape) {
+    match shape {
+        ColliderShape::Circle { radius } => {
+            bytes.push(0);
+            put_f32(bytes, *radius);
+        }
+        ColliderShape::Polygon { points, len } => {
+            bytes.push(1);
+            bytes.push(*len as u8);
+            for point in points[..*len].iter() {
+                put_vec2(bytes, *point);
+            }
+        }
+        ColliderShape::Segment { end } => {
+            bytes.push(2);
+            put_vec2(bytes, *end);
+        }
+    }
+}
+
+fn read_collider_shape(reader: &mut &[u8]) -> io::Result<ColliderShape> {
+    match read_u8(reader)? {
+        0 => Ok(ColliderShape::circle(read_f32(reader)?)),
+        1 => {
+            let len = read_u8(reader)? as usize;
+            if len > MAX_POLYGON_POINTS {
+                return Err(invalid_data(&format!("polygon has {} corners", len)));
+            }
+            let mut points = [Vec2::ZERO; MAX_POLYGON_POINTS];
+            for point in points[..len].iter_mut() {
+                *point = read_vec2(reader)?;
+            }
+            Ok(ColliderShape::Polygon { points, len })
+        }
+        2 => Ok(ColliderShape::segment(read_vec2(reader)?)),
+        byte => Err(invalid_data(&format!("unknown collider {}", byte))),
+    }
+}
+
+impl SavedComponent for Beam {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_vec2(bytes, self.dir);
+        put_f32(bytes, self.length);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            dir: read_vec2(reader)?,
+            length: read_f32(reader)?,
+        })
+    }
+}
+
+impl SavedComponent for Pickup {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        bytes.push(powerup_kind_byte(self.kind));
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            kind: powerup_kind_from_byte(read_u8(reader)?)?,
+        })
+    }
+}
+
+impl SavedComponent for Powerups {
+    fn write(&self, bytes: &mut Vec<u8>, _ids: &mut EntityIds) {
+        put_u32(bytes, self.bigger_bullets);
+        put_u32(bytes, self.longer_range);
+        put_u32(bytes, self.faster_shooting);
+        put_u32(bytes, self.streams);
+        put_u32(bytes, self.streams_steps_left);
+        put_u32(bytes, self.laser_steps_left);
+    }
+
+    fn read(reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+        Ok(Self {
+            bigger_bullets: read_u32(reader)?,
+            longer_range: read_u32(reader)?,
+            faster_shooting: read_u32(reader)?,
+            streams: read_u32(reader)?,
+            streams_steps_left: read_u32(reader)?,
+            laser_steps_left: read_u32(reader)?,
+        })
+    }
+}
+
+fn powerup_kind_byte(kind: PowerupKind) -> u8 {
+    match kind {
+        PowerupKind::BiggerBullets => 0,
+        PowerupKind::LongerRange => 1,
+        PowerupKind::FasterShooting => 2,
+        PowerupKind::TwoStreams => 3,
+        PowerupKind::ThreeStreams => 4,
+        PowerupKind::Laser => 5,
+    }
+}
+
+fn powerup_kind_from_byte(byte: u8) -> io::Result<PowerupKind> {
+    match byte {
+        0 => Ok(PowerupKind::BiggerBullets),
+        1 => Ok(PowerupKind::LongerRange),
+        2 => Ok(PowerupKind::FasterShooting),
+        3 => Ok(PowerupKind::TwoStreams),
+        4 => Ok(PowerupKind::ThreeStreams),
+        5 => Ok(PowerupKind::Laser),
+        _ => Err(invalid_data(&format!("unknown powerup {}", byte))),
+    }
+}
+
+// markers, all there is to save is that the entity has them
+macro_rules! saved_marker {
+    ($($marker:ident),*) => {
+        $(
+            impl SavedComponent for $marker {
+                fn write(&self, _bytes: &mut Vec<u8>, _ids: &mut EntityIds) {}
+
+                fn read(_reader: &mut &[u8], _entities: &[Entity]) -> io::Result<Self> {
+                    Ok($marker)
+                }
+            }
+        )*
+    };
+}
+
+saved_marker!(
+    Player,
+    Bullet,
+    VelocityUncapped,
+    CaptureInPlayField,
+    Attachable,
+    Enemy
+);
+
+fn put_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_f32(bytes: &mut Vec<u8>, value: f32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_vec2(bytes: &mut Vec<u8>, value: Vec2) {
+    put_f32(bytes, value.x);
+    put_f32(bytes, value.y);
+}
+
+fn put_timer<T>(bytes: &mut Vec<u8>, timer: &TypedTimer<T>) {
+    put_u32(bytes, timer.get_interval());
+    put_u32(bytes, timer.get_countdown());
+}
+
+fn read_u16(reader: &mut &[u8]) -> io::Result<u16> {
+    Ok(u16::from_le_bytes(read_array(reader)?))
+}
+
+fn read_u32(reader: &mut &[u8]) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(read_array(reader)?))
+}
+
+fn read_u64(reader: &mut &[u8]) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(read_array(reader)?))
+}
+
+// a count of things still to come, each at least a byte, so a broken file can't ask for a huge allocation
+fn read_len(reader: &mut &[u8]) -> io::Result<usize> {
+    let len = read_u32(reader)? as usize;
+    if len > reader.len() {
+        return Err(invalid_data(&format!("length {} runs past the end", len)));
+    }
+    Ok(len)
+}
+
+fn read_f32(reader: &mut &[u8]) -> io::Result<f32> {
+    Ok(f32::from_le_bytes(read_array(reader)?))
+}
+
+fn read_vec2(reader: &mut &[u8]) -> io::Result<Vec2> {
+    Ok(Vec2::new(read_f32(reader)?, read_f32(reader)?))
+}
+
+fn read_timer<T>(reader: &mut &[u8]) -> io::Result<TypedTimer<T>> {
+    let interval = read_u32(reader)?;
+    let countdown = read_u32(reader)?;
+    Ok(TypedTimer::new(interval, countdown))
+}
+
+fn read_entity(reader: &mut &[u8], entities: &[Entity]) -> io::Result<Entity> {
+    let index = read_u32(reader)?;
+    entities
+        .get(index as usize)
+        .copied()
+        .ok_or_else(|| invalid_data(&format!("entity {} is out of range", index)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use legion::IntoQuery;
+
+    use super::*;
+    use crate::{
+        playing::{self, PlayerInputs, PlayingInputs},
+        systems::playing::{
+            collision::shot_collider, init_state::init_with_config, powerups::pickup_collider,
+        },
+        topology::Topology,
+    };
+
+    fn step_with_inputs(state: &mut State, steps: u32) {
+        for step in 0..steps {
+            let inputs = PlayingInputs {
+                turn: ((step / 25) % 3) as f32 - 1.0,
+                thrust: 0.5,
+                shoot: step % 5 == 0,
+            };
+            state.resources.insert(PlayerInputs([inputs; MAX_PLAYERS]));
+            playing::step(state);
+        }
+    }
+
+    /** a run a little way in, with something of every saved kind in it */
+    fn busy_state() -> State {
+        let mut state = State::new();
+        init_with_config(
+            &mut state,
+            RunConfig {
+                seed: 3,
+                players: 2,
+                friendly_fire: true,
+                topology: Topology::Open,
+                ..Default::default()
+            },
+        );
+        step_with_inputs(&mut state, 120);
+
+        let ship = <(Entity, &Player)>::query()
+            .iter(&state.ecs)
+            .map(|(entity, _)| *entity)
+            .next()
+            .unwrap();
+        let at = CTransform {
+            pos: Vec2::new(50.0, 60.0),
+            rot: Vec2::X,
+        };
+        state.ecs.push((
+            at,
+            Beam {
+                dir: Vec2::X,
+                length: 240.0,
+            },
+            shot_collider(
+                ColliderShape::segment(Vec2::new(240.0, 0.0)),
+                Layers::PLAYER,
+                true,
+            ),
+            Damage { amount: 1 },
+            OwnedBy { owner: ship },
+            LifeSpan { frames_left: 3 },
+        ));
+        state.ecs.push((
+            at,
+            Pickup {
+                kind: PowerupKind::ThreeStreams,
+            },
+            pickup_collider(),
+        ));
+        state.ecs.push((
+            Respawning { steps_left: 10 },
+            Invulnerable { steps_left: 20 },
+            WantsToGoTo {
+                pos: Vec2::new(1.0, 2.0),
+            },
+            LookAt { entity: ship },
+        ));
+        state
+    }
+
+    fn saved_kinds(world: &World) -> HashSet<usize> {
+        let mut lister = ArchetypeLister::default();
+        World::default().clone_from(world, &legion::any(), &mut lister);
+        lister
+            .archetypes
+            .iter()
+            .flat_map(|archetype| archetype.kinds.iter().copied())
+            .collect()
+    }
+
+    fn transforms(state: &State) -> Vec<(CTransform, Option<Vec2>)> {
+        <(&CTransform, Option<&Physics>)>::query()
+            .iter(&state.ecs)
+            .map(|(transform, physics)| (*transform, physics.map(|physics| physics.vel)))
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_a_world_through_bytes() {
+        let state = busy_state();
+        assert_eq!(saved_kinds(&state.ecs).len(), COMPONENT_KINDS.len());
+
+        let bytes = Snapshot::capture(&state).to_bytes();
+        let loaded = Snapshot::read_from(&mut bytes.as_slice()).unwrap();
+        let mut restored = State::new();
+        load_into(&mut restored, &loaded);
+
+        assert_eq!(transforms(&restored), transforms(&state));
+        assert_eq!(
+            <&Score>::query().iter(&restored.ecs).count(),
+            <&Score>::query().iter(&state.ecs).count()
+        );
+        // entities come back with new ids, but saved again they are numbered the same
+        assert_eq!(Snapshot::capture(&restored).to_bytes(), bytes);
+    }
+
+    #[test]
+    fn a_restored_world_plays_on_the_same() {
+        let mut state = busy_state();
+        let bytes = Snapshot::capture(&state).to_bytes();
+        let mut restored = State::new();
+        load_into(
+            &mut restored,
+            &Snapshot::read_from(&mut bytes.as_slice()).unwrap(),
+        );
+
+        step_with_inputs(&mut state, 200);
+        step_with_inputs(&mut restored, 200);
+        assert_eq!(
+            Snapshot::capture(&restored).to_bytes(),
+            Snapshot::capture(&state).to_bytes()
+        );
+    }
+
+    #[test]
+    fn rejects_broken_files() {
+        let bytes = Snapshot::capture(&busy_state()).to_bytes();
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = VERSION + 1;
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'X';
+        for broken in [
+            &wrong_version[..],
+            &wrong_magic[..],
+            &bytes[..bytes.len() - 1],
+            &bytes[..5],
+        ] {
+            assert!(Snapshot::read_from(&mut &broken[..]).is_err());
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+
+use glam::{IVec2, Vec2};
+use legion::Entity;
+
+use crate::{torus, DIMS};
+
+// wide enough that most things sit in one or two cells, small enough that a cell holds only a few
+pub const CELL_SIZE: f32 = 16.0;
+
+// something in the grid, with how far from its position it can touch anything
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Body {
+    pub entity: Entity,
+    pub pos: Vec2,
+    pub radius: f32,
+    // whether it goes round the edges of the play field, anything still coming in from off it doesn't
+    pub wraps: bool,
+}
+
+// resource, a uniform grid of cells lined up with the screen's worth of field from origin, rebuilt
+// every step so collisions and searches only look at what is nearby. when the field wraps round the
+// cells over the screen do too, so things by one edge are found from the other. cells are kept by
+// where they are rather than in a fixed array, so anything off the screen, which on an open field can
+// be a long way off, gets cells of its own instead of piling up in the edge ones. queries give back
+// indices into bodies in the order they were inserted, so results never depend on hashing
+pub struct SpatialHash {
+    cols: i32,
+    rows: i32,
+    origin: Vec2,
+    wraps: bool,
+    bodies: Vec<Body>,
+    cells: HashMap<IVec2, Vec<usize>>,
+    // the first and last column and row with anything in, as far as any search has to go
+    extent: Option<(IVec2, IVec2)>,
+}
+
+impl Default for SpatialHash {
+    fn default() -> Self {
+        Self {
+            cols: (DIMS.x as f32 / CELL_SIZE).ceil() as i32,
+            rows: (DIMS.y as f32 / CELL_SIZE).ceil() as i32,
+            origin: Vec2::ZERO,
+            wraps: true,
+            bodies: Vec::new(),
+            cells: HashMap::new(),
+            extent: None,
+        }
+    }
+}
+
+impl SpatialHash {
+    pub fn clear(&mut self, origin: Vec2, wraps: bool) {
+        self.origin = origin;
+        self.wraps = wraps;
+        self.bodies.clear();
+        self.extent = None;
+        // cells that were used last step are likely to be used again, the rest are let go
+        self.cells.retain(|_, cell| {
+            let used = !cell.is_empty();
+            cell.clear();
+            used
+        });
+    }
+
+    pub fn insert(&mut self, entity: Entity, pos: Vec2, radius: f32, wraps: bool) {
+        let index = self.bodies.len();
+        self.bodies.push(Body {
+            entity,
+            pos,
+            radius,
+            wraps,
+        });
+        let (min, max) = (pos - Vec2::splat(radius), pos + Vec2::splat(radius));
+        let cells = if wraps && self.wraps {
+            self.wrapped_cells(min, max)
+        } else {
+            cells_between(self.cell_at(min), self.cell_at(max))
+        };
+        for cell in cells {
+            self.cells.entry(cell).or_default().push(index);
+            self.extent = Some(match self.extent {
+                Some((first, last)) => (first.min(cell), last.max(cell)),
+                None => (cell, cell),
+            });
+        }
+    }
+
+    pub fn bodies(&self) -> &[Body] {
+        &self.bodies
+    }
+
+    pub fn body(&self, index: usize) -> &Body {
+        &self.bodies[index]
+    }
+
+    /** everything whose cells overlap the box around the circle, which may be further than it looks */
+    pub fn query(&self, pos: Vec2, radius: f32) -> Vec<usize> {
+        self.query_box(pos - Vec2::splat(radius), pos + Vec2::splat(radius))
+    }
+
+    /** everything near a line of the given thickness, for things that move fast or reach far */
+    pub fn query_segment(&self, start: Vec2, end: Vec2, radius: f32) -> Vec<usize> {
+        self.query_box(
+            start.min(end) - Vec2::splat(radius),
+            start.max(end) + Vec2::splat(radius),
+        )
+    }
+
+    /** the shortest way from pos to the body, which only goes across the edges when whatever is at pos
+    and the body both wrap round */
+    pub fn offset_to(&self, pos: Vec2, wraps: bool, index: usize) -> Vec2 {
+        let body = &self.bodies[index];
+        if wraps && body.wraps {
+            torus::offset(pos, body.pos)
+        } else {
+            body.pos - pos
+        }
+    }
+
+    /** the closest body the filter accepts to something at pos that wraps round if the grid does,
+    searching outwards a ring of cells at a time */
+    pub fn nearest(&self, pos: Vec2, filter: impl Fn(&Body) -> bool) -> Option<usize> {
+        let (first, last) = self.extent?;
+        let at = self.cell_at(pos);
+        // far enough to reach every cell with anything in
+        let rings = (at - first).abs().max((last - at).abs()).max_element();
+        let mut best: Option<(f32, usize)> = None;
+        for ring in 0..=rings {
+            // nothing further out can beat what has been found
+            if let Some((distance, _)) = best {
+                let ring_distance = (ring - 1).max(0) as f32 * CELL_SIZE;
+                if ring_distance * ring_distance > distance {
+                    break;
+                }
+            }
+            let mut ring_indices: Vec<usize> = Vec::new();
+            for cell in ring_cells(at, ring) {
+                ring_indices.extend(self.bodies_in(cell));
+            }
+            ring_indices.sort_unstable();
+            ring_indices.dedup();
+            for index in ring_indices {
+                let body = &self.bodies[index];
+                if !filter(body) {
+                    continue;
+                }
+                let distance = self.offset_to(pos, self.wraps, index).length_squared();
+                // ties go to whatever was inserted first
+                if best.is_none_or(|(best_distance, best_index)| {
+                    distance < best_distance || (distance == best_distance && index < best_index)
+                }) {
+                    best = Some((distance, index));
+                }
+            }
+        }
+        best.map(|(_, index)| index)
+    }
+
+    /** everything whose cells overlap the box from min to max, going round the edges if the grid does
+    and looking where it is for whatever doesn't go round */
+    pub fn query_box(&self, min: Vec2, max: Vec2) -> Vec<usize> {
+        let mut cells = self.cells_under(min, max);
+        if self.wraps {
+            cells.extend(self.wrapped_cells(min, max));
+        }
+        let mut indices: Vec<usize> = Vec::new();
+        for cell in cells {
+            if let Some(cell) = self.cells.get(&cell) {
+                indices.extend(cell.iter());
+            }
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    // what is in the cell, and when the grid wraps also whatever wrapping round puts there
+    fn bodies_in(&self, cell: IVec2) -> impl Iterator<Item = &usize> {
+        let wrapped = self.wrap(cell);
+        let at_cell = self.cells.get(&cell).into_iter().flatten();
+        let wrapped_to_cell = self
+            .cells
+            .get(&wrapped)
+            .filter(|_| self.wraps && wrapped != cell)
+            .into_iter()
+            .flatten();
+        at_cell.chain(wrapped_to_cell)
+    }
+
+    // each cell under the box once, carrying on across the other side past the edges
+    fn wrapped_cells(&self, min: Vec2, max: Vec2) -> Vec<IVec2> {
+        let (min, max) = (self.cell_at(min), self.cell_at(max));
+        let cols = wrapped_range(min.x, max.x, self.cols);
+        let rows = wrapped_range(min.y, max.y, self.rows);
+        cells_in(&cols, &rows)
+    }
+
+    // the cells under the box, where they are. only the ones anything is in are listed, so a box
+    // reaching far off the screen doesn't cost anything
+    fn cells_under(&self, min: Vec2, max: Vec2) -> Vec<IVec2> {
+        let (first, last) = match self.extent {
+            Some(extent) => extent,
+            None => return Vec::new(),
+        };
+        cells_between(self.cell_at(min).max(first), self.cell_at(max).min(last))
+    }
+
+    // which column and row the position is in, on the grid over the screen or not
+    fn cell_at(&self, pos: Vec2) -> IVec2 {
+        ((pos - self.origin) / CELL_SIZE).floor().as_ivec2()
+    }
+
+    fn wrap(&self, cell: IVec2) -> IVec2 {
+        IVec2::new(cell.x.rem_euclid(self.cols), cell.y.rem_euclid(self.rows))
+    }
+}
+
+// from min to max, at most once round
+fn wrapped_range(min: i32, max: i32, count: i32) -> Vec<i32> {
+    (min..=max.min(min + count - 1))
+        .map(|coord| coord.rem_euclid(count))
+        .collect()
+}
+
+fn cells_between(min: IVec2, max: IVec2) -> Vec<IVec2> {
+    let cols: Vec<i32> = (min.x..=max.x).collect();
+    let rows: Vec<i32> = (min.y..=max.y).collect();
+    cells_in(&cols, &rows)
+}
+
+fn cells_in(cols: &[i32], rows: &[i32]) -> Vec<IVec2> {
+    rows.iter()
+        .flat_map(|row| cols.iter().map(move |col| IVec2::new(*col, *row)))
+        .collect()
+}
+
+// the cells ring cells out from the one at the middle, going round its edge
+fn ring_cells(at: IVec2, ring: i32) -> Vec<IVec2> {
+    if ring == 0 {
+        return vec![at];
+    }
+    let mut cells = Vec::new();
+    for col in (at.x - ring)..=(at.x + ring) {
+        cells.push(IVec2::new(col, at.y - ring));
+        cells.push(IVec2::new(col, at.y + ring));
+    }
+    for row in (at.y - ring + 1)..=(at.y + ring - 1) {
+        cells.push(IVec2::new(at.x - ring, row));
+        cells.push(IVec2::new(at.x + ring, row));
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use legion::world::Allocate;
+
+    use super::*;
+
+    fn entities(count: usize) -> Vec<Entity> {
+        Allocate::new().take(count).collect()
+    }
+
+    /** a grid over the screen from the corner with the bodies in, all the same size */
+    fn grid(wraps: bool, bodies: &[(Vec2, bool)]) -> SpatialHash {
+        let mut grid = SpatialHash::default();
+        grid.clear(Vec2::ZERO, wraps);
+        for (entity, (pos, body_wraps)) in entities(bodies.len()).into_iter().zip(bodies) {
+            grid.insert(entity, *pos, 4.0, *body_wraps);
+        }
+        grid
+    }
+
+    #[test]
+    fn queries_find_what_is_near_and_not_what_is_far() {
+        let grid = grid(
+            true,
+            &[
+                (Vec2::new(40.0, 40.0), true),
+                (Vec2::new(120.0, 80.0), true),
+                (Vec2::new(46.0, 40.0), true),
+            ],
+        );
+        assert_eq!(grid.query(Vec2::new(42.0, 42.0), 4.0), vec![0, 2]);
+        assert_eq!(grid.query(Vec2::new(120.0, 80.0), 1.0), vec![1]);
+        assert!(grid.query(Vec2::new(200.0, 130.0), 4.0).is_empty());
+        assert_eq!(
+            grid.query_segment(Vec2::new(40.0, 40.0), Vec2::new(120.0, 80.0), 1.0),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn queries_go_round_the_edges_only_when_the_grid_wraps() {
+        let bodies = [
+            (Vec2::new(2.0, 80.0), true),
+            (Vec2::new(120.0, 158.0), true),
+        ];
+        let wrapping = grid(true, &bodies);
+        assert_eq!(wrapping.query(Vec2::new(238.0, 80.0), 4.0), vec![0]);
+        assert_eq!(wrapping.query(Vec2::new(120.0, 1.0), 4.0), vec![1]);
+        assert_eq!(
+            wrapping.offset_to(Vec2::new(238.0, 80.0), true, 0),
+            Vec2::new(4.0, 0.0)
+        );
+
+        let open = grid(false, &bodies);
+        assert!(open.query(Vec2::new(238.0, 80.0), 4.0).is_empty());
+        assert!(open.query(Vec2::new(120.0, 1.0), 4.0).is_empty());
+        assert_eq!(
+            open.offset_to(Vec2::new(238.0, 80.0), false, 0),
+            Vec2::new(-236.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn things_off_an_open_field_stay_where_they_are() {
+        // off the right and bottom by more than a screen, as far as anything lasts before despawning
+        let far_off = Vec2::new(500.0, 300.0);
+        let grid = grid(false, &[(far_off, false), (Vec2::new(-300.0, 80.0), false)]);
+        // nothing at the edges of the screen, where they would have been clamped to
+        assert!(grid.query(Vec2::new(236.0, 156.0), 8.0).is_empty());
+        assert!(grid.query(Vec2::new(4.0, 80.0), 8.0).is_empty());
+        assert_eq!(grid.query(far_off, 1.0), vec![0]);
+        assert_eq!(grid.query(Vec2::new(-300.0, 80.0), 1.0), vec![1]);
+    }
+
+    #[test]
+    fn things_coming_in_are_found_where_they_are_on_a_wrapping_field() {
+        let coming_in = Vec2::new(-20.0, 80.0);
+        let grid = grid(true, &[(coming_in, false), (Vec2::new(220.0, 80.0), true)]);
+        // the queries themselves still go round, so what wraps to the same cells comes too
+        assert!(grid.query(coming_in, 1.0).contains(&0));
+        // wrapping round would put it here, but it doesn't wrap
+        assert_eq!(grid.query(Vec2::new(220.0, 80.0), 1.0), vec![1]);
+    }
+
+    #[test]
+    fn nearest_finds_the_closest_the_filter_accepts() {
+        let grid = grid(
+            true,
+            &[
+                (Vec2::new(100.0, 80.0), true),
+                (Vec2::new(60.0, 80.0), true),
+                (Vec2::new(110.0, 80.0), true),
+            ],
+        );
+        let from = Vec2::new(105.0, 80.0);
+        assert_eq!(grid.nearest(from, |_| true), Some(0));
+        assert_eq!(grid.nearest(from, |body| body.pos.x != 100.0), Some(2));
+        assert_eq!(grid.nearest(from, |body| body.pos.x < 100.0), Some(1));
+        assert_eq!(grid.nearest(from, |_| false), None);
+    }
+
+    #[test]
+    fn nearest_gives_ties_to_the_first_inserted() {
+        let grid = grid(
+            true,
+            &[
+                (Vec2::new(130.0, 80.0), true),
+                (Vec2::new(110.0, 80.0), true),
+            ],
+        );
+        assert_eq!(grid.nearest(Vec2::new(120.0, 80.0), |_| true), Some(0));
+    }
+
+    #[test]
+    fn nearest_goes_round_the_edges_when_the_grid_wraps() {
+        let bodies = [(Vec2::new(80.0, 80.0), true), (Vec2::new(2.0, 80.0), true)];
+        let from = Vec2::new(200.0, 80.0);
+        assert_eq!(grid(true, &bodies).nearest(from, |_| true), Some(1));
+        assert_eq!(grid(false, &bodies).nearest(from, |_| true), Some(0));
+    }
+
+    #[test]
+    fn nearest_finds_things_off_an_open_field() {
+        let grid = grid(false, &[(Vec2::new(600.0, 400.0), false)]);
+        assert_eq!(grid.nearest(Vec2::new(120.0, 80.0), |_| true), Some(0));
+    }
+
+    #[test]
+    fn nearest_of_nothing_is_nothing() {
+        assert_eq!(grid(true, &[]).nearest(Vec2::ZERO, |_| true), None);
+    }
+}
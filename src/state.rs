@@ -1,31 +1,85 @@
+use glam::Vec2;
 pub use legion::*;
-use rand::{rngs::StdRng, SeedableRng};
 
 use crate::{
     audio_playing::AudioCommandBuffer,
+    controls_menu,
+    events::{CollisionEvents, DeathEvents},
+    game_over, high_scores,
     message_stream::ExpiringMessages,
+    modes::{ModeRegistry, Transition},
+    name_entry, netplay, pause,
+    playing::{self, ControlScheme, PlayerInputs},
     rendering::RenderCommandBuffer,
+    replay,
+    rng::{self, Seed},
     schedules,
+    settings::Settings,
+    settings_menu,
+    spatial_hash::SpatialHash,
     timer::{AsteroidSpawnTimer, EnemySpawnTimer, GunSpawnTimer},
+    title,
+    topology::{Camera, Topology},
 };
 
 pub const FRAMES_PER_SECOND: u32 = 60;
 
-#[derive(Clone, Copy)]
+// everything besides the inputs that decides how a run plays out, saved with replays
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunConfig {
+    pub seed: u64,
+    pub control_scheme: ControlScheme,
+    // how many local players have a ship, 1 to MAX_PLAYERS
+    pub players: usize,
+    // whether players' bullets hurt each other
+    pub friendly_fire: bool,
+    pub topology: Topology,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            control_scheme: ControlScheme::default(),
+            players: 1,
+            friendly_fire: false,
+            topology: Topology::default(),
+        }
+    }
+}
+
+// resource, how many steps the run has lasted, it stops counting once every player is out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunSteps(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum GameMode {
     Title,
     Playing,
     GameOver,
+    Replay,
+    Pause,
+    Settings,
+    Controls,
+    Netplay,
+    HighScores,
+    NameEntry,
 }
 
 pub struct State {
     pub running: bool,
     pub time_since_last_update: f32,
+    // when set every run uses this seed, otherwise each run picks a fresh one
+    pub seed: Option<u64>,
 
     pub ecs: World,
     pub resources: Resources,
+    pub modes: ModeRegistry,
+    // the top mode is stepped, the ones below it are paused
+    pub mode_stack: Vec<GameMode>,
     pub title_schedule: Schedule,
     pub playing_schedule: Schedule,
+    pub playing_render_schedule: Schedule,
 }
 
 impl State {
@@ -39,34 +93,84 @@ impl State {
         let audio_command_buffer: AudioCommandBuffer = AudioCommandBuffer::new();
         resources.insert(audio_command_buffer);
 
-        let expiring_messages = ExpiringMessages::new();
-        resources.insert(expiring_messages);
-
-        let rng: StdRng = StdRng::from_entropy();
-        resources.insert(rng);
-
-        let asteroid_spawn_timer = AsteroidSpawnTimer::new(500, 0);
-        resources.insert::<AsteroidSpawnTimer>(asteroid_spawn_timer);
-
-        let gun_spawn_timer = GunSpawnTimer::new(100, 0);
-        resources.insert::<GunSpawnTimer>(gun_spawn_timer);
-
-        let enemy_spawn_timer = EnemySpawnTimer::new(2000, 0);
-        resources.insert::<EnemySpawnTimer>(enemy_spawn_timer);
-
-        let game_mode = GameMode::Title;
-        resources.insert(game_mode);
-        let transition_to: Option<GameMode> = None;
-        resources.insert(transition_to);
+        resources.insert(Settings::default());
+
+        // inputs stay neutral until a frontend or a replay fills them in
+        resources.insert(PlayerInputs::default());
+        let mouse_pos = Vec2::ZERO;
+        resources.insert(mouse_pos);
+
+        let run_config = RunConfig {
+            seed: rng::random_seed(),
+            ..Default::default()
+        };
+        insert_simulation_resources(&mut resources, run_config);
+
+        let transition: Option<Transition> = Some(Transition::Reset(GameMode::Title));
+        resources.insert(transition);
+
+        let mut modes = ModeRegistry::new();
+        title::register(&mut modes);
+        playing::register(&mut modes);
+        game_over::register(&mut modes);
+        replay::register(&mut modes);
+        pause::register(&mut modes);
+        settings_menu::register(&mut modes);
+        controls_menu::register(&mut modes);
+        netplay::register(&mut modes);
+        high_scores::register(&mut modes);
+        name_entry::register(&mut modes);
 
         Self {
             running: true,
             time_since_last_update: 0.0,
+            seed: None,
 
             ecs: World::default(),
             resources,
+            modes,
+            mode_stack: Vec::new(),
             title_schedule: schedules::build_title_schedule(),
             playing_schedule: schedules::build_play_schedule(),
+            playing_render_schedule: schedules::build_play_render_schedule(),
         }
     }
 }
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/** everything a run depends on besides the world and inputs, so a run config fully determines a run */
+pub fn insert_simulation_resources(resources: &mut Resources, run_config: RunConfig) {
+    let seed = run_config.seed;
+    resources.insert(run_config);
+    resources.insert(Seed(seed));
+    resources.insert(RunSteps::default());
+    resources.insert(rng::simulation_rng(seed));
+    resources.insert(rng::render_rng(seed));
+    resources.insert(rng::audio_rng(seed));
+
+    let expiring_messages = ExpiringMessages::new();
+    resources.insert(expiring_messages);
+
+    let death_events = DeathEvents::new();
+    resources.insert(death_events);
+
+    let collision_events = CollisionEvents::new();
+    resources.insert(collision_events);
+
+    resources.insert(SpatialHash::default());
+    resources.insert(Camera::default());
+
+    let asteroid_spawn_timer = AsteroidSpawnTimer::new(500, 0);
+    resources.insert::<AsteroidSpawnTimer>(asteroid_spawn_timer);
+
+    let gun_spawn_timer = GunSpawnTimer::new(100, 0);
+    resources.insert::<GunSpawnTimer>(gun_spawn_timer);
+
+    let enemy_spawn_timer = EnemySpawnTimer::new(2000, 0);
+    resources.insert::<EnemySpawnTimer>(enemy_spawn_timer);
+}
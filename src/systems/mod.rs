@@ -1,2 +1,3 @@
 pub mod playing;
+pub mod replay;
 pub mod title;
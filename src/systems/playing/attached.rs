@@ -1,6 +1,6 @@
 use glam::Vec2;
 pub use legion::*;
-use legion::{system, systems::CommandBuffer, world::SubWorld};
+use legion::{systems::CommandBuffer, world::SubWorld};
 
 use crate::components::{AttachedTo, CTransform, Physics};
 
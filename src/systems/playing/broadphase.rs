@@ -0,0 +1,47 @@
+use legion::world::SubWorld;
+pub use legion::*;
+
+use crate::{
+    components::{Beam, Bullet, CTransform, CaptureInPlayField, Collider, GrabZone},
+    spatial_hash::SpatialHash,
+    state::RunConfig,
+    topology::Camera,
+};
+
+/** puts everything with a position in the grid with how far it reaches. bullets and beams go looking
+for things but nothing looks for them, so they are left out. the grid covers what is on screen, and
+on a field that wraps round things still coming in from off it don't wrap until they are on it */
+#[system]
+#[read_component(CTransform)]
+#[read_component(Collider)]
+#[read_component(GrabZone)]
+#[read_component(Bullet)]
+#[read_component(Beam)]
+#[read_component(CaptureInPlayField)]
+pub fn build_spatial_hash(
+    ecs: &SubWorld,
+    #[resource] spatial_hash: &mut SpatialHash,
+    #[resource] camera: &Camera,
+    #[resource] run_config: &RunConfig,
+) {
+    let wraps = run_config.topology.wraps();
+    spatial_hash.clear(camera.pos, wraps);
+    let mut query = <(
+        Entity,
+        &CTransform,
+        Option<&Collider>,
+        Option<&GrabZone>,
+        Option<&CaptureInPlayField>,
+    )>::query()
+    .filter(!component::<Bullet>() & !component::<Beam>());
+    for (entity, transform, collider, grab_zone, incoming) in query.iter(ecs) {
+        let mut radius: f32 = 0.0;
+        if let Some(collider) = collider {
+            radius = radius.max(collider.shape.reach());
+        }
+        if let Some(grab_zone) = grab_zone {
+            radius = radius.max(grab_zone.radius);
+        }
+        spatial_hash.insert(*entity, transform.pos, radius, wraps && incoming.is_none());
+    }
+}
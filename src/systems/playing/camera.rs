@@ -0,0 +1,36 @@
+use glam::Vec2;
+use legion::world::SubWorld;
+pub use legion::*;
+
+use crate::{
+    components::{CTransform, Player},
+    state::RunConfig,
+    topology::{Camera, Topology},
+    DIMS,
+};
+
+/** on an open field the screen keeps the middle of the ships in the middle of it, and stays where it
+is while there are none. everywhere else it never moves */
+#[system]
+#[read_component(CTransform)]
+#[read_component(Player)]
+pub fn follow_ships(
+    ecs: &SubWorld,
+    #[resource] camera: &mut Camera,
+    #[resource] run_config: &RunConfig,
+) {
+    camera.previous = camera.pos;
+    if run_config.topology != Topology::Open {
+        return;
+    }
+    let ships: Vec<_> = <&CTransform>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .map(|transform| transform.pos)
+        .collect();
+    if ships.is_empty() {
+        return;
+    }
+    let middle = ships.iter().sum::<Vec2>() / ships.len() as f32;
+    camera.pos = middle - DIMS.as_vec2() / 2.0;
+}
@@ -1,150 +1,537 @@
+use std::collections::HashMap;
+
 use glam::Vec2;
 pub use legion::*;
 use legion::{systems::CommandBuffer, world::SubWorld};
-use rand::{rngs::StdRng, Rng};
 
 use crate::{
     audio_playing::{AudioCommand, AudioCommandBuffer},
     components::{
-        Asteroid, Attachable, AttachedTo, Bullet, CTransform, Enemy, GrabZone, InputControlled,
-        OwnedBy, Physics, Player, Score,
+        Asteroid, Attachable, AttachedTo, Beam, Bullet, CTransform, CaptureInPlayField, Collider,
+        ColliderShape, Damage, Enemy, GrabZone, Health, InputControlled, Invulnerable, Layers,
+        OwnedBy, Physics, Player, PreviousCTransform,
     },
+    events::{CollisionEvent, CollisionEvents, DeathEvent, DeathEvents},
+    narrowphase::{contact, sweep, Shape},
+    spatial_hash::SpatialHash,
+    torus,
+};
+
+use super::damage::{
+    apply_damage, impact_damage, BULLET_DAMAGE, HIT_INVULNERABLE_STEPS, SHIP_SIZE,
 };
 
-pub struct ScoreInstance {
-    pub owner: Entity,
-    pub score: u32,
+// roughly how far a ship's hull reaches from its center
+pub const SHIP_HIT_RADIUS: f32 = 4.0;
+// about the size of the little triangle guns are drawn as
+pub const GUN_HIT_RADIUS: f32 = 2.5;
+// what each kind of thing looks for. shots do the looking for what they hit, so nothing has to look
+// for them
+const SHIP_MASK: Layers = Layers::ASTEROID.union(Layers::PICKUP);
+const ENEMY_MASK: Layers = Layers::ASTEROID;
+const ASTEROID_MASK: Layers = Layers::ASTEROID;
+const PLAYER_SHOT_MASK: Layers = Layers::ASTEROID.union(Layers::ENEMY);
+const ENEMY_SHOT_MASK: Layers = Layers::ASTEROID.union(Layers::PLAYER);
+// asteroids smaller than this come through running into each other in one piece
+const MINIMUM_INTRA_ASTEROID_COLLISION_SIZE: u32 = 10;
+// how much energy goes into a crash between asteroids, by mass and the speed they close at, before
+// they shatter rather than bounce. two mid sized ones meeting head on at full speed just about do it
+const SHATTER_ENERGY: f32 = 300.0;
+// attachments deeper than this are not followed, in case they ever loop
+const MAX_ATTACHMENT_DEPTH: usize = 8;
+
+// damage on its way to an entity, applied once every collision has been looked at
+struct Hit {
+    target: Entity,
+    amount: u32,
+    source: Option<Entity>,
+}
+
+// an asteroid's part in bouncing off the others, written back once every collision has been seen to
+#[derive(Clone, Copy)]
+struct AsteroidBody {
+    size: u32,
+    pos: Vec2,
+    vel: Vec2,
+}
+
+pub fn ship_collider() -> Collider {
+    Collider {
+        shape: ColliderShape::circle(SHIP_HIT_RADIUS),
+        layer: Layers::PLAYER,
+        mask: SHIP_MASK,
+    }
 }
 
+pub fn enemy_collider() -> Collider {
+    Collider {
+        shape: ColliderShape::circle(SHIP_HIT_RADIUS),
+        layer: Layers::ENEMY,
+        mask: ENEMY_MASK,
+    }
+}
+
+pub fn asteroid_collider(size: u32) -> Collider {
+    Collider {
+        shape: ColliderShape::asteroid(size),
+        layer: Layers::ASTEROID,
+        mask: ASTEROID_MASK,
+    }
+}
+
+/** guns only get picked up by grab zones, nothing runs into them */
+pub fn gun_collider() -> Collider {
+    Collider {
+        shape: ColliderShape::circle(GUN_HIT_RADIUS),
+        layer: Layers::MODULE,
+        mask: Layers::NONE,
+    }
+}
+
+/** a bullet or beam fired by something on the shooter layer. shots only hit their own side when
+players have friendly fire on, and a shot from something on neither side only hits asteroids */
+pub fn shot_collider(shape: ColliderShape, shooter: Layers, friendly_fire: bool) -> Collider {
+    let (layer, mask) = if shooter.intersects(Layers::PLAYER) {
+        let mask = if friendly_fire {
+            PLAYER_SHOT_MASK | Layers::PLAYER
+        } else {
+            PLAYER_SHOT_MASK
+        };
+        (Layers::PLAYER_BULLET, mask)
+    } else if shooter.intersects(Layers::ENEMY) {
+        (Layers::ENEMY_BULLET, ENEMY_SHOT_MASK)
+    } else {
+        (Layers::NONE, Layers::ASTEROID)
+    };
+    Collider { shape, layer, mask }
+}
+
+/** finds everything that touches this step, for the response systems to deal with. nothing touches
+whatever it belongs to or is attached to. things are tested against the copy of each other nearest
+across the edges of the play field, and beams long enough to reach round against every copy */
 #[system]
 #[read_component(CTransform)]
+#[read_component(PreviousCTransform)]
+#[read_component(Collider)]
 #[read_component(Bullet)]
+#[read_component(Beam)]
+#[read_component(OwnedBy)]
+#[read_component(AttachedTo)]
+pub fn detect_collisions(
+    ecs: &SubWorld,
+    #[resource] spatial_hash: &SpatialHash,
+    #[resource] collision_events: &mut CollisionEvents,
+) {
+    collision_events.clear();
+
+    let bodies: Vec<Option<(Collider, Shape)>> = spatial_hash
+        .bodies()
+        .iter()
+        .map(|body| {
+            let entry = ecs.entry_ref(body.entity).ok()?;
+            let collider = *entry.get_component::<Collider>().ok()?;
+            let transform = entry.get_component::<CTransform>().ok()?;
+            Some((collider, Shape::new(&collider, transform)))
+        })
+        .collect();
+
+    // everything in the grid against everything near it, each pair once
+    for (index, body) in spatial_hash.bodies().iter().enumerate() {
+        let (collider, shape) = match &bodies[index] {
+            Some(body) => body,
+            None => continue,
+        };
+        for other_index in spatial_hash.query(body.pos, body.radius) {
+            if other_index <= index {
+                continue;
+            }
+            let (other_collider, other_shape) = match &bodies[other_index] {
+                Some(other) => other,
+                None => continue,
+            };
+            if !collider.can_touch(other_collider) {
+                continue;
+            }
+            let other_entity = spatial_hash.body(other_index).entity;
+            let other_shape =
+                nearest_image(spatial_hash, body.pos, body.wraps, other_index, other_shape);
+            if let Some(contact) = contact(shape, &other_shape) {
+                if !belong_together(ecs, body.entity, other_entity) {
+                    collision_events.push(CollisionEvent {
+                        a: body.entity,
+                        b: other_entity,
+                        normal: contact.normal,
+                        depth: contact.depth,
+                    });
+                }
+            }
+        }
+    }
+
+    // bullets are swept from where they were at the start of the step to where they are now, so a
+    // fast one can't pass through something between steps. each only touches the first thing along
+    // its path
+    let mut bullets = <(Entity, &CTransform, Option<&PreviousCTransform>, &Collider)>::query()
+        .filter(component::<Bullet>());
+    for (bullet_entity, bullet_transform, previous, collider) in bullets.iter(ecs) {
+        let start = previous.map_or(bullet_transform.pos, |previous| previous.0.pos);
+        let end = bullet_transform.pos;
+        let radius = collider.shape.reach();
+
+        let mut earliest: Option<(f32, Entity)> = None;
+        for index in spatial_hash.query_segment(start, end, radius) {
+            let (other_collider, other_shape) = match &bodies[index] {
+                Some(other) => other,
+                None => continue,
+            };
+            if !collider.can_touch(other_collider) {
+                continue;
+            }
+            let other_entity = spatial_hash.body(index).entity;
+            let other_shape = nearest_image(spatial_hash, start, true, index, other_shape);
+            if let Some(t) = sweep(start, end, radius, &other_shape) {
+                if earliest.is_none_or(|(earliest_t, _)| t < earliest_t)
+                    && !belong_together(ecs, *bullet_entity, other_entity)
+                {
+                    earliest = Some((t, other_entity));
+                }
+            }
+        }
+
+        if let Some((_, other_entity)) = earliest {
+            collision_events.push(CollisionEvent {
+                a: *bullet_entity,
+                b: other_entity,
+                // pushed on the way it was going
+                normal: (end - start).try_normalize().unwrap_or(Vec2::X),
+                depth: 0.0,
+            });
+        }
+    }
+
+    // beams touch everything in their way
+    let mut beams = <(Entity, &CTransform, &Collider)>::query().filter(component::<Beam>());
+    for (beam_entity, beam_transform, collider) in beams.iter(ecs) {
+        let beam_shape = Shape::new(collider, beam_transform);
+        let (min, max) = beam_shape.bounds();
+        for index in spatial_hash.query_box(min, max) {
+            let (other_collider, other_shape) = match &bodies[index] {
+                Some(other) => other,
+                None => continue,
+            };
+            if !collider.can_touch(other_collider) {
+                continue;
+            }
+            let other_entity = spatial_hash.body(index).entity;
+            let images = if spatial_hash.body(index).wraps {
+                torus::images().to_vec()
+            } else {
+                vec![Vec2::ZERO]
+            };
+            if let Some(contact) = images
+                .into_iter()
+                .find_map(|image| contact(&beam_shape, &other_shape.translated(image)))
+            {
+                if !belong_together(ecs, *beam_entity, other_entity) {
+                    collision_events.push(CollisionEvent {
+                        a: *beam_entity,
+                        b: other_entity,
+                        normal: contact.normal,
+                        depth: contact.depth,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/** asteroids that touch bounce off each other like billiard balls weighted by their size and are
+pushed apart so they don't overlap. big ones that hit hard enough shatter instead of bouncing */
+#[system]
+#[write_component(CTransform)]
+#[write_component(Physics)]
 #[read_component(Asteroid)]
-#[write_component(OwnedBy)]
-#[read_component(Score)]
-#[write_component(Score)]
-pub fn collision(
+pub fn bounce_asteroids(
     ecs: &mut SubWorld,
-    cmd: &mut CommandBuffer,
-    #[resource] audio_command_buffer: &mut AudioCommandBuffer,
-    #[resource] rng: &mut StdRng,
+    #[resource] collision_events: &CollisionEvents,
+    #[resource] death_events: &mut DeathEvents,
 ) {
-    let mut score_instances: Vec<ScoreInstance> = Vec::new();
-
-    let mut bullets = <(Entity, &CTransform)>::query().filter(component::<Bullet>());
-    let mut asteroids = <(Entity, &CTransform, &Asteroid)>::query();
-
-    // Bullet and asteroid collision
-    for (asteroid_entity, asteroid_transform, asteroid) in asteroids.iter(ecs) {
-        for (bullet_entity, bullet_transform) in bullets.iter(ecs) {
-            let distance = (bullet_transform.pos - asteroid_transform.pos).length();
-            let asteroid_radius = asteroid.size as f32 * 0.8;
-            if distance <= asteroid_radius {
-                if asteroid.size < 3 {
-                    cmd.remove(*asteroid_entity);
-                } else {
-                    split_asteroid(asteroid_transform, asteroid, cmd, rng);
-                    cmd.remove(*asteroid_entity);
-                }
-                cmd.remove(*bullet_entity);
-
-                if let Ok(entry) = ecs.entry_ref(*bullet_entity) {
-                    if let Ok(owned_by) = entry.get_component::<OwnedBy>() {
-                        // if theres already a score for this entity, increment it
-                        if let Some(score_instance) = score_instances
-                            .iter_mut()
-                            .find(|score_instance| score_instance.owner == owned_by.owner)
-                        {
-                            score_instance.score += 1;
-                            continue;
-                        } else {
-                            score_instances.push(ScoreInstance {
-                                owner: owned_by.owner,
-                                score: 1,
-                            });
-                        }
-                    } else {
-                        println!(
-                            "Failed to get OwnedBy component for bullet: {:?}",
-                            bullet_entity
-                        );
+    let mut asteroid_bodies: HashMap<Entity, AsteroidBody> = HashMap::new();
+    // written back in the order they were first bounced, so nothing depends on the map's order
+    let mut bounced: Vec<Entity> = Vec::new();
+    let mut shattered: Vec<Entity> = Vec::new();
+    for event in collision_events.iter() {
+        let (first, second) = match (
+            asteroid_body(ecs, &asteroid_bodies, event.a),
+            asteroid_body(ecs, &asteroid_bodies, event.b),
+        ) {
+            (Some(first), Some(second)) => (first, second),
+            _ => continue,
+        };
+        let normal = event.normal;
+        let (first_mass, second_mass) = (asteroid_mass(first.size), asteroid_mass(second.size));
+        let inverse_mass_sum = 1.0 / first_mass + 1.0 / second_mass;
+
+        // push them apart, the lighter one moves further
+        let first_share = (1.0 / first_mass) / inverse_mass_sum;
+        let mut first_after = first;
+        let mut second_after = second;
+        first_after.pos -= normal * event.depth * first_share;
+        second_after.pos += normal * event.depth * (1.0 - first_share);
+
+        // only pairs that are closing in bounce, ones already moving apart are left to it
+        let closing_speed = -(second.vel - first.vel).dot(normal);
+        if closing_speed > 0.0 {
+            let reduced_mass = 1.0 / inverse_mass_sum;
+            let impact_energy = 0.5 * reduced_mass * closing_speed * closing_speed;
+            if impact_energy >= SHATTER_ENERGY {
+                for (entity, size) in [(event.a, first.size), (event.b, second.size)] {
+                    if size >= MINIMUM_INTRA_ASTEROID_COLLISION_SIZE && !shattered.contains(&entity)
+                    {
+                        shattered.push(entity);
                     }
-                } else {
-                    println!("Failed to get entry for bullet: {:?}", bullet_entity);
                 }
+            }
+            let impulse = 2.0 * closing_speed / inverse_mass_sum;
+            first_after.vel -= normal * impulse / first_mass;
+            second_after.vel += normal * impulse / second_mass;
+        }
 
-                audio_command_buffer.push(AudioCommand::AsteroidExplosion);
+        for (entity, asteroid_body) in [(event.a, first_after), (event.b, second_after)] {
+            if asteroid_bodies.insert(entity, asteroid_body).is_none() {
+                bounced.push(entity);
             }
         }
     }
 
-    // process the score instances
-    for score_instance in score_instances {
-        let mut score_query = <&mut Score>::query();
-        let score = score_query
-            .iter_mut(ecs)
-            .find(|score| score.owner == score_instance.owner);
-        match score {
-            Some(score) => {
-                score.score += score_instance.score;
+    for entity in bounced {
+        let asteroid_body = asteroid_bodies[&entity];
+        if let Ok(mut entry) = ecs.entry_mut(entity) {
+            if let Ok(transform) = entry.get_component_mut::<CTransform>() {
+                transform.pos = asteroid_body.pos;
             }
-            None => {
-                cmd.push((Score {
-                    owner: score_instance.owner,
-                    score: score_instance.score,
-                },));
+            if let Ok(physics) = entry.get_component_mut::<Physics>() {
+                physics.vel = asteroid_body.vel;
             }
         }
     }
+    for entity in shattered {
+        if !death_events.iter().any(|event| event.entity == entity) {
+            death_events.push(DeathEvent {
+                entity,
+                killer: None,
+            });
+        }
+    }
+}
+
+/** shots hurt what they touch, and ships running into asteroids hurt both sides by how big the other
+is and how hard they hit. ships that were just hit or just respawned are let off everything */
+#[system]
+#[read_component(Bullet)]
+#[read_component(Beam)]
+#[read_component(Damage)]
+#[read_component(OwnedBy)]
+#[read_component(Asteroid)]
+#[read_component(Player)]
+#[read_component(Enemy)]
+#[read_component(Invulnerable)]
+#[read_component(Physics)]
+#[write_component(Health)]
+pub fn collision_damage(
+    ecs: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] collision_events: &CollisionEvents,
+    #[resource] death_events: &mut DeathEvents,
+    #[resource] audio_command_buffer: &mut AudioCommandBuffer,
+) {
+    let mut hits: Vec<Hit> = Vec::new();
+    let mut impacted_ships: Vec<Entity> = Vec::new();
 
-    let mut dead_ships: Vec<Entity> = Vec::new();
+    for event in collision_events.iter() {
+        for (entity, other) in [(event.a, event.b), (event.b, event.a)] {
+            let entry = match ecs.entry_ref(entity) {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            let is_bullet = entry.get_component::<Bullet>().is_ok();
+            let is_beam = entry.get_component::<Beam>().is_ok();
 
-    // asteroid and player collision
-    let mut players =
-        <(Entity, &CTransform)>::query().filter(component::<Player>() | component::<Enemy>());
-    for (player_entity, player_transform) in players.iter(ecs) {
-        for (asteroid_entity, asteroid_transform, asteroid) in asteroids.iter(ecs) {
-            let distance = (player_transform.pos - asteroid_transform.pos).length();
-            let combined_radius = (asteroid.size + 1) as f32 * 0.8;
-            if distance <= combined_radius {
-                cmd.remove(*asteroid_entity);
-                cmd.remove(*player_entity);
-                dead_ships.push(*player_entity);
+            if is_bullet || is_beam {
+                // shots go straight through ships that can't be hit right now
+                if is_invulnerable(ecs, other) {
+                    continue;
+                }
+                // beams that have already done their damage are only drawn
+                let amount = match entry.get_component::<Damage>() {
+                    Ok(damage) => damage.amount,
+                    Err(_) if is_bullet => BULLET_DAMAGE,
+                    Err(_) => continue,
+                };
+                // bullets are spent on what they hit, beams go through everything in their way
+                if is_bullet {
+                    cmd.remove(entity);
+                }
+                hits.push(Hit {
+                    target: other,
+                    amount,
+                    source: entry
+                        .get_component::<OwnedBy>()
+                        .ok()
+                        .map(|owned_by| owned_by.owner),
+                });
+                if is_ship(ecs, other) {
+                    audio_command_buffer.push(AudioCommand::PlayerHit);
+                }
+                continue;
             }
+
+            let asteroid = match ecs
+                .entry_ref(other)
+                .ok()
+                .and_then(|entry| entry.get_component::<Asteroid>().ok().copied())
+            {
+                Some(asteroid) => asteroid,
+                None => continue,
+            };
+            // one impact per step is plenty
+            if !is_ship(ecs, entity)
+                || is_invulnerable(ecs, entity)
+                || impacted_ships.contains(&entity)
+            {
+                continue;
+            }
+            let relative_speed = (velocity(ecs, entity) - velocity(ecs, other)).length();
+            hits.push(Hit {
+                target: entity,
+                amount: impact_damage(asteroid.size, relative_speed),
+                source: None,
+            });
+            hits.push(Hit {
+                target: other,
+                amount: impact_damage(SHIP_SIZE, relative_speed),
+                source: None,
+            });
+            cmd.add_component(
+                entity,
+                Invulnerable {
+                    steps_left: HIT_INVULNERABLE_STEPS,
+                },
+            );
+            impacted_ships.push(entity);
+            audio_command_buffer.push(AudioCommand::PlayerHit);
         }
     }
 
-    // remove scores for any dead players
-    // let mut score_query = <(Entity, &Score)>::query();
-    // for (entity, score) in score_query.iter(ecs) {
-    //     if dead_players.contains(&score.owner) {
-    //         cmd.remove(*entity);
-    //     }
-    // }
-
-    //  //  //  //// // ASTEROID TO ASTEROID COLLISION    //  //  //  //
-    //  //  //  //// // BLEW UP MY COMPUTER   //  //  //  //  //
-    // const MINIMUM_INTRA_ASTEROID_COLLISION_SIZE: u32 = 10;
-    // let asteroids_vec: Vec<_> = asteroids.iter(ecs).collect();
-    // for i in 0..asteroids_vec.len() {
-    //     let (asteroid1_entity, asteroid1_transform, asteroid1) = &asteroids_vec[i];
-    //     for j in (i + 1)..asteroids_vec.len() {
-    //         let (asteroid2_entity, asteroid2_transform, asteroid2) = &asteroids_vec[j];
-    //         let distance = (asteroid1_transform.pos - asteroid2_transform.pos).length();
-    //         let combined_radius = (asteroid1.size + asteroid2.size) as f32 * 0.8;
-    //         if distance <= combined_radius {
-    //             if asteroid1.size >= MINIMUM_INTRA_ASTEROID_COLLISION_SIZE {
-    //                 split_asteroid(asteroid1_transform, asteroid1, cmd, rng);
-    //                 cmd.remove(**asteroid1_entity);
-    //             }
-
-    //             if asteroid2.size >= MINIMUM_INTRA_ASTEROID_COLLISION_SIZE {
-    //                 split_asteroid(asteroid2_transform, asteroid2, cmd, rng);
-    //                 cmd.remove(**asteroid2_entity);
-    //             }
-    //         }
-    //     }
-    // }
+    // a beam only hurts on the step after it is fired, then it is just drawn until it fades
+    for beam in <Entity>::query()
+        .filter(component::<Beam>() & component::<Damage>())
+        .iter(ecs)
+    {
+        cmd.remove_component::<Damage>(*beam);
+    }
+
+    // something can be hit more than once in a step but only dies once
+    for hit in hits {
+        let killed = match ecs.entry_mut(hit.target) {
+            Ok(mut entry) => apply_damage(entry.get_component_mut::<Health>().ok(), hit.amount),
+            Err(_) => false,
+        };
+        if killed && !death_events.iter().any(|event| event.entity == hit.target) {
+            death_events.push(DeathEvent {
+                entity: hit.target,
+                killer: hit.source,
+            });
+        }
+    }
+}
+
+fn is_ship(ecs: &SubWorld, entity: Entity) -> bool {
+    ecs.entry_ref(entity).is_ok_and(|entry| {
+        entry.get_component::<Player>().is_ok() || entry.get_component::<Enemy>().is_ok()
+    })
+}
+
+fn is_invulnerable(ecs: &SubWorld, entity: Entity) -> bool {
+    ecs.entry_ref(entity)
+        .is_ok_and(|entry| entry.get_component::<Invulnerable>().is_ok())
+}
+
+/** anything that doesn't move counts as standing still */
+fn velocity(ecs: &SubWorld, entity: Entity) -> Vec2 {
+    ecs.entry_ref(entity)
+        .ok()
+        .and_then(|entry| {
+            entry
+                .get_component::<Physics>()
+                .ok()
+                .map(|physics| physics.vel)
+        })
+        .unwrap_or(Vec2::ZERO)
+}
+
+/** the body's shape moved to the copy of it nearest pos */
+fn nearest_image(
+    spatial_hash: &SpatialHash,
+    pos: Vec2,
+    wraps: bool,
+    index: usize,
+    shape: &Shape,
+) -> Shape {
+    let body_pos = spatial_hash.body(index).pos;
+    shape.translated(pos + spatial_hash.offset_to(pos, wraps, index) - body_pos)
+}
+
+/** only asteroids that move can bounce, ones that already have been this step carry on from there */
+fn asteroid_body(
+    ecs: &SubWorld,
+    asteroid_bodies: &HashMap<Entity, AsteroidBody>,
+    entity: Entity,
+) -> Option<AsteroidBody> {
+    if let Some(asteroid_body) = asteroid_bodies.get(&entity) {
+        return Some(*asteroid_body);
+    }
+    let entry = ecs.entry_ref(entity).ok()?;
+    Some(AsteroidBody {
+        size: entry.get_component::<Asteroid>().ok()?.size,
+        pos: entry.get_component::<CTransform>().ok()?.pos,
+        vel: entry.get_component::<Physics>().ok()?.vel,
+    })
+}
+
+/** by area, so twice the size is four times as hard to push around */
+fn asteroid_mass(size: u32) -> f32 {
+    (size.max(1) * size.max(1)) as f32
+}
+
+/** whether they are on the same ship, counting what fired them as where they are */
+fn belong_together(ecs: &SubWorld, a: Entity, b: Entity) -> bool {
+    owner_root(ecs, a) == owner_root(ecs, b)
+}
+
+fn owner_root(ecs: &SubWorld, entity: Entity) -> Entity {
+    let owner = ecs
+        .entry_ref(entity)
+        .ok()
+        .and_then(|entry| entry.get_component::<OwnedBy>().ok().copied())
+        .map_or(entity, |owned_by| owned_by.owner);
+    attachment_root(ecs, owner)
+}
+
+/** what the entity is attached to, all the way up, or the entity itself */
+fn attachment_root(ecs: &SubWorld, entity: Entity) -> Entity {
+    let mut root = entity;
+    for _ in 0..MAX_ATTACHMENT_DEPTH {
+        match ecs
+            .entry_ref(root)
+            .ok()
+            .and_then(|entry| entry.get_component::<AttachedTo>().ok().copied())
+        {
+            Some(attached_to) => root = attached_to.entity,
+            None => break,
+        }
+    }
+    root
 }
 
 /*
@@ -168,31 +555,63 @@ pub fn collision(
 #[system]
 #[write_component(CTransform)]
 #[read_component(Physics)]
+#[read_component(Collider)]
 #[read_component(GrabZone)]
 #[read_component(Attachable)]
-pub fn attach_to_grab_zone(ecs: &mut SubWorld, cmd: &mut CommandBuffer) {
+#[read_component(AttachedTo)]
+#[read_component(InputControlled)]
+#[read_component(CaptureInPlayField)]
+pub fn attach_to_grab_zone(
+    ecs: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] spatial_hash: &SpatialHash,
+) {
     // 1. Fetch all attachable entities
-    // let unattached_attachables: Vec<(Entity, CTransform)> =
-    //     <(Entity, &CTransform, &Attachable)>::query()
-    //         .filter(!component::<AttachedTo>()) // 2. Filter out attached entities
-    //         .iter(ecs)
-    //         .map(|(entity, transform, _)| (*entity, *transform))
-    //         .collect();
-
-    // 3. Fetch all grab zones
-    let grab_zone_entities: Vec<(Entity, CTransform, GrabZone)> =
-        <(Entity, &CTransform, &GrabZone)>::query()
-            .iter(ecs)
-            .map(|(entity, transform, grabzone)| (*entity, *transform, *grabzone))
-            .collect();
-
-    let mut unattached_attachables =
-        <(Entity, &CTransform, &Attachable)>::query().filter(!component::<AttachedTo>());
-    for (attachable_entity, attachable_transform, _) in unattached_attachables.iter_mut(ecs) {
-        for (grab_entity, grab_transform, grab_zone) in grab_zone_entities.iter() {
-            let offset = attachable_transform.pos - grab_transform.pos;
-            let distance = offset.length();
-            if distance <= grab_zone.radius {
+    let unattached_attachables: Vec<(Entity, CTransform, Shape, bool)> = <(
+        Entity,
+        &CTransform,
+        Option<&Collider>,
+        &Attachable,
+        Option<&CaptureInPlayField>,
+    )>::query()
+    .filter(!component::<AttachedTo>()) // 2. Filter out attached entities
+    .iter(ecs)
+    .map(|(entity, transform, collider, _, incoming)| {
+        let shape = collider.map_or(Shape::point(transform.pos), |collider| {
+            Shape::new(collider, transform)
+        });
+        (*entity, *transform, shape, incoming.is_none())
+    })
+    .collect();
+
+    // 3. Check the grab zones that reach the cells the attachable is in, from whichever side of the
+    // edges they are nearest
+    for (attachable_entity, attachable_transform, attachable_shape, wraps) in
+        unattached_attachables.iter()
+    {
+        let (min, max) = attachable_shape.bounds();
+        for index in spatial_hash.query_box(min, max) {
+            let grab_entity = &spatial_hash.body(index).entity;
+            let (grab_transform, grab_zone, input_controlled) = match ecs.entry_ref(*grab_entity) {
+                Ok(entry) => match (
+                    entry.get_component::<CTransform>(),
+                    entry.get_component::<GrabZone>(),
+                ) {
+                    (Ok(transform), Ok(grab_zone)) => (
+                        *transform,
+                        *grab_zone,
+                        entry.get_component::<InputControlled>().ok().copied(),
+                    ),
+                    _ => continue,
+                },
+                Err(_) => continue,
+            };
+            let offset = -spatial_hash.offset_to(attachable_transform.pos, *wraps, index);
+            let grab_shape = Shape::Circle {
+                center: attachable_transform.pos - offset,
+                radius: grab_zone.radius,
+            };
+            if contact(&grab_shape, attachable_shape).is_some() {
                 // This computes the matrix to rotate by -theta if rot represents rotation by theta.
                 // let inverse_rotation_matrix = glam::Mat2::from_cols(
                 //     grab_transform.rot,
@@ -208,7 +627,10 @@ pub fn attach_to_grab_zone(ecs: &mut SubWorld, cmd: &mut CommandBuffer) {
                 };
 
                 cmd.add_component(*attachable_entity, attached);
-                cmd.add_component(*attachable_entity, InputControlled);
+                // guns answer to whoever steers the ship that grabbed them
+                if let Some(input_controlled) = input_controlled {
+                    cmd.add_component(*attachable_entity, input_controlled);
+                }
                 cmd.add_component(
                     *attachable_entity,
                     OwnedBy {
@@ -220,26 +642,3 @@ pub fn attach_to_grab_zone(ecs: &mut SubWorld, cmd: &mut CommandBuffer) {
         }
     }
 }
-
-fn split_asteroid(
-    transform: &CTransform,
-    asteroid: &Asteroid,
-    cmd: &mut CommandBuffer,
-    rng: &mut StdRng,
-) {
-    let new_size = asteroid.size / 2;
-    for _ in 0..2 {
-        let random_velocity = Vec2::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5));
-        cmd.push((
-            CTransform {
-                pos: transform.pos,
-                rot: transform.rot,
-            },
-            Asteroid { size: new_size },
-            Physics {
-                vel: random_velocity,
-                rot_vel: rng.gen_range(-50.0..50.0),
-            },
-        ));
-    }
-}
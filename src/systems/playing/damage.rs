@@ -0,0 +1,223 @@
+use glam::Vec2;
+pub use legion::*;
+use legion::{systems::CommandBuffer, world::SubWorld};
+use rand::Rng;
+
+use crate::{
+    audio_playing::{AudioCommand, AudioCommandBuffer},
+    components::{
+        Asteroid, CTransform, Enemy, Health, InputControlled, Physics, Player, PlayerSlot,
+        Respawning, Score,
+    },
+    events::DeathEvents,
+    rng::SimulationRng,
+};
+
+use super::{collision::asteroid_collider, lives::RESPAWN_STEPS, powerups::spawn_pickup};
+
+pub const SHIP_HEALTH: u32 = 10;
+pub const ENEMY_HEALTH: u32 = 4;
+pub const BULLET_DAMAGE: u32 = 1;
+// asteroids this big or bigger take a few bullets, smaller ones go down to any hit
+pub const MIN_HEALTHY_ASTEROID_SIZE: u32 = 16;
+// what an impact takes off, per unit of the other side's size per unit of speed they hit at
+const IMPACT_DAMAGE: f32 = 0.1;
+// ships count as this big when they ram something
+pub const SHIP_SIZE: u32 = 1;
+// steps a ship can't be hit again after taking an impact, so one overlap only hurts once
+pub const HIT_INVULNERABLE_STEPS: u32 = 40;
+// pieces smaller than this are destroyed rather than split
+const MIN_SPLIT_SIZE: u32 = 3;
+const ASTEROID_SCORE: u32 = 1;
+const ENEMY_SCORE: u32 = 5;
+// chance of a powerup being left behind by a big asteroid or an enemy
+const ASTEROID_PICKUP_CHANCE: f64 = 0.25;
+const ENEMY_PICKUP_CHANCE: f64 = 0.5;
+
+pub struct ScoreInstance {
+    pub owner: Entity,
+    pub score: u32,
+}
+
+pub fn asteroid_health(size: u32) -> Option<Health> {
+    if size >= MIN_HEALTHY_ASTEROID_SIZE {
+        Some(Health::full(size / 8))
+    } else {
+        None
+    }
+}
+
+/** always at least 1, a graze still hurts */
+pub fn impact_damage(other_size: u32, relative_speed: f32) -> u32 {
+    ((other_size as f32 * relative_speed * IMPACT_DAMAGE).ceil() as u32).max(1)
+}
+
+/** takes the damage off, returns true if this is what killed it. anything without health dies to any hit */
+pub fn apply_damage(health: Option<&mut Health>, amount: u32) -> bool {
+    match health {
+        Some(health) => {
+            let was_alive = health.hp > 0;
+            health.hp = health.hp.saturating_sub(amount);
+            was_alive && health.hp == 0
+        }
+        None => true,
+    }
+}
+
+/** credits whoever fired the shot that finished something off, before the deaths are dealt with */
+#[system]
+#[read_component(Asteroid)]
+#[read_component(Enemy)]
+#[write_component(Score)]
+pub fn score_kills(
+    ecs: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] death_events: &DeathEvents,
+) {
+    let mut score_instances: Vec<ScoreInstance> = Vec::new();
+
+    for event in death_events.iter() {
+        let killer = match event.killer {
+            Some(killer) => killer,
+            None => continue,
+        };
+        let entry = match ecs.entry_ref(event.entity) {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        if entry.get_component::<Asteroid>().is_ok() {
+            add_score(&mut score_instances, killer, ASTEROID_SCORE);
+        }
+        if entry.get_component::<Enemy>().is_ok() {
+            add_score(&mut score_instances, killer, ENEMY_SCORE);
+        }
+    }
+
+    // process the score instances
+    for score_instance in score_instances {
+        let mut score_query = <&mut Score>::query();
+        let score = score_query
+            .iter_mut(ecs)
+            .find(|score| score.owner == score_instance.owner);
+        match score {
+            Some(score) => {
+                score.score += score_instance.score;
+            }
+            None => {
+                cmd.push((Score {
+                    owner: score_instance.owner,
+                    score: score_instance.score,
+                },));
+            }
+        }
+    }
+}
+
+/** removes whatever died this step, splits asteroids and takes lives */
+#[system]
+#[read_component(CTransform)]
+#[read_component(Asteroid)]
+#[read_component(Player)]
+#[read_component(Enemy)]
+#[read_component(InputControlled)]
+#[write_component(PlayerSlot)]
+pub fn handle_deaths(
+    ecs: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] death_events: &mut DeathEvents,
+    #[resource] audio_command_buffer: &mut AudioCommandBuffer,
+    #[resource] rng: &mut SimulationRng,
+) {
+    let mut dead_players: Vec<usize> = Vec::new();
+
+    for event in death_events.drain(..) {
+        let entry = match ecs.entry_ref(event.entity) {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        cmd.remove(event.entity);
+
+        if let Ok(asteroid) = entry.get_component::<Asteroid>() {
+            if let Ok(transform) = entry.get_component::<CTransform>() {
+                if asteroid.size >= MIN_SPLIT_SIZE {
+                    split_asteroid(transform, asteroid, cmd, rng);
+                }
+                if asteroid.size >= MIN_HEALTHY_ASTEROID_SIZE
+                    && rng.gen_bool(ASTEROID_PICKUP_CHANCE)
+                {
+                    spawn_pickup(cmd, rng, transform.pos);
+                }
+            }
+            audio_command_buffer.push(AudioCommand::AsteroidExplosion);
+        }
+
+        if entry.get_component::<Enemy>().is_ok() {
+            if let Ok(transform) = entry.get_component::<CTransform>() {
+                if rng.gen_bool(ENEMY_PICKUP_CHANCE) {
+                    spawn_pickup(cmd, rng, transform.pos);
+                }
+            }
+            audio_command_buffer.push(AudioCommand::PlayerExplosion);
+        }
+
+        if entry.get_component::<Player>().is_ok() {
+            if let Ok(input_controlled) = entry.get_component::<InputControlled>() {
+                dead_players.push(input_controlled.player);
+            }
+            audio_command_buffer.push(AudioCommand::PlayerExplosion);
+        }
+    }
+
+    // a player's ship going down costs them a life and they wait for the next one
+    for (entity, slot) in <(Entity, &mut PlayerSlot)>::query().iter_mut(ecs) {
+        if dead_players.contains(&slot.index) {
+            slot.lives = slot.lives.saturating_sub(1);
+            if slot.lives > 0 {
+                cmd.add_component(
+                    *entity,
+                    Respawning {
+                        steps_left: RESPAWN_STEPS,
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn add_score(score_instances: &mut Vec<ScoreInstance>, owner: Entity, score: u32) {
+    // if theres already a score for this entity, increment it
+    match score_instances
+        .iter_mut()
+        .find(|score_instance| score_instance.owner == owner)
+    {
+        Some(score_instance) => score_instance.score += score,
+        None => score_instances.push(ScoreInstance { owner, score }),
+    }
+}
+
+fn split_asteroid(
+    transform: &CTransform,
+    asteroid: &Asteroid,
+    cmd: &mut CommandBuffer,
+    rng: &mut SimulationRng,
+) {
+    let new_size = asteroid.size / 2;
+    for _ in 0..2 {
+        let random_velocity = Vec2::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5));
+        let piece = cmd.push((
+            CTransform {
+                pos: transform.pos,
+                rot: transform.rot,
+            },
+            Asteroid { size: new_size },
+            asteroid_collider(new_size),
+            Physics {
+                vel: random_velocity,
+                rot_vel: rng.gen_range(-50.0..50.0),
+            },
+        ));
+        if let Some(health) = asteroid_health(new_size) {
+            cmd.add_component(piece, health);
+        }
+    }
+}
@@ -1,30 +1,39 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use glam::Vec2;
 pub use legion::*;
-use legion::{storage::Component, systems::CommandBuffer, world::SubWorld};
-use rand::{rngs::StdRng, Rng};
+use legion::{systems::CommandBuffer, world::SubWorld};
+use rand::Rng;
 
 use crate::{
-    components::{
-        CTransform, Enemy, Gun, InputControlled, LookAt, OwnedBy, Physics, Player, WantsToGoTo,
-    },
-    playing::PlayingInputs,
-    state::GameMode,
+    components::{CTransform, Enemy, Gun, LookAt, OwnedBy, Physics, Player, WantsToGoTo},
+    rng::SimulationRng,
+    spatial_hash::SpatialHash,
+    state::RunConfig,
+    topology::Camera,
 };
 
 use super::util::{get_random_pos_in_play_area, is_in_play_area};
 
-const ROTATION_SPEED: f32 = 50.0;
-const ACCELERATION: f32 = 0.04;
 #[system]
 #[read_component(CTransform)]
+#[read_component(Gun)]
+#[read_component(OwnedBy)]
 #[write_component(Physics)]
 #[read_component(Enemy)]
 #[read_component(Player)]
 #[read_component(LookAt)]
 #[write_component(WantsToGoTo)]
-pub fn enemy_behaviour(ecs: &mut SubWorld, #[resource] rng: &mut StdRng, cmd: &mut CommandBuffer) {
+pub fn enemy_behaviour(
+    ecs: &mut SubWorld,
+    #[resource] rng: &mut SimulationRng,
+    #[resource] spatial_hash: &SpatialHash,
+    #[resource] run_config: &RunConfig,
+    #[resource] camera: &Camera,
+    cmd: &mut CommandBuffer,
+) {
+    let topology = run_config.topology;
+
     let players: Vec<_> = <Entity>::query()
         .filter(component::<Player>())
         .iter(ecs)
@@ -54,7 +63,7 @@ pub fn enemy_behaviour(ecs: &mut SubWorld, #[resource] rng: &mut StdRng, cmd: &m
         .filter(!component::<WantsToGoTo>())
         .iter_mut(ecs)
     {
-        let target_position = get_random_pos_in_play_area(rng);
+        let target_position = camera.pos + get_random_pos_in_play_area(rng);
         cmd.add_component(
             *entity,
             WantsToGoTo {
@@ -63,45 +72,37 @@ pub fn enemy_behaviour(ecs: &mut SubWorld, #[resource] rng: &mut StdRng, cmd: &m
         );
     }
 
-    let unowned_guns: Vec<(Entity, Vec2)> = <(Entity, &CTransform)>::query()
+    let unowned_guns: HashSet<Entity> = <(Entity, &CTransform)>::query()
         .filter(component::<Gun>() & !component::<OwnedBy>())
         .iter(ecs)
-        .map(|(entity, transform)| (*entity, transform.pos))
-        .filter(|(_, pos)| is_in_play_area(*pos))
+        .filter(|(_, transform)| is_in_play_area(transform.pos - camera.pos))
+        .map(|(entity, _)| *entity)
         .collect();
 
     // always set your wants to go to to be the nearest unowned gun if there are any
     if !unowned_guns.is_empty() {
-        for (entity, enemy_transform, wants_to_go_to) in
+        for (_entity, enemy_transform, wants_to_go_to) in
             <(Entity, &CTransform, &mut WantsToGoTo)>::query()
                 .filter(component::<Enemy>())
                 .iter_mut(ecs)
         {
-            let enemy_pos = enemy_transform.pos;
-            if let Some(closest_gun) =
-                unowned_guns
-                    .iter()
-                    .min_by(|&(_, gun_pos1), &(_, gun_pos2)| {
-                        let dist1 = (*gun_pos1 - enemy_pos).length_squared(); // using squared magnitude for performance
-                        let dist2 = (*gun_pos2 - enemy_pos).length_squared();
-                        dist1
-                            .partial_cmp(&dist2)
-                            .unwrap_or(std::cmp::Ordering::Equal)
-                    })
-            {
-                let closest_gun_pos = closest_gun.1;
-                wants_to_go_to.pos = closest_gun_pos;
+            if let Some(closest_gun) = spatial_hash.nearest(enemy_transform.pos, |body| {
+                unowned_guns.contains(&body.entity)
+            }) {
+                wants_to_go_to.pos = spatial_hash.body(closest_gun).pos;
             }
         }
     }
 
-    // if the enemy has a target, vel towards it
+    // if the enemy has a target, vel towards it, across the edges if they wrap and that is the short way
     //  TODO: should be its own system
     for (transform, physics, wants_to_go_to) in <(&CTransform, &mut Physics, &WantsToGoTo)>::query()
         .filter(component::<Enemy>())
         .iter_mut(ecs)
     {
-        let dir = (wants_to_go_to.pos - transform.pos).normalize();
+        let dir = topology
+            .offset(transform.pos, wants_to_go_to.pos)
+            .normalize();
         // physics.vel *= 0.98;
         // physics.vel += dir * 0.02;
         // physics.vel *= 0.98;
@@ -113,7 +114,7 @@ pub fn enemy_behaviour(ecs: &mut SubWorld, #[resource] rng: &mut StdRng, cmd: &m
         .filter(component::<Enemy>())
         .iter_mut(ecs)
     {
-        let delta = wants_to_go_to.pos - transform.pos;
+        let delta = topology.offset(transform.pos, wants_to_go_to.pos);
         if delta.length() < 5.0 {
             cmd.remove_component::<WantsToGoTo>(*entity);
         }
@@ -161,7 +162,7 @@ pub fn enemy_behaviour(ecs: &mut SubWorld, #[resource] rng: &mut StdRng, cmd: &m
 #[read_component(LookAt)]
 #[write_component(Physics)]
 #[read_component(CTransform)]
-pub fn look_at(ecs: &mut SubWorld) {
+pub fn look_at(ecs: &mut SubWorld, #[resource] run_config: &RunConfig) {
     let entity_look_at_targets: HashMap<Entity, Option<CTransform>> = <(Entity, &LookAt)>::query()
         .iter(ecs)
         .map(|(entity, look_at)| {
@@ -178,8 +179,10 @@ pub fn look_at(ecs: &mut SubWorld) {
     for (entity, transform, physics) in query.iter_mut(ecs) {
         if let Some(target_transform_option) = entity_look_at_targets.get(entity) {
             if let Some(target_transform) = target_transform_option {
-                // Calculate the direction from the entity to the target
-                let direction_to_target = target_transform.pos - transform.pos;
+                // Calculate the direction from the entity to the target, the short way round
+                let direction_to_target = run_config
+                    .topology
+                    .offset(transform.pos, target_transform.pos);
 
                 // Normalize the direction
                 let magnitude =
@@ -1,47 +1,70 @@
 use glam::Vec2;
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use legion::{systems::CommandBuffer, Entity};
 
 use crate::{
     components::{
-        AttachedTo, CTransform, GrabZone, Gun, InputControlled, OwnedBy, Physics, Player,
+        AttachedTo, CTransform, ExtraLives, GrabZone, Gun, Health, InputControlled, OwnedBy,
+        Physics, Player, PlayerSlot, Powerups, Score,
     },
-    state::State,
+    playing::MAX_PLAYERS,
+    replay::{Replay, ReplayRecorder},
+    rng,
+    settings::Settings,
+    snapshot::{self, PendingSnapshot},
+    state::{insert_simulation_resources, RunConfig, State},
 };
 
+use super::{
+    collision::{gun_collider, ship_collider},
+    damage::SHIP_HEALTH,
+};
+
+// lives each player starts a run with
+pub const STARTING_LIVES: u32 = 3;
+// player one starts where the lone ship always has, the others around them
+pub const PLAYER_SPAWNS: [Vec2; MAX_PLAYERS] = [
+    Vec2::new(100.0, 100.0),
+    Vec2::new(140.0, 100.0),
+    Vec2::new(100.0, 60.0),
+    Vec2::new(140.0, 60.0),
+];
+
 pub fn init(state: &mut State) {
-    state.ecs.clear();
+    if let Some(PendingSnapshot(snapshot)) = state.resources.remove::<PendingSnapshot>() {
+        snapshot::load_into(state, &snapshot);
+        return;
+    }
+    let seed = state.seed.unwrap_or_else(rng::random_seed);
+    println!("Seed: {}", seed);
+    let gameplay = state
+        .resources
+        .get::<Settings>()
+        .map(|settings| settings.gameplay.clone())
+        .unwrap_or_default();
+    let run_config = RunConfig {
+        seed,
+        control_scheme: gameplay.control_scheme,
+        players: gameplay.players,
+        friendly_fire: gameplay.friendly_fire,
+        topology: gameplay.topology,
+    };
+    init_with_config(state, run_config);
+    state
+        .resources
+        .insert(ReplayRecorder(Replay::new(run_config)));
+}
 
-    let player = state.ecs.push((
-        CTransform {
-            pos: Vec2::new(100.0, 100.0),
-            rot: Vec2::new(0.0, 1.0),
-        },
-        Physics {
-            vel: Vec2::new(1.0, 1.0),
-            rot_vel: 30.0,
-        },
-        InputControlled,
-        Player,
-        GrabZone { radius: 15.0 },
-    ));
+pub fn init_with_config(state: &mut State, run_config: RunConfig) {
+    state.ecs.clear();
+    insert_simulation_resources(&mut state.resources, run_config);
 
-    state.ecs.push((
-        CTransform {
-            pos: Vec2::new(100.0, 100.0),
-            rot: Vec2::new(0.0, 1.0),
-        },
-        Gun {
-            wants_to_shoot: false,
-            fire_delay: 10,
-            cooldown: 0,
-        },
-        AttachedTo {
-            entity: player,
-            offset: Vec2::new(10.0, 0.0),
-        },
-        InputControlled,
-        OwnedBy { owner: player },
-    ));
+    for (index, spawn) in PLAYER_SPAWNS
+        .iter()
+        .enumerate()
+        .take(run_config.players.clamp(1, MAX_PLAYERS))
+    {
+        spawn_player(state, index, *spawn);
+    }
 
     // spawn a bunch of guns attached to the player at random offsets
     // for _ in 0..10 {
@@ -66,3 +89,61 @@ pub fn init(state: &mut State) {
     //     ));
     // }
 }
+
+fn spawn_player(state: &mut State, index: usize, pos: Vec2) {
+    let mut cmd = CommandBuffer::new(&state.ecs);
+    let ship = spawn_ship(&mut cmd, index, pos);
+    cmd.push((
+        PlayerSlot {
+            index,
+            lives: STARTING_LIVES,
+        },
+        Score {
+            owner: ship,
+            score: 0,
+        },
+        ExtraLives { awarded: 0 },
+    ));
+    cmd.flush(&mut state.ecs, &mut state.resources);
+}
+
+/** a ship for the player with the gun every ship starts with, used at the start of a run and to respawn */
+pub fn spawn_ship(cmd: &mut CommandBuffer, index: usize, pos: Vec2) -> Entity {
+    let ship = cmd.push((
+        CTransform {
+            pos,
+            rot: Vec2::new(0.0, 1.0),
+        },
+        Physics {
+            vel: Vec2::new(1.0, 1.0),
+            rot_vel: 30.0,
+        },
+        InputControlled { player: index },
+        Player,
+        ship_collider(),
+        GrabZone { radius: 15.0 },
+        Health::full(SHIP_HEALTH),
+        // a new ship starts over without any of the last one's powerups
+        Powerups::default(),
+    ));
+
+    cmd.push((
+        CTransform {
+            pos,
+            rot: Vec2::new(0.0, 1.0),
+        },
+        Gun {
+            wants_to_shoot: false,
+            fire_delay: 10,
+            cooldown: 0,
+        },
+        AttachedTo {
+            entity: ship,
+            offset: Vec2::new(10.0, 0.0),
+        },
+        InputControlled { player: index },
+        OwnedBy { owner: ship },
+        gun_collider(),
+    ));
+    ship
+}
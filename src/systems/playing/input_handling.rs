@@ -1,54 +1,100 @@
+use glam::Vec2;
 use legion::world::SubWorld;
 pub use legion::*;
 
 use crate::{
-    components::{CTransform, Gun, InputControlled, Physics, Player},
-    playing::PlayingInputs,
-    state::GameMode,
+    components::{AttachedTo, CTransform, Gun, InputControlled, Physics, Player, PlayerSlot},
+    modes::Transition,
+    playing::{ControlScheme, PlayerInputs},
+    state::{GameMode, RunConfig},
+    topology::Camera,
 };
 
 const ROTATION_SPEED: f32 = 50.0;
 const ACCELERATION: f32 = 0.04;
 #[system]
-#[read_component(CTransform)]
+#[write_component(CTransform)]
 #[write_component(Physics)]
 #[write_component(Gun)]
 #[read_component(Player)]
+#[read_component(InputControlled)]
+#[read_component(PlayerSlot)]
 pub fn handle_inputs(
     ecs: &mut SubWorld,
-    #[resource] inputs: &PlayingInputs,
-    #[resource] transition_to: &mut Option<GameMode>,
+    #[resource] player_inputs: &PlayerInputs,
+    #[resource] mouse_pos: &Vec2,
+    #[resource] run_config: &RunConfig,
+    #[resource] camera: &Camera,
+    #[resource] transition: &mut Option<Transition>,
 ) {
-    let mut query = <(&CTransform, &mut Physics)>::query().filter(component::<InputControlled>());
-    for (ctransform, physics) in query.iter_mut(ecs) {
-        // if left is true in inputs, rotate left
-        if inputs.left {
-            physics.rot_vel = -ROTATION_SPEED;
-        } else if inputs.right {
-            physics.rot_vel = ROTATION_SPEED;
-        } else {
-            physics.rot_vel = 0.0;
-        }
-
-        // if up is true in inputs, accelerate
-        if inputs.up {
-            physics.vel += ctransform.rot * ACCELERATION;
+    let mut query = <(&mut CTransform, &mut Physics, &InputControlled)>::query();
+    for (ctransform, physics, input_controlled) in query.iter_mut(ecs) {
+        let inputs = &player_inputs.0[input_controlled.player];
+        match run_config.control_scheme {
+            ControlScheme::Classic => {
+                // scaled by how far the stick is pushed, keys always push all the way
+                physics.rot_vel = inputs.turn * ROTATION_SPEED;
+                physics.vel += ctransform.rot * ACCELERATION * inputs.thrust;
+            }
+            ControlScheme::TwinStick => {
+                // screen up is negative y
+                let direction = Vec2::new(inputs.turn, -inputs.thrust).clamp_length_max(1.0);
+                physics.vel += direction * ACCELERATION;
+                physics.rot_vel = 0.0;
+                // the cursor is on the screen, which scrolls on an open field
+                let aim = if input_controlled.player == 0 {
+                    camera.pos + *mouse_pos - ctransform.pos
+                } else {
+                    direction
+                };
+                if let Some(aim) = aim.try_normalize() {
+                    ctransform.rot = aim;
+                }
+            }
         }
+    }
 
-        // if down is true in inputs, decelerate
-        if inputs.down {
-            physics.vel -= ctransform.rot * ACCELERATION;
-        }
+    let mut query = <(&mut Gun, &InputControlled)>::query();
+    for (gun, input_controlled) in query.iter_mut(ecs) {
+        gun.wants_to_shoot = player_inputs.0[input_controlled.player].shoot;
     }
 
-    let mut query = <&mut Gun>::query().filter(component::<InputControlled>());
-    for gun in query.iter_mut(ecs) {
-        gun.wants_to_shoot = inputs.shoot;
+    // once every player is out, go to game over when anyone presses shoot
+    if all_players_out(ecs) && player_inputs.any_shoot() {
+        *transition = Some(Transition::Push(GameMode::GameOver));
     }
+}
+
+/** true when no player has a ship left or lives to come back with */
+pub fn all_players_out(ecs: &SubWorld) -> bool {
+    let ships_left = <&Player>::query().iter(ecs).count() > 0;
+    let lives_left = <&PlayerSlot>::query().iter(ecs).any(|slot| slot.lives > 0);
+    !ships_left && !lives_left
+}
 
-    // if theres no players, go back to title on shoot press
-    let mut players = <(Entity, &CTransform)>::query().filter(component::<Player>());
-    if players.iter(ecs).count() == 0 && inputs.shoot {
-        *transition_to = Some(GameMode::GameOver);
+/** with the twin stick scheme player one's attached guns turn from the ship's heading to point at the cursor */
+#[system]
+#[write_component(CTransform)]
+#[read_component(Gun)]
+#[read_component(AttachedTo)]
+#[read_component(InputControlled)]
+pub fn aim_guns_at_cursor(
+    ecs: &mut SubWorld,
+    #[resource] mouse_pos: &Vec2,
+    #[resource] run_config: &RunConfig,
+    #[resource] camera: &Camera,
+) {
+    if run_config.control_scheme != ControlScheme::TwinStick {
+        return;
+    }
+    let mut query = <(&mut CTransform, &InputControlled)>::query()
+        .filter(component::<Gun>() & component::<AttachedTo>());
+    for (ctransform, _) in query
+        .iter_mut(ecs)
+        .filter(|(_, input_controlled)| input_controlled.player == 0)
+    {
+        if let Some(to_cursor) = (camera.pos + *mouse_pos - ctransform.pos).try_normalize() {
+            ctransform.rot = to_cursor;
+        }
     }
 }
@@ -0,0 +1,39 @@
+pub use legion::*;
+use legion::{systems::CommandBuffer, world::SubWorld};
+
+use crate::components::{CTransform, PreviousCTransform};
+
+#[system]
+#[read_component(CTransform)]
+#[write_component(PreviousCTransform)]
+pub fn store_previous_transform(ecs: &mut SubWorld, cmd: &mut CommandBuffer) {
+    for (ctransform, previous) in <(&CTransform, &mut PreviousCTransform)>::query().iter_mut(ecs) {
+        previous.0 = *ctransform;
+    }
+
+    for (entity, ctransform) in <(Entity, &CTransform)>::query()
+        .filter(!component::<PreviousCTransform>())
+        .iter(ecs)
+    {
+        cmd.add_component(*entity, PreviousCTransform(*ctransform));
+    }
+}
+
+/** blends from the previous step to the current one, entities that only just spawned are drawn where they are */
+pub fn interpolate(
+    current: &CTransform,
+    previous: Option<&PreviousCTransform>,
+    alpha: f32,
+) -> CTransform {
+    match previous {
+        Some(PreviousCTransform(previous)) => CTransform {
+            pos: previous.pos.lerp(current.pos, alpha),
+            rot: previous
+                .rot
+                .lerp(current.rot, alpha)
+                .try_normalize()
+                .unwrap_or(current.rot),
+        },
+        None => *current,
+    }
+}
@@ -0,0 +1,123 @@
+use glam::Vec2;
+pub use legion::*;
+use legion::{systems::CommandBuffer, world::SubWorld};
+
+use crate::{
+    components::{
+        Asteroid, CTransform, Collider, Enemy, ExtraLives, Invulnerable, PlayerSlot, Respawning,
+        Score,
+    },
+    message_stream::{ExpiringMessage, ExpiringMessages},
+    rng::SimulationRng,
+    state::RunConfig,
+    topology::Camera,
+};
+
+use super::{
+    init_state::{spawn_ship, PLAYER_SPAWNS},
+    util::get_random_pos_in_play_area,
+};
+
+// steps between losing a ship and the next one turning up
+pub const RESPAWN_STEPS: u32 = 120;
+// steps a new ship can't be hit for
+pub const INVULNERABLE_STEPS: u32 = 180;
+// a blinking ship is drawn for this many steps, then hidden for as many
+pub const BLINK_STEPS: u32 = 6;
+// how far asteroids and enemies have to be from where a ship comes back
+const SAFE_RADIUS: f32 = 40.0;
+// random spots tried each step when the player's own spawn point is crowded
+const SAFE_SPOT_TRIES: u32 = 16;
+// every this many points is worth another life
+pub const EXTRA_LIFE_SCORE: u32 = 50;
+const MESSAGE_LIFETIME: u32 = 90;
+
+/** once the delay is up the ship comes back at the first spot on screen with nothing dangerous
+nearby, on either side of the edges when they wrap round */
+#[system]
+#[read_component(CTransform)]
+#[read_component(Collider)]
+#[read_component(Asteroid)]
+#[read_component(Enemy)]
+#[read_component(PlayerSlot)]
+#[write_component(Score)]
+#[write_component(Respawning)]
+pub fn respawn(
+    ecs: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] rng: &mut SimulationRng,
+    #[resource] run_config: &RunConfig,
+    #[resource] camera: &Camera,
+) {
+    // as far out as each can hit, so a new ship never turns up already touching one
+    let dangers: Vec<(Vec2, f32)> = <(&CTransform, &Collider)>::query()
+        .filter(component::<Asteroid>() | component::<Enemy>())
+        .iter(ecs)
+        .map(|(transform, collider)| (transform.pos, collider.shape.reach()))
+        .collect();
+    let is_safe = |pos: &Vec2| {
+        dangers.iter().all(|(danger, radius)| {
+            run_config.topology.offset(*pos, *danger).length() > SAFE_RADIUS + radius
+        })
+    };
+
+    let mut query = <(Entity, &PlayerSlot, &mut Score, &mut Respawning)>::query();
+    for (entity, slot, score, respawning) in query.iter_mut(ecs) {
+        if respawning.steps_left > 0 {
+            respawning.steps_left -= 1;
+            continue;
+        }
+
+        // with nowhere safe the player waits and tries again next step
+        let spot = std::iter::once(PLAYER_SPAWNS[slot.index])
+            .chain((0..SAFE_SPOT_TRIES).map(|_| get_random_pos_in_play_area(rng)))
+            .map(|pos| camera.pos + pos)
+            .find(is_safe);
+        if let Some(pos) = spot {
+            let ship = spawn_ship(cmd, slot.index, pos);
+            cmd.add_component(
+                ship,
+                Invulnerable {
+                    steps_left: INVULNERABLE_STEPS,
+                },
+            );
+            // the score carries over to the new ship
+            score.owner = ship;
+            cmd.remove_component::<Respawning>(*entity);
+        }
+    }
+}
+
+#[system]
+#[write_component(Invulnerable)]
+pub fn step_invulnerability(ecs: &mut SubWorld, cmd: &mut CommandBuffer) {
+    for (entity, invulnerable) in <(Entity, &mut Invulnerable)>::query().iter_mut(ecs) {
+        invulnerable.steps_left = invulnerable.steps_left.saturating_sub(1);
+        if invulnerable.steps_left == 0 {
+            cmd.remove_component::<Invulnerable>(*entity);
+        }
+    }
+}
+
+#[system]
+#[write_component(PlayerSlot)]
+#[read_component(Score)]
+#[write_component(ExtraLives)]
+pub fn award_extra_lives(ecs: &mut SubWorld, #[resource] expiring_messages: &mut ExpiringMessages) {
+    let mut query = <(&mut PlayerSlot, &Score, &mut ExtraLives)>::query();
+    for (slot, score, extra_lives) in query.iter_mut(ecs) {
+        // a player who is out stays out, even if a bullet still in flight scores for them
+        if slot.lives == 0 {
+            continue;
+        }
+        let earned = score.score / EXTRA_LIFE_SCORE;
+        while extra_lives.awarded < earned {
+            extra_lives.awarded += 1;
+            slot.lives += 1;
+            expiring_messages.push(ExpiringMessage {
+                text: format!("P{} extra life!", slot.index + 1),
+                lifetime: MESSAGE_LIFETIME,
+            });
+        }
+    }
+}
@@ -1,10 +1,16 @@
 pub mod attached;
+pub mod broadphase;
+pub mod camera;
 pub mod collision;
+pub mod damage;
 pub mod enemy_behaviour;
 pub mod init_state;
 pub mod input_handling;
+pub mod interpolation;
+pub mod lives;
 pub mod ownership;
 pub mod physics;
+pub mod powerups;
 pub mod rendering;
 pub mod shooting;
 pub mod spawning_enemies;
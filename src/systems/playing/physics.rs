@@ -1,12 +1,23 @@
+use glam::Vec2;
 pub use legion::*;
 use legion::{systems::CommandBuffer, world::SubWorld};
 
 use crate::{
-    components::{CTransform, CaptureInPlayField, Physics, VelocityUncapped},
+    components::{
+        AttachedTo, CTransform, CaptureInPlayField, Collider, Physics, Player, PreviousCTransform,
+        VelocityUncapped,
+    },
+    state::RunConfig,
+    topology::{Camera, Topology},
     DIMS,
 };
 
 const MAX_VEL: f32 = 2.0;
+// how much of its speed something keeps bouncing off the walls of an arena
+pub const WALL_RESTITUTION: f32 = 0.8;
+// on an open field, how far from the nearest ship things can get before they are dropped. well off
+// the screen, so nothing vanishes in sight
+pub const DESPAWN_RADIUS: f32 = DIMS.x as f32 * 1.5;
 
 #[system]
 #[write_component(CTransform)]
@@ -20,9 +31,6 @@ pub fn physics(ecs: &mut SubWorld) {
     }
     let mut step_query = <(&mut CTransform, &mut Physics)>::query();
     for (ctransform, physics) in step_query.iter_mut(ecs) {
-        if physics.vel.length() > MAX_VEL {
-            physics.vel = physics.vel.normalize() * MAX_VEL;
-        }
         ctransform.pos += physics.vel;
 
         let rot_matrix = glam::Mat2::from_angle(physics.rot_vel.to_radians() * 0.1);
@@ -32,18 +40,100 @@ pub fn physics(ecs: &mut SubWorld) {
 
 #[system]
 #[write_component(CTransform)]
-pub fn world_wrap(ecs: &mut SubWorld) {
-    let mut query = <&mut CTransform>::query().filter(!component::<CaptureInPlayField>());
-    for ctransform in query.iter_mut(ecs) {
+#[write_component(PreviousCTransform)]
+pub fn world_wrap(ecs: &mut SubWorld, #[resource] run_config: &RunConfig) {
+    if !run_config.topology.wraps() {
+        return;
+    }
+    let mut query = <(&mut CTransform, Option<&mut PreviousCTransform>)>::query()
+        .filter(!component::<CaptureInPlayField>());
+    for (ctransform, previous) in query.iter_mut(ecs) {
+        let mut wrap = Vec2::ZERO;
         if ctransform.pos.x < 0.0 {
-            ctransform.pos.x += DIMS.x as f32;
+            wrap.x = DIMS.x as f32;
         } else if ctransform.pos.x > DIMS.x as f32 {
-            ctransform.pos.x -= DIMS.x as f32;
+            wrap.x = -(DIMS.x as f32);
         }
         if ctransform.pos.y < 0.0 {
-            ctransform.pos.y += DIMS.y as f32;
+            wrap.y = DIMS.y as f32;
         } else if ctransform.pos.y > DIMS.y as f32 {
-            ctransform.pos.y -= DIMS.y as f32;
+            wrap.y = -(DIMS.y as f32);
+        }
+        ctransform.pos += wrap;
+
+        // move the previous position along so interpolation doesnt sweep across the screen
+        if let Some(previous) = previous {
+            previous.0.pos += wrap;
+        }
+    }
+}
+
+/** in an arena everything heading out bounces off the edges of the screen where its collider meets
+them, losing some speed, and nothing gets its middle past them. things still on their way in pass
+through until they are on the field, and attached things go wherever they are attached */
+#[system]
+#[write_component(CTransform)]
+#[write_component(Physics)]
+#[read_component(Collider)]
+pub fn bounce_off_walls(ecs: &mut SubWorld, #[resource] run_config: &RunConfig) {
+    if run_config.topology != Topology::Arena {
+        return;
+    }
+    let size = DIMS.as_vec2();
+    let mut query = <(&mut CTransform, &mut Physics, Option<&Collider>)>::query()
+        .filter(!component::<CaptureInPlayField>() & !component::<AttachedTo>());
+    for (ctransform, physics, collider) in query.iter_mut(ecs) {
+        let reach = collider
+            .map_or(0.0, |collider| collider.shape.reach())
+            .min(size.min_element() / 2.0);
+        for axis in 0..2 {
+            let heading_out = if ctransform.pos[axis] < reach {
+                physics.vel[axis] < 0.0
+            } else if ctransform.pos[axis] > size[axis] - reach {
+                physics.vel[axis] > 0.0
+            } else {
+                false
+            };
+            if heading_out {
+                physics.vel[axis] = -physics.vel[axis] * WALL_RESTITUTION;
+            }
+            // only the middle is kept in, so an asteroid that has just come on isn't snapped in whole
+            ctransform.pos[axis] = ctransform.pos[axis].clamp(0.0, size[axis]);
+        }
+    }
+}
+
+/** on an open field anything left too far behind every ship is dropped, besides ships and whatever
+is attached to them. with no ships about it is measured from the middle of the screen */
+#[system]
+#[read_component(CTransform)]
+#[read_component(Player)]
+#[read_component(AttachedTo)]
+pub fn drop_far_away(
+    ecs: &SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] run_config: &RunConfig,
+    #[resource] camera: &Camera,
+) {
+    if run_config.topology != Topology::Open {
+        return;
+    }
+    let mut anchors: Vec<Vec2> = <&CTransform>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .map(|transform| transform.pos)
+        .collect();
+    if anchors.is_empty() {
+        anchors.push(camera.center());
+    }
+    let mut query = <(Entity, &CTransform)>::query()
+        .filter(!component::<Player>() & !component::<AttachedTo>());
+    for (entity, transform) in query.iter(ecs) {
+        if anchors
+            .iter()
+            .all(|anchor| (transform.pos - *anchor).length() > DESPAWN_RADIUS)
+        {
+            cmd.remove(*entity);
         }
     }
 }
@@ -51,13 +141,17 @@ pub fn world_wrap(ecs: &mut SubWorld) {
 #[system]
 #[write_component(CTransform)]
 #[write_component(Physics)]
-pub fn capture_in_play_field(ecs: &mut SubWorld, cmd: &mut CommandBuffer) {
+pub fn capture_in_play_field(
+    ecs: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] camera: &Camera,
+) {
     let mut query = <(Entity, &mut CTransform)>::query().filter(component::<CaptureInPlayField>());
     for (entity, ctransform) in query.iter_mut(ecs) {
-        let is_in_play_field = ctransform.pos.x > 0.0
-            && ctransform.pos.x < DIMS.x as f32
-            && ctransform.pos.y > 0.0
-            && (ctransform.pos.y < DIMS.y as f32);
+        // on screen, which only moves on an open field
+        let pos = ctransform.pos - camera.pos;
+        let is_in_play_field =
+            pos.x > 0.0 && pos.x < DIMS.x as f32 && pos.y > 0.0 && (pos.y < DIMS.y as f32);
         if is_in_play_field {
             cmd.remove_component::<CaptureInPlayField>(*entity);
         }
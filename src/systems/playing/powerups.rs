@@ -0,0 +1,239 @@
+use glam::Vec2;
+pub use legion::*;
+use legion::{systems::CommandBuffer, world::SubWorld};
+use rand::Rng;
+
+use crate::{
+    components::{
+        CTransform, Collider, ColliderShape, InputControlled, Layers, LifeSpan, Physics, Pickup,
+        Player, PowerupKind, Powerups,
+    },
+    events::CollisionEvents,
+    message_stream::{ExpiringMessage, ExpiringMessages},
+    rendering::Color,
+    rng::SimulationRng,
+};
+
+pub const POWERUP_KINDS: [PowerupKind; 6] = [
+    PowerupKind::BiggerBullets,
+    PowerupKind::LongerRange,
+    PowerupKind::FasterShooting,
+    PowerupKind::TwoStreams,
+    PowerupKind::ThreeStreams,
+    PowerupKind::Laser,
+];
+
+// how many of each stacking powerup count, more are picked up but do nothing
+pub const MAX_POWERUP_LEVEL: u32 = 4;
+// how long extra streams last, picking more up starts the time again
+const STREAMS_STEPS: u32 = 600;
+// the laser adds this much time per pickup, up to the most it can hold
+const LASER_STEPS: u32 = 240;
+const MAX_LASER_STEPS: u32 = 480;
+
+// a plain bullet is a point, each level of bigger bullets makes it this much wider
+const BULLET_RADIUS_PER_LEVEL: f32 = 1.0;
+const BASE_BULLET_LIFESPAN: u32 = 60;
+const BULLET_LIFESPAN_PER_LEVEL: u32 = 15;
+const FIRE_DELAY_PER_LEVEL: u32 = 2;
+const MIN_FIRE_DELAY: u32 = 2;
+// angle between neighbouring streams, in degrees
+const STREAM_SPREAD: f32 = 12.0;
+
+// how far a pickup reaches, ships that touch it pick it up
+const PICKUP_RADIUS: f32 = 4.0;
+// pickups drift away unclaimed after this many steps
+const PICKUP_LIFESPAN: u32 = 600;
+const PICKUP_SPEED: f32 = 0.3;
+const MESSAGE_LIFETIME: u32 = 90;
+
+impl PowerupKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerupKind::BiggerBullets => "bigger bullets",
+            PowerupKind::LongerRange => "longer range",
+            PowerupKind::FasterShooting => "faster shooting",
+            PowerupKind::TwoStreams => "two streams",
+            PowerupKind::ThreeStreams => "three streams",
+            PowerupKind::Laser => "laser",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PowerupKind::BiggerBullets => Color::new(255, 120, 0, 255),
+            PowerupKind::LongerRange => Color::SKYBLUE,
+            PowerupKind::FasterShooting => Color::GOLD,
+            PowerupKind::TwoStreams => Color::LIME,
+            PowerupKind::ThreeStreams => Color::new(0, 228, 48, 255),
+            PowerupKind::Laser => Color::RED,
+        }
+    }
+}
+
+impl Powerups {
+    /** stacking ones go up a level, timed ones start or top up their time */
+    pub fn add(&mut self, kind: PowerupKind) {
+        match kind {
+            PowerupKind::BiggerBullets => {
+                self.bigger_bullets = (self.bigger_bullets + 1).min(MAX_POWERUP_LEVEL)
+            }
+            PowerupKind::LongerRange => {
+                self.longer_range = (self.longer_range + 1).min(MAX_POWERUP_LEVEL)
+            }
+            PowerupKind::FasterShooting => {
+                self.faster_shooting = (self.faster_shooting + 1).min(MAX_POWERUP_LEVEL)
+            }
+            // two streams never takes away a third one, it only keeps it going longer
+            PowerupKind::TwoStreams => {
+                self.streams = self.streams.max(2);
+                self.streams_steps_left = STREAMS_STEPS;
+            }
+            PowerupKind::ThreeStreams => {
+                self.streams = 3;
+                self.streams_steps_left = STREAMS_STEPS;
+            }
+            PowerupKind::Laser => {
+                self.laser_steps_left = (self.laser_steps_left + LASER_STEPS).min(MAX_LASER_STEPS)
+            }
+        }
+    }
+
+    /** the kinds in effect and their level, timed ones count as level 1, for the hud */
+    pub fn active(&self) -> Vec<(PowerupKind, u32)> {
+        let streams = match self.streams {
+            2 => Some(PowerupKind::TwoStreams),
+            3 => Some(PowerupKind::ThreeStreams),
+            _ => None,
+        };
+        [
+            (PowerupKind::BiggerBullets, self.bigger_bullets),
+            (PowerupKind::LongerRange, self.longer_range),
+            (PowerupKind::FasterShooting, self.faster_shooting),
+        ]
+        .into_iter()
+        .chain(streams.map(|kind| (kind, 1)))
+        .chain((self.laser_steps_left > 0).then_some((PowerupKind::Laser, 1)))
+        .filter(|(_, level)| *level > 0)
+        .collect()
+    }
+
+    pub fn has_laser(&self) -> bool {
+        self.laser_steps_left > 0
+    }
+
+    pub fn bullet_radius(&self) -> f32 {
+        self.bigger_bullets as f32 * BULLET_RADIUS_PER_LEVEL
+    }
+
+    pub fn bullet_lifespan(&self) -> u32 {
+        BASE_BULLET_LIFESPAN + self.longer_range * BULLET_LIFESPAN_PER_LEVEL
+    }
+
+    pub fn fire_delay(&self, base: u32) -> u32 {
+        base.saturating_sub(self.faster_shooting * FIRE_DELAY_PER_LEVEL)
+            .max(MIN_FIRE_DELAY)
+    }
+
+    /** one direction per stream, fanned out evenly around the gun's heading */
+    pub fn stream_directions(&self, dir: Vec2) -> Vec<Vec2> {
+        let streams = self.streams.max(1);
+        (0..streams)
+            .map(|i| {
+                let offset = (i as f32 - (streams - 1) as f32 / 2.0) * STREAM_SPREAD;
+                glam::Mat2::from_angle(offset.to_radians()) * dir
+            })
+            .collect()
+    }
+}
+
+/** ships find pickups, pickups don't go looking for anything */
+pub fn pickup_collider() -> Collider {
+    Collider {
+        shape: ColliderShape::circle(PICKUP_RADIUS),
+        layer: Layers::PICKUP,
+        mask: Layers::NONE,
+    }
+}
+
+pub fn spawn_pickup(cmd: &mut CommandBuffer, rng: &mut SimulationRng, pos: Vec2) {
+    let kind = POWERUP_KINDS[rng.gen_range(0..POWERUP_KINDS.len())];
+    let angle: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
+    cmd.push((
+        CTransform {
+            pos,
+            rot: Vec2::new(0.0, 1.0),
+        },
+        Physics {
+            vel: Vec2::from_angle(angle) * PICKUP_SPEED,
+            rot_vel: 0.0,
+        },
+        Pickup { kind },
+        pickup_collider(),
+        LifeSpan {
+            frames_left: PICKUP_LIFESPAN,
+        },
+    ));
+}
+
+/** ships pick up the pickups they touch */
+#[system]
+#[read_component(Pickup)]
+#[read_component(Player)]
+#[read_component(InputControlled)]
+#[write_component(Powerups)]
+pub fn collect_pickups(
+    ecs: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] collision_events: &CollisionEvents,
+    #[resource] expiring_messages: &mut ExpiringMessages,
+) {
+    let mut collected: Vec<Entity> = Vec::new();
+    for event in collision_events.iter() {
+        for (pickup_entity, ship_entity) in [(event.a, event.b), (event.b, event.a)] {
+            // the first ship touching it gets it
+            if collected.contains(&pickup_entity) {
+                continue;
+            }
+            let kind = match ecs
+                .entry_ref(pickup_entity)
+                .ok()
+                .and_then(|entry| entry.get_component::<Pickup>().ok().copied())
+            {
+                Some(pickup) => pickup.kind,
+                None => continue,
+            };
+            let mut entry = match ecs.entry_mut(ship_entity) {
+                Ok(entry) if entry.get_component::<Player>().is_ok() => entry,
+                _ => continue,
+            };
+            let player = match entry.get_component::<InputControlled>() {
+                Ok(input_controlled) => input_controlled.player,
+                Err(_) => continue,
+            };
+            if let Ok(powerups) = entry.get_component_mut::<Powerups>() {
+                powerups.add(kind);
+                collected.push(pickup_entity);
+                cmd.remove(pickup_entity);
+                expiring_messages.push(ExpiringMessage {
+                    text: format!("P{} {}!", player + 1, kind.label()),
+                    lifetime: MESSAGE_LIFETIME,
+                });
+            }
+        }
+    }
+}
+
+#[system]
+#[write_component(Powerups)]
+pub fn step_powerups(ecs: &mut SubWorld) {
+    for powerups in <&mut Powerups>::query().iter_mut(ecs) {
+        if powerups.streams_steps_left > 0 {
+            powerups.streams_steps_left -= 1;
+            if powerups.streams_steps_left == 0 {
+                powerups.streams = 0;
+            }
+        }
+        powerups.laser_steps_left = powerups.laser_steps_left.saturating_sub(1);
+    }
+}
@@ -1,185 +1,406 @@
 use glam::Vec2;
 use legion::world::SubWorld;
 pub use legion::*;
-use rand::{rngs::StdRng, Rng};
-use raylib::prelude::Color;
+use rand::Rng;
 
 use crate::{
     components::{
-        Asteroid, AttachedTo, Bullet, CTransform, Enemy, GrabZone, Gun, Player, Score, WantsToGoTo,
+        Asteroid, AttachedTo, Beam, Bullet, CTransform, CaptureInPlayField, Collider,
+        ColliderShape, Enemy, GrabZone, Gun, Health, InputControlled, Invulnerable, Pickup, Player,
+        PlayerSlot, Powerups, PreviousCTransform, Score, WantsToGoTo,
     },
     message_stream::ExpiringMessages,
-    rendering::{DrawCommand, RenderCommandBuffer},
-    DIMS,
+    rendering::{Color, DrawCommand, Interpolation, RenderCommandBuffer, PLAYER_COLORS},
+    rng::RenderRng,
+    state::RunConfig,
+    topology::Camera,
+    torus, DIMS,
 };
 
+use super::{interpolation::interpolate, lives::BLINK_STEPS};
+
+const HEALTH_BAR_GAP: f32 = 3.0;
+const MIN_HEALTH_BAR_WIDTH: f32 = 10.0;
+// hud powerup icons, going left from the start of each player's row
+const HUD_ICON_SPACING: f32 = 8.0;
+const HUD_ICON_DROP: f32 = 3.0;
+// how far past their positions the frontend draws things, so copies show up before they reach an edge
+const SHIP_DRAWN_REACH: f32 = 10.0;
+const GUN_DRAWN_REACH: f32 = 3.0;
+const PICKUP_DRAWN_REACH: f32 = 4.0;
+const BULLET_DRAWN_REACH: f32 = 1.0;
+
 #[system]
 #[read_component(CTransform)]
+#[read_component(PreviousCTransform)]
 #[read_component(Asteroid)]
 #[read_component(AttachedTo)]
 #[read_component(GrabZone)]
 #[read_component(WantsToGoTo)]
+#[read_component(InputControlled)]
+#[read_component(Invulnerable)]
+#[read_component(Health)]
+#[read_component(Bullet)]
+#[read_component(Collider)]
+#[read_component(Beam)]
+#[read_component(Pickup)]
+#[read_component(CaptureInPlayField)]
 pub fn entity_render(
     ecs: &SubWorld,
-    #[resource] rng: &mut StdRng,
+    #[resource] rng: &mut RenderRng,
+    #[resource] interpolation: &Interpolation,
+    #[resource] run_config: &RunConfig,
+    #[resource] camera: &Camera,
     #[resource] render_command_buffer: &mut RenderCommandBuffer,
 ) {
+    let alpha = interpolation.alpha;
+    let topology = run_config.topology;
+    let wraps = topology.wraps();
+    let first_command = render_command_buffer.len();
+
     // render GrabZones
-    <(&CTransform, &GrabZone)>::query()
+    <(&CTransform, Option<&PreviousCTransform>, &GrabZone)>::query()
         .iter(ecs)
-        .for_each(|(transform, grabzone)| {
-            render_command_buffer.push(DrawCommand::Circle {
-                pos: transform.pos,
-                radius: grabzone.radius,
-                color: Color::new(0, 0, 255, 50),
-            })
+        .for_each(|(transform, previous, grabzone)| {
+            let transform = interpolate(transform, previous, alpha);
+            push_wrapped(
+                render_command_buffer,
+                wraps,
+                DrawCommand::Circle {
+                    pos: transform.pos,
+                    radius: grabzone.radius,
+                    color: Color::new(0, 0, 255, 50),
+                },
+                around(transform.pos, grabzone.radius),
+            )
         });
 
-    // render WantsToGoTo
-    <(&CTransform, &WantsToGoTo)>::query()
+    // render WantsToGoTo, the way the enemy goes
+    <(&CTransform, Option<&PreviousCTransform>, &WantsToGoTo)>::query()
         .iter(ecs)
-        .for_each(|(transform, wants_to_go_to)| {
-            render_command_buffer.push(DrawCommand::Line {
-                start: transform.pos,
-                end: wants_to_go_to.pos,
-                color: Color::new(0, 0, 255, 50),
-            })
+        .for_each(|(transform, previous, wants_to_go_to)| {
+            let transform = interpolate(transform, previous, alpha);
+            let end = transform.pos + topology.offset(transform.pos, wants_to_go_to.pos);
+            push_wrapped(
+                render_command_buffer,
+                wraps,
+                DrawCommand::Line {
+                    start: transform.pos,
+                    end,
+                    color: Color::new(0, 0, 255, 50),
+                },
+                (transform.pos.min(end), transform.pos.max(end)),
+            )
         });
 
-    // schedule asteroid rendering
-    <(&CTransform, &Asteroid)>::query()
-        .iter(ecs)
-        .for_each(|(transform, asteroid)| {
-            render_command_buffer.push(DrawCommand::Asteroid {
+    // schedule asteroid rendering, ones still coming in from off the field don't wrap round yet
+    <(
+        &CTransform,
+        Option<&PreviousCTransform>,
+        &Asteroid,
+        Option<&CaptureInPlayField>,
+    )>::query()
+    .iter(ecs)
+    .for_each(|(transform, previous, asteroid, incoming)| {
+        let transform = interpolate(transform, previous, alpha);
+        push_wrapped(
+            render_command_buffer,
+            wraps && incoming.is_none(),
+            DrawCommand::Asteroid {
                 pos: transform.pos,
                 size: asteroid.size,
                 dir: transform.rot,
-            });
-        });
-    // wrap rendering
-    // <(&CTransform, &Asteroid)>::query()
-    //     .iter(ecs)
-    //     .for_each(|(transform, asteroid)| {
-    //         let mut positions = vec![transform.pos];
-
-    //         // Check if the object overlaps the right edge
-    //         if transform.pos.x + (asteroid.size as f32) > DIMS.x as f32 {
-    //             positions.push(Vec2::new(transform.pos.x - DIMS.x as f32, transform.pos.y));
-    //         }
-    //         // Check if the object overlaps the left edge
-    //         else if transform.pos.x - (asteroid.size as f32) < 0.0 {
-    //             positions.push(Vec2::new(transform.pos.x + DIMS.x as f32, transform.pos.y));
-    //         }
-
-    //         // Check if the object overlaps the top edge
-    //         if transform.pos.y + (asteroid.size as f32) > DIMS.y as f32 {
-    //             positions.push(Vec2::new(transform.pos.x, transform.pos.y - DIMS.y as f32));
-    //         }
-    //         // Check if the object overlaps the bottom edge
-    //         else if transform.pos.y - (asteroid.size as f32) < 0.0 {
-    //             positions.push(Vec2::new(transform.pos.x, transform.pos.y + DIMS.y as f32));
-    //         }
-
-    //         for pos in positions {
-    //             render_command_buffer.push(DrawCommand::Asteroid {
-    //                 pos,
-    //                 size: asteroid.size,
-    //                 dir: transform.rot,
-    //             });
-    //         }
-    //     });
-
-    // schedule bullet rendering
-    <&CTransform>::query()
+            },
+            around(transform.pos, asteroid.size as f32),
+        );
+    });
+
+    // schedule bullet rendering, bigger bullets are drawn as big as they hit
+    <(&CTransform, Option<&PreviousCTransform>, Option<&Collider>)>::query()
         .filter(component::<Bullet>())
         .iter(ecs)
-        .for_each(|transform| {
-            render_command_buffer.push(DrawCommand::ColoredSquare {
-                pos: transform.pos,
-                color: Color::new(255, rng.gen_range(10..255), 0, 255),
-            });
+        .for_each(|(transform, previous, collider)| {
+            let transform = interpolate(transform, previous, alpha);
+            let color = Color::new(255, rng.0.gen_range(10..255), 0, 255);
+            let (command, reach) = match collider {
+                Some(Collider {
+                    shape: ColliderShape::Circle { radius },
+                    ..
+                }) if *radius > 0.0 => (
+                    DrawCommand::Circle {
+                        pos: transform.pos,
+                        radius: *radius,
+                        color,
+                    },
+                    *radius,
+                ),
+                _ => (
+                    DrawCommand::ColoredSquare {
+                        pos: transform.pos,
+                        color,
+                    },
+                    BULLET_DRAWN_REACH,
+                ),
+            };
+            push_wrapped(
+                render_command_buffer,
+                wraps,
+                command,
+                around(transform.pos, reach),
+            );
         });
 
-    // schedule player rendering
-    <&CTransform>::query()
-        .filter(component::<Player>())
+    // schedule beam rendering
+    <(&CTransform, &Beam)>::query()
+        .iter(ecs)
+        .for_each(|(transform, beam)| {
+            let end = transform.pos + beam.dir * beam.length;
+            push_wrapped(
+                render_command_buffer,
+                wraps,
+                DrawCommand::Line {
+                    start: transform.pos,
+                    end,
+                    color: Color::new(255, rng.0.gen_range(0..100), rng.0.gen_range(0..100), 255),
+                },
+                (transform.pos.min(end), transform.pos.max(end)),
+            );
+        });
+
+    // schedule pickup rendering
+    <(&CTransform, Option<&PreviousCTransform>, &Pickup)>::query()
         .iter(ecs)
-        .for_each(|transform| {
-            render_command_buffer.push(DrawCommand::Ship {
+        .for_each(|(transform, previous, pickup)| {
+            let transform = interpolate(transform, previous, alpha);
+            push_wrapped(
+                render_command_buffer,
+                wraps,
+                DrawCommand::Pickup {
+                    pos: transform.pos,
+                    color: pickup.kind.color(),
+                },
+                around(transform.pos, PICKUP_DRAWN_REACH),
+            );
+        });
+
+    // schedule player rendering, invulnerable ships blink
+    <(
+        &CTransform,
+        Option<&PreviousCTransform>,
+        &InputControlled,
+        Option<&Invulnerable>,
+    )>::query()
+    .filter(component::<Player>())
+    .iter(ecs)
+    .for_each(|(transform, previous, input_controlled, invulnerable)| {
+        if blinked_out(invulnerable) {
+            return;
+        }
+        let transform = interpolate(transform, previous, alpha);
+        push_wrapped(
+            render_command_buffer,
+            wraps,
+            DrawCommand::Ship {
                 pos: transform.pos,
                 dir: transform.rot,
-                color: Color::GOLD,
-            });
-        });
+                color: PLAYER_COLORS[input_controlled.player],
+            },
+            around(transform.pos, SHIP_DRAWN_REACH),
+        );
+    });
 
     // schedule player rendering
-    <&CTransform>::query()
-        .filter(component::<Enemy>())
-        .iter(ecs)
-        .for_each(|transform| {
-            render_command_buffer.push(DrawCommand::Ship {
+    <(
+        &CTransform,
+        Option<&PreviousCTransform>,
+        Option<&Invulnerable>,
+    )>::query()
+    .filter(component::<Enemy>())
+    .iter(ecs)
+    .for_each(|(transform, previous, invulnerable)| {
+        if blinked_out(invulnerable) {
+            return;
+        }
+        let transform = interpolate(transform, previous, alpha);
+        push_wrapped(
+            render_command_buffer,
+            wraps,
+            DrawCommand::Ship {
                 pos: transform.pos,
                 dir: transform.rot,
                 color: Color::MAROON,
-            });
-        });
+            },
+            around(transform.pos, SHIP_DRAWN_REACH),
+        );
+    });
 
     // schedule player rendering
-    <&CTransform>::query()
+    <(&CTransform, Option<&PreviousCTransform>)>::query()
         .filter(component::<Gun>())
         .iter(ecs)
-        .for_each(|transform| {
-            render_command_buffer.push(DrawCommand::Gun {
-                pos: transform.pos,
-                dir: transform.rot,
-            });
+        .for_each(|(transform, previous)| {
+            let transform = interpolate(transform, previous, alpha);
+            push_wrapped(
+                render_command_buffer,
+                wraps,
+                DrawCommand::Gun {
+                    pos: transform.pos,
+                    dir: transform.rot,
+                },
+                around(transform.pos, GUN_DRAWN_REACH),
+            );
         });
 
+    // health bars over anything that has been hurt, clear of where it can be hit
+    <(
+        &CTransform,
+        Option<&PreviousCTransform>,
+        &Health,
+        Option<&Collider>,
+    )>::query()
+    .iter(ecs)
+    .filter(|(_, _, health, _)| health.hp < health.max_hp)
+    .for_each(|(transform, previous, health, collider)| {
+        let transform = interpolate(transform, previous, alpha);
+        let radius = collider.map_or(0.0, |collider| collider.shape.reach());
+        let pos = transform.pos - Vec2::new(0.0, radius + HEALTH_BAR_GAP);
+        let width = (radius * 2.0).max(MIN_HEALTH_BAR_WIDTH);
+        push_wrapped(
+            render_command_buffer,
+            wraps,
+            DrawCommand::HealthBar {
+                pos,
+                width,
+                fraction: health.hp as f32 / health.max_hp.max(1) as f32,
+            },
+            around(pos, width / 2.0),
+        );
+    });
+
     // render attachment struts
-    let start_to: Vec<(CTransform, Entity)> = <(&CTransform, &AttachedTo)>::query()
-        .iter(ecs)
-        .map(|(transform, attached_to)| (*transform, attached_to.entity))
-        .collect();
+    let start_to: Vec<(CTransform, Entity)> =
+        <(&CTransform, Option<&PreviousCTransform>, &AttachedTo)>::query()
+            .iter(ecs)
+            .map(|(transform, previous, attached_to)| {
+                (interpolate(transform, previous, alpha), attached_to.entity)
+            })
+            .collect();
 
     for (start, end_entity) in start_to {
         if let Ok(end) = ecs.entry_ref(end_entity) {
             if let Ok(end_transform) = end.get_component::<CTransform>() {
+                let end_transform = interpolate(
+                    end_transform,
+                    end.get_component::<PreviousCTransform>().ok(),
+                    alpha,
+                );
+                // the two ends can be either side of an edge, so go the short way round
+                let end_pos = start.pos + topology.offset(start.pos, end_transform.pos);
                 // skip if line is too long
-                if (start.pos - end_transform.pos).length() > 100.0 {
+                if (start.pos - end_pos).length() > 100.0 {
                     continue;
                 }
 
-                render_command_buffer.push(DrawCommand::Line {
-                    start: start.pos,
-                    end: end_transform.pos,
-                    color: Color::new(255, 255, 255, 100),
-                });
+                push_wrapped(
+                    render_command_buffer,
+                    wraps,
+                    DrawCommand::Line {
+                        start: start.pos,
+                        end: end_pos,
+                        color: Color::new(255, 255, 255, 100),
+                    },
+                    (start.pos.min(end_pos), start.pos.max(end_pos)),
+                );
             }
         }
     }
+
+    // everything above is where it is on the field, which only scrolls on an open one
+    let view = camera.interpolated(alpha);
+    if view != Vec2::ZERO {
+        for command in render_command_buffer[first_command..].iter_mut() {
+            *command = command.translated(-view);
+        }
+    }
+}
+
+/** draws it, and when it wraps round again across every edge of the play field the box from min to
+max hangs over, so things slide off one side and onto the other rather than popping across */
+fn push_wrapped(
+    render_command_buffer: &mut RenderCommandBuffer,
+    wraps: bool,
+    command: DrawCommand,
+    (min, max): (Vec2, Vec2),
+) {
+    if wraps {
+        for offset in torus::ghost_offsets(min, max) {
+            render_command_buffer.push(command.translated(offset));
+        }
+    }
+    render_command_buffer.push(command);
+}
+
+// the box something this far across around pos fits in
+fn around(pos: Vec2, reach: f32) -> (Vec2, Vec2) {
+    (pos - Vec2::splat(reach), pos + Vec2::splat(reach))
+}
+
+// invulnerable ships blink, this is true while they are hidden
+fn blinked_out(invulnerable: Option<&Invulnerable>) -> bool {
+    invulnerable.is_some_and(|invulnerable| (invulnerable.steps_left / BLINK_STEPS) % 2 == 1)
 }
 
 // render system
 /* fetch position and sprite entities, and just blit them with a fixed size with the given position */
 #[system]
 #[read_component(Score)]
+#[read_component(PlayerSlot)]
+#[read_component(Powerups)]
 pub fn score_render(ecs: &SubWorld, #[resource] render_command_buffer: &mut RenderCommandBuffer) {
     let mut cursor = Vec2::new(DIMS.x as f32 * 0.28, DIMS.y as f32 * 0.1);
     let size = 1;
 
-    // schedule asteroid rendering
-    <&Score>::query().iter(ecs).for_each(|score| {
-        let text = format!("Score: {}", score.score);
+    // one row per player, in player order
+    let mut rows: Vec<(PlayerSlot, u32, Entity)> = <(&PlayerSlot, &Score)>::query()
+        .iter(ecs)
+        .map(|(slot, score)| (*slot, score.score, score.owner))
+        .collect();
+    rows.sort_by_key(|(slot, _, _)| slot.index);
+
+    for (slot, score, score_owner) in rows {
+        let text = format!("P{} Score: {} Lives: {}", slot.index + 1, score, slot.lives);
 
         render_command_buffer.push(DrawCommand::Text {
             pos: cursor,
             text,
             size,
-            color: Color::WHITE,
+            color: PLAYER_COLORS[slot.index],
         });
 
+        // the ship's powerups to the left of its row, with the level of the ones that stack
+        let powerups = ecs
+            .entry_ref(score_owner)
+            .ok()
+            .and_then(|entry| entry.get_component::<Powerups>().ok().copied())
+            .unwrap_or_default();
+        for (i, (kind, level)) in powerups.active().into_iter().enumerate() {
+            let pos = cursor - Vec2::new(HUD_ICON_SPACING * (i + 1) as f32, -HUD_ICON_DROP);
+            render_command_buffer.push(DrawCommand::Pickup {
+                pos,
+                color: kind.color(),
+            });
+            if level > 1 {
+                render_command_buffer.push(DrawCommand::Text {
+                    pos: pos + Vec2::new(2.0, 1.0),
+                    text: level.to_string(),
+                    size,
+                    color: Color::WHITE,
+                });
+            }
+        }
+
         cursor.y += 10.0;
-    });
+    }
 }
 
 #[system]
@@ -1,21 +1,50 @@
+use std::collections::HashMap;
+
+use glam::Vec2;
 pub use legion::*;
 use legion::{systems::CommandBuffer, world::SubWorld};
 
 use crate::{
     audio_playing::{AudioCommand, AudioCommandBuffer},
-    components::{Bullet, CTransform, Gun, LifeSpan, OwnedBy, Physics, VelocityUncapped},
+    components::{
+        Beam, Bullet, CTransform, Collider, ColliderShape, Damage, Gun, Layers, LifeSpan, OwnedBy,
+        Physics, Powerups, VelocityUncapped,
+    },
+    state::RunConfig,
+    DIMS,
 };
 
+use super::{collision::shot_collider, damage::BULLET_DAMAGE};
+
 const BULLET_VELOCITY: f32 = 100.0;
+// steps between laser pulses, each one hits everything along it once
+const LASER_FIRE_DELAY: u32 = 3;
+// a pulse stays on screen until the next one replaces it
+const BEAM_LIFESPAN: u32 = LASER_FIRE_DELAY;
+
 #[system]
 #[read_component(CTransform)]
 #[write_component(Gun)]
 #[read_component(OwnedBy)]
+#[read_component(Powerups)]
+#[read_component(Collider)]
 pub fn guns(
     ecs: &mut SubWorld,
     #[resource] audio_command_buffer: &mut AudioCommandBuffer,
+    #[resource] run_config: &RunConfig,
     cmd: &mut CommandBuffer,
 ) {
+    // guns shoot with whatever the ship they belong to has picked up
+    let powerups: HashMap<Entity, Powerups> = <(Entity, &Powerups)>::query()
+        .iter(ecs)
+        .map(|(entity, powerups)| (*entity, *powerups))
+        .collect();
+    // and for whichever side it is on
+    let sides: HashMap<Entity, Layers> = <(Entity, &Collider)>::query()
+        .iter(ecs)
+        .map(|(entity, collider)| (*entity, collider.layer))
+        .collect();
+
     let mut query = <(&CTransform, &mut Gun, &OwnedBy)>::query();
     for (ctransform, gun, owned_by) in query.iter_mut(ecs) {
         if gun.cooldown > 0 {
@@ -23,24 +52,69 @@ pub fn guns(
         }
 
         if gun.cooldown == 0 && gun.wants_to_shoot {
-            cmd.push((
-                CTransform {
-                    pos: ctransform.pos + ctransform.rot * 2.0,
-                    rot: ctransform.rot,
-                },
-                Physics {
-                    vel: ctransform.rot * BULLET_VELOCITY,
-                    rot_vel: 0.0,
-                },
-                Bullet,
-                OwnedBy {
-                    owner: owned_by.owner,
-                },
-                LifeSpan { frames_left: 60 },
-                VelocityUncapped,
-            ));
-
-            gun.cooldown = gun.fire_delay;
+            let powerups = powerups.get(&owned_by.owner).copied().unwrap_or_default();
+            let muzzle = ctransform.pos + ctransform.rot * 2.0;
+            let shooter = sides.get(&owned_by.owner).copied().unwrap_or_default();
+
+            if powerups.has_laser() {
+                cmd.push((
+                    CTransform {
+                        pos: muzzle,
+                        rot: ctransform.rot,
+                    },
+                    Beam {
+                        dir: ctransform.rot,
+                        length: DIMS.x as f32,
+                    },
+                    shot_collider(
+                        ColliderShape::segment(Vec2::new(DIMS.x as f32, 0.0)),
+                        shooter,
+                        run_config.friendly_fire,
+                    ),
+                    Damage {
+                        amount: BULLET_DAMAGE,
+                    },
+                    OwnedBy {
+                        owner: owned_by.owner,
+                    },
+                    LifeSpan {
+                        frames_left: BEAM_LIFESPAN,
+                    },
+                ));
+                gun.cooldown = LASER_FIRE_DELAY;
+                continue;
+            }
+
+            for dir in powerups.stream_directions(ctransform.rot) {
+                cmd.push((
+                    CTransform {
+                        pos: muzzle,
+                        rot: dir,
+                    },
+                    Physics {
+                        vel: dir * BULLET_VELOCITY,
+                        rot_vel: 0.0,
+                    },
+                    Bullet,
+                    shot_collider(
+                        ColliderShape::circle(powerups.bullet_radius()),
+                        shooter,
+                        run_config.friendly_fire,
+                    ),
+                    Damage {
+                        amount: BULLET_DAMAGE,
+                    },
+                    OwnedBy {
+                        owner: owned_by.owner,
+                    },
+                    LifeSpan {
+                        frames_left: powerups.bullet_lifespan(),
+                    },
+                    VelocityUncapped,
+                ));
+            }
+
+            gun.cooldown = powerups.fire_delay(gun.fire_delay);
 
             audio_command_buffer.push(AudioCommand::Shoot);
         }
@@ -1,25 +1,30 @@
 use glam::Vec2;
 use legion::systems::CommandBuffer;
 pub use legion::*;
-use rand::{rngs::StdRng, Rng};
+use rand::Rng;
 
 use crate::{
     components::{
-        Asteroid, Attachable, AttachedTo, CTransform, CaptureInPlayField, Enemy, GrabZone, Gun,
-        Physics,
+        Asteroid, Attachable, CTransform, CaptureInPlayField, Enemy, GrabZone, Gun, Health, Physics,
     },
+    rng::SimulationRng,
     timer::{AsteroidSpawnTimer, EnemySpawnTimer, GunSpawnTimer},
-    DIMS,
+    topology::Camera,
 };
 
-use super::util::{get_padded_position_outside_play_area, get_random_pos_in_play_area};
+use super::{
+    collision::{asteroid_collider, enemy_collider, gun_collider},
+    damage::{asteroid_health, ENEMY_HEALTH},
+    util::{get_padded_position_outside_play_area, get_random_pos_in_play_area},
+};
 
 #[system]
 #[read_component(Asteroid)]
 #[write_component(CTransform)]
 pub fn spawn_asteroids(
     #[resource] asteroid_spawn_timer: &mut AsteroidSpawnTimer,
-    #[resource] rng: &mut StdRng,
+    #[resource] rng: &mut SimulationRng,
+    #[resource] camera: &Camera,
     cmd: &mut CommandBuffer,
 ) {
     asteroid_spawn_timer.step();
@@ -29,26 +34,30 @@ pub fn spawn_asteroids(
 
         let size = rng.gen_range(10..30);
         let padded_size = size as f32 * 2.0;
-        let position = get_padded_position_outside_play_area(rng, padded_size);
-        let target_position = get_random_pos_in_play_area(rng);
+        let position = camera.pos + get_padded_position_outside_play_area(rng, padded_size);
+        let target_position = camera.pos + get_random_pos_in_play_area(rng);
         let direction = (target_position - position).normalize();
         let velocity = direction * rng.gen_range(0.5..1.0);
 
         let angle = rng.gen_range(0.0..360.0);
         let rotation = glam::Mat2::from_angle(angle) * Vec2::new(0.0, 1.0);
 
-        cmd.push((
+        let asteroid = cmd.push((
             CTransform {
                 pos: position,
                 rot: rotation,
             },
             Asteroid { size },
+            asteroid_collider(size),
             Physics {
                 vel: velocity,
                 rot_vel: rng.gen_range(-0.01..0.01),
             },
             CaptureInPlayField,
         ));
+        if let Some(health) = asteroid_health(size) {
+            cmd.add_component(asteroid, health);
+        }
     }
 }
 
@@ -73,7 +82,8 @@ pub fn spawn_asteroids(
 #[write_component(CTransform)]
 pub fn spawn_enemies(
     #[resource] enemy_spawn_timer: &mut EnemySpawnTimer,
-    #[resource] rng: &mut StdRng,
+    #[resource] rng: &mut SimulationRng,
+    #[resource] camera: &Camera,
     cmd: &mut CommandBuffer,
 ) {
     enemy_spawn_timer.step();
@@ -83,14 +93,11 @@ pub fn spawn_enemies(
 
         let size = 1;
         let padded_size = size as f32 * 2.0;
-        let position = get_padded_position_outside_play_area(rng, padded_size);
-        let target_position = get_random_pos_in_play_area(rng);
+        let position = camera.pos + get_padded_position_outside_play_area(rng, padded_size);
+        let target_position = camera.pos + get_random_pos_in_play_area(rng);
         let direction = (target_position - position).normalize();
         let velocity = direction * rng.gen_range(0.5..1.0);
 
-        let angle = rng.gen_range(0.0..360.0);
-        let rotation = glam::Mat2::from_angle(angle) * Vec2::new(0.0, 1.0);
-
         cmd.push((
             Enemy,
             CTransform {
@@ -101,7 +108,9 @@ pub fn spawn_enemies(
                 vel: velocity,
                 rot_vel: 0.0,
             },
+            enemy_collider(),
             GrabZone { radius: 10.0 },
+            Health::full(ENEMY_HEALTH),
         ));
     }
 }
@@ -110,7 +119,8 @@ pub fn spawn_enemies(
 #[write_component(CTransform)]
 pub fn spawn_guns(
     #[resource] gun_spawn_timer: &mut GunSpawnTimer,
-    #[resource] rng: &mut StdRng,
+    #[resource] rng: &mut SimulationRng,
+    #[resource] camera: &Camera,
     cmd: &mut CommandBuffer,
 ) {
     gun_spawn_timer.step();
@@ -122,8 +132,8 @@ pub fn spawn_guns(
 
     let size = 5;
     let padded_size = size as f32 * 2.0;
-    let position = get_padded_position_outside_play_area(rng, padded_size);
-    let target_position = get_random_pos_in_play_area(rng);
+    let position = camera.pos + get_padded_position_outside_play_area(rng, padded_size);
+    let target_position = camera.pos + get_random_pos_in_play_area(rng);
     let direction = (target_position - position).normalize();
     let velocity = direction * rng.gen_range(0.5..1.0);
 
@@ -144,6 +154,7 @@ pub fn spawn_guns(
             fire_delay: rng.gen_range(28..32),
             cooldown: 0,
         },
+        gun_collider(),
         // AttachedTo {
         //     entity: player,
         //     offset: random_offset,
@@ -1,20 +1,21 @@
 use glam::Vec2;
 use legion::world::SubWorld;
 pub use legion::*;
-use raylib::prelude::Color;
 
 use crate::{
-    components::{CTransform, Player},
-    rendering::{DrawCommand, RenderCommandBuffer},
+    components::{Player, PlayerSlot},
+    rendering::{Color, DrawCommand, RenderCommandBuffer},
+    state::RunSteps,
     DIMS,
 };
 
+use super::input_handling::all_players_out;
+
 #[system]
-#[read_component(CTransform)]
 #[read_component(Player)]
+#[read_component(PlayerSlot)]
 pub fn game_over(ecs: &mut SubWorld, #[resource] render_command_buffer: &mut RenderCommandBuffer) {
-    let mut players = <(Entity, &CTransform)>::query().filter(component::<Player>());
-    if players.iter(ecs).count() == 0 {
+    if all_players_out(ecs) {
         let mut cursor = Vec2::new(DIMS.x as f32 * 0.28, DIMS.y as f32 * 0.4);
         let title = "GAME OVER!";
         let size = 20;
@@ -37,3 +38,12 @@ pub fn game_over(ecs: &mut SubWorld, #[resource] render_command_buffer: &mut Ren
         });
     }
 }
+
+#[system]
+#[read_component(Player)]
+#[read_component(PlayerSlot)]
+pub fn count_run_steps(ecs: &mut SubWorld, #[resource] run_steps: &mut RunSteps) {
+    if !all_players_out(ecs) {
+        run_steps.0 += 1;
+    }
+}
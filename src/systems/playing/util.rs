@@ -1,8 +1,8 @@
-use crate::{components::LifeSpan, message_stream::ExpiringMessages, DIMS};
+use crate::{components::LifeSpan, message_stream::ExpiringMessages, rng::SimulationRng, DIMS};
 use glam::Vec2;
 pub use legion::*;
-use legion::{system, systems::CommandBuffer, world::SubWorld, Entity};
-use rand::{rngs::StdRng, Rng};
+use legion::{systems::CommandBuffer, world::SubWorld};
+use rand::Rng;
 
 #[system]
 #[write_component(LifeSpan)]
@@ -24,19 +24,19 @@ pub fn step_alerts(#[resource] expiring_messages: &mut ExpiringMessages) {
     expiring_messages.retain(|message| message.lifetime > 0);
 }
 
-pub fn get_random_pos_in_play_area(rng: &mut StdRng) -> Vec2 {
+pub fn get_random_pos_in_play_area(rng: &mut SimulationRng) -> Vec2 {
     Vec2::new(
         rng.gen_range(0.0..DIMS.x as f32),
         rng.gen_range(0.0..DIMS.y as f32),
     )
 }
 
-pub fn get_position_outside_play_area(rng: &mut StdRng) -> Vec2 {
+pub fn get_position_outside_play_area(rng: &mut SimulationRng) -> Vec2 {
     get_padded_position_outside_play_area(rng, 0.0)
 }
 
 /** gives a random position outside the viewable area, also can account for padded sizes */
-pub fn get_padded_position_outside_play_area(rng: &mut StdRng, padded_size: f32) -> Vec2 {
+pub fn get_padded_position_outside_play_area(rng: &mut SimulationRng, padded_size: f32) -> Vec2 {
     // position needs to be outside of the screen
     // there are 8 zones, first pick a zone
     let zone = rng.gen_range(0..8);
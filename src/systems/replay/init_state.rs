@@ -0,0 +1,22 @@
+use crate::{
+    replay::{ReplayPlayback, ReplayRecorder},
+    state::State,
+    systems::playing,
+};
+
+// expects a ReplayPlayback resource, rewinds it and restarts the world from its run config
+pub fn init(state: &mut State) {
+    let run_config = match state.resources.get_mut::<ReplayPlayback>() {
+        Some(mut playback) => {
+            playback.cursor = 0;
+            playback.replay.run_config
+        }
+        None => {
+            println!("No replay loaded");
+            return;
+        }
+    };
+    println!("Replaying seed: {}", run_config.seed);
+    playing::init_state::init_with_config(state, run_config);
+    state.resources.remove::<ReplayRecorder>();
+}
@@ -2,6 +2,7 @@ pub use legion::*;
 
 use crate::{
     components::{CTransform, Gun, Physics, Player},
+    modes::Transition,
     state::GameMode,
 };
 
@@ -14,10 +15,10 @@ use crate::title::TitleInputs;
 #[read_component(Player)]
 pub fn handle_inputs(
     #[resource] title_inputs: &TitleInputs,
-    #[resource] transition_to: &mut Option<GameMode>,
+    #[resource] transition: &mut Option<Transition>,
 ) {
     if title_inputs.confirm {
         println!("Confirm!");
-        *transition_to = Some(GameMode::Playing);
+        *transition = Some(Transition::Replace(GameMode::Playing));
     }
 }
@@ -1,3 +1,4 @@
+#[derive(Clone, Copy)]
 pub struct Timer {
     pub interval: u32, // frames
     pub countdown: u32,
@@ -26,6 +27,9 @@ impl<T> TypedTimer<T> {
     pub fn get_countdown(&self) -> u32 {
         self.timer.countdown
     }
+    pub fn get_interval(&self) -> u32 {
+        self.timer.interval
+    }
     pub fn reset(&mut self) {
         self.timer.countdown = self.timer.interval;
     }
@@ -36,6 +40,16 @@ impl<T> TypedTimer<T> {
         }
     }
 }
+// derived Clone would need the marker types to be Clone too
+impl<T> Clone for TypedTimer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            timer: self.timer,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
 pub struct ForAsteroidSpawning;
 pub struct ForGunSpawning;
 pub struct ForEnemySpawning;
@@ -1,55 +1,110 @@
-use glam::Vec2;
 pub use legion::*;
-use raylib::prelude::*;
 
 use crate::{
+    modes::{self, Mode, ModeRegistry, Transition},
     state::{GameMode, State},
-    DIMS,
 };
 
 pub struct TitleInputs {
     pub confirm: bool,
 }
 
-pub fn process_events_and_input(rl: &mut RaylibHandle, state: &mut State) {
-    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_ESCAPE) {
-        state.running = false;
-    }
-    let mut title_inputs = TitleInputs { confirm: false };
-    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_SPACE) {
-        title_inputs.confirm = true;
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TitleMenuItem {
+    Start,
+    Settings,
+    HighScores,
+    Quit,
+}
+
+pub const TITLE_MENU_ITEMS: [TitleMenuItem; 4] = [
+    TitleMenuItem::Start,
+    TitleMenuItem::Settings,
+    TitleMenuItem::HighScores,
+    TitleMenuItem::Quit,
+];
 
-        if let Some(mut transition_to) = state.resources.get_mut::<Option<GameMode>>() {
-            *transition_to = Option::Some(GameMode::Playing);
+impl TitleMenuItem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TitleMenuItem::Start => "start",
+            TitleMenuItem::Settings => "settings",
+            TitleMenuItem::HighScores => "high scores",
+            TitleMenuItem::Quit => "quit",
         }
     }
+}
 
-    state.resources.insert(title_inputs);
+// resource, only meaningful on the title screen
+#[derive(Default)]
+pub struct TitleMenu {
+    pub selected: usize,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct TitleMenuInputs {
+    pub up: bool,
+    pub down: bool,
+    pub confirm: bool,
+    pub back: bool,
 }
 
-pub fn step(_rl: &mut RaylibHandle, state: &mut State) {
+pub fn step(state: &mut State) {
     state
         .title_schedule
         .execute(&mut state.ecs, &mut state.resources);
 }
 
-pub fn draw(_state: &State, d: &mut RaylibTextureMode<RaylibDrawHandle>) {
-    // draw the title screen
-    // name is ecsstroids
-
-    let mut cursor = Vec2::new(DIMS.x as f32 * 0.28, DIMS.y as f32 * 0.4);
-    let title = "ECStroids!";
-    let size = 20;
-    d.draw_text(title, cursor.x as i32, cursor.y as i32, size, Color::WHITE);
-    cursor.y += size as f32 * 1.5;
-
-    let subtitle = "press space to start";
-    let size = 1;
-    d.draw_text(
-        subtitle,
-        cursor.x as i32,
-        cursor.y as i32,
-        size,
-        Color::WHITE,
+pub fn register(modes: &mut ModeRegistry) {
+    modes.insert(
+        GameMode::Title,
+        Mode {
+            step,
+            on_enter,
+            ..Default::default()
+        },
     );
 }
+
+fn on_enter(state: &mut State) {
+    state.resources.insert(TitleMenu::default());
+    crate::systems::title::init_state::init(state);
+}
+
+/** starting a run goes through TitleInputs so the title schedule sees it */
+pub fn handle_inputs(state: &mut State, inputs: TitleMenuInputs) {
+    if inputs.back {
+        state.running = false;
+        return;
+    }
+
+    let selected = match state.resources.get_mut::<TitleMenu>() {
+        Some(mut menu) => {
+            if inputs.up {
+                menu.selected =
+                    (menu.selected + TITLE_MENU_ITEMS.len() - 1) % TITLE_MENU_ITEMS.len();
+            }
+            if inputs.down {
+                menu.selected = (menu.selected + 1) % TITLE_MENU_ITEMS.len();
+            }
+            menu.selected
+        }
+        None => return,
+    };
+
+    let mut title_inputs = TitleInputs { confirm: false };
+    if inputs.confirm {
+        match TITLE_MENU_ITEMS[selected] {
+            TitleMenuItem::Start => {
+                title_inputs.confirm = true;
+                modes::request(state, Transition::Replace(GameMode::Playing));
+            }
+            TitleMenuItem::Settings => modes::request(state, Transition::Push(GameMode::Settings)),
+            TitleMenuItem::HighScores => {
+                modes::request(state, Transition::Push(GameMode::HighScores))
+            }
+            TitleMenuItem::Quit => state.running = false,
+        }
+    }
+    state.resources.insert(title_inputs);
+}
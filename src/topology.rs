@@ -0,0 +1,75 @@
+use glam::Vec2;
+
+use crate::{torus, DIMS};
+
+// what happens at the edges of the play field, fixed for the length of a run
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    // going off one edge comes back on the other
+    #[default]
+    Wrap,
+    // walls round the screen that everything bounces off
+    Arena,
+    // no edges, the view scrolls with the ships and anything left far behind is dropped
+    Open,
+}
+
+pub const TOPOLOGIES: [Topology; 3] = [Topology::Wrap, Topology::Arena, Topology::Open];
+
+impl Topology {
+    // the value used in settings files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topology::Wrap => "wrap",
+            Topology::Arena => "arena",
+            Topology::Open => "open",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Topology::Wrap => "wrap around",
+            Topology::Arena => "walled arena",
+            Topology::Open => "open field",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        TOPOLOGIES
+            .iter()
+            .copied()
+            .find(|topology| topology.name() == name)
+    }
+
+    pub fn wraps(&self) -> bool {
+        *self == Topology::Wrap
+    }
+
+    /** the shortest way from one point to another, across the edges when they wrap round */
+    pub fn offset(&self, from: Vec2, to: Vec2) -> Vec2 {
+        if self.wraps() {
+            torus::offset(from, to)
+        } else {
+            to - from
+        }
+    }
+}
+
+// resource, the top left corner of the part of the field on screen. it only moves on an open field
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Camera {
+    pub pos: Vec2,
+    // where it was at the start of the last step, to draw between steps like everything else
+    pub previous: Vec2,
+}
+
+impl Camera {
+    pub fn interpolated(&self, alpha: f32) -> Vec2 {
+        self.previous.lerp(self.pos, alpha)
+    }
+
+    /** the middle of the screen, in the field */
+    pub fn center(&self) -> Vec2 {
+        self.pos + DIMS.as_vec2() / 2.0
+    }
+}
@@ -0,0 +1,146 @@
+use glam::Vec2;
+
+use crate::DIMS;
+
+// the play field wraps round at its edges, so going off one side comes back on the other and
+// everything on it is also a whole field away in every direction
+
+/** the shortest way from one point to another, going across the edges when that is nearer */
+pub fn offset(from: Vec2, to: Vec2) -> Vec2 {
+    let size = DIMS.as_vec2();
+    let delta = to - from;
+    delta - size * (delta / size).round()
+}
+
+/** every copy of something one field away or less, where it is first. for things long enough that
+the nearest copy isn't the only one that can reach */
+pub fn images() -> [Vec2; 9] {
+    let size = DIMS.as_vec2();
+    let mut images = [Vec2::ZERO; 9];
+    let steps = [0.0, -1.0, 1.0];
+    for (i, image) in images.iter_mut().enumerate() {
+        *image = Vec2::new(steps[i % 3], steps[i / 3]) * size;
+    }
+    images
+}
+
+/** the copies of something in the box from min to max that show across the edges it hangs over,
+not counting itself */
+pub fn ghost_offsets(min: Vec2, max: Vec2) -> Vec<Vec2> {
+    let size = DIMS.as_vec2();
+    let across = |min: f32, max: f32, size: f32| {
+        let mut offsets = vec![0.0];
+        if min < 0.0 {
+            offsets.push(size);
+        }
+        if max > size {
+            offsets.push(-size);
+        }
+        offsets
+    };
+    let xs = across(min.x, max.x, size.x);
+    let ys = across(min.y, max.y, size.y);
+    ys.iter()
+        .flat_map(|y| xs.iter().map(move |x| Vec2::new(*x, *y)))
+        .filter(|offset| *offset != Vec2::ZERO)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_take_the_short_way_across_the_edges() {
+        assert_eq!(
+            offset(Vec2::new(10.0, 20.0), Vec2::new(30.0, 50.0)),
+            Vec2::new(20.0, 30.0)
+        );
+        assert_eq!(
+            offset(Vec2::new(235.0, 80.0), Vec2::new(5.0, 80.0)),
+            Vec2::new(10.0, 0.0)
+        );
+        assert_eq!(
+            offset(Vec2::new(5.0, 80.0), Vec2::new(235.0, 80.0)),
+            Vec2::new(-10.0, 0.0)
+        );
+        assert_eq!(
+            offset(Vec2::new(120.0, 2.0), Vec2::new(120.0, 157.0)),
+            Vec2::new(0.0, -5.0)
+        );
+        // across a corner, both ways at once
+        assert_eq!(
+            offset(Vec2::new(238.0, 158.0), Vec2::new(1.0, 2.0)),
+            Vec2::new(3.0, 4.0)
+        );
+        // from off the field, as something coming in is
+        assert_eq!(
+            offset(Vec2::new(-10.0, 80.0), Vec2::new(230.0, 80.0)),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn half_a_field_apart_is_as_far_either_way() {
+        let size = DIMS.as_vec2();
+        let from = Vec2::new(30.0, 40.0);
+        for to in [
+            from + Vec2::new(size.x / 2.0, 0.0),
+            from - Vec2::new(size.x / 2.0, 0.0),
+            from + Vec2::new(0.0, size.y / 2.0),
+            from + size / 2.0,
+        ] {
+            let there = offset(from, to);
+            let back = offset(to, from);
+            assert_eq!(there.abs(), back.abs(), "{:?}", to);
+            assert_eq!(there.abs(), (to - from).abs(), "{:?}", to);
+        }
+        // never further than half the field on either axis
+        for step in 0..=48 {
+            let to = Vec2::new(step as f32 * 5.0, step as f32 * 3.3);
+            let there = offset(Vec2::ZERO, to);
+            assert!(there.x.abs() <= size.x / 2.0 && there.y.abs() <= size.y / 2.0);
+        }
+    }
+
+    #[test]
+    fn images_are_every_neighbouring_field_once() {
+        let images = images();
+        assert_eq!(images[0], Vec2::ZERO);
+        for x in [-240.0, 0.0, 240.0] {
+            for y in [-160.0, 0.0, 160.0] {
+                let image = Vec2::new(x, y);
+                assert_eq!(
+                    images.iter().filter(|found| **found == image).count(),
+                    1,
+                    "{:?}",
+                    image
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ghosts_show_across_the_edges_hung_over() {
+        assert!(ghost_offsets(Vec2::new(10.0, 10.0), Vec2::new(20.0, 20.0)).is_empty());
+        assert_eq!(
+            ghost_offsets(Vec2::new(-5.0, 10.0), Vec2::new(5.0, 20.0)),
+            vec![Vec2::new(240.0, 0.0)]
+        );
+        assert_eq!(
+            ghost_offsets(Vec2::new(100.0, 150.0), Vec2::new(110.0, 170.0)),
+            vec![Vec2::new(0.0, -160.0)]
+        );
+        // over a corner there is one on each side and one across it
+        let mut corner = ghost_offsets(Vec2::new(235.0, -5.0), Vec2::new(245.0, 5.0));
+        corner.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        assert_eq!(
+            corner,
+            vec![
+                Vec2::new(-240.0, 0.0),
+                Vec2::new(-240.0, 160.0),
+                Vec2::new(0.0, 160.0)
+            ]
+        );
+    }
+}
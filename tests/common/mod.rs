@@ -0,0 +1,84 @@
+#![allow(dead_code)]
+
+use ecsteroids::{
+    components::{CTransform, Gun, InputControlled, Physics, Score},
+    modes::{self, Transition},
+    playing::{PlayerInputs, PlayingInputs, MAX_PLAYERS},
+    settings::Settings,
+    state::{GameMode, State},
+    topology::Topology,
+};
+use glam::Vec2;
+use legion::{query::component, IntoQuery};
+
+/** a state that has gone through the title into a fresh playing run with the seed */
+pub fn start_playing(seed: u64) -> State {
+    start_playing_on(seed, Topology::default())
+}
+
+/** the same, with the edges of the play field set up another way */
+pub fn start_playing_on(seed: u64, topology: Topology) -> State {
+    let mut state = State::new();
+    state.seed = Some(seed);
+    let mut settings = Settings::default();
+    settings.gameplay.topology = topology;
+    state.resources.insert(settings);
+    modes::request(&mut state, Transition::Reset(GameMode::Playing));
+    modes::apply_transition(&mut state);
+    assert_eq!(modes::current(&state), Some(GameMode::Playing));
+    state
+}
+
+/** sets every player's inputs for the next step */
+pub fn hold(state: &mut State, inputs: PlayingInputs) {
+    state.resources.insert(PlayerInputs([inputs; MAX_PLAYERS]));
+}
+
+pub fn shoot() -> PlayingInputs {
+    PlayingInputs {
+        shoot: true,
+        ..Default::default()
+    }
+}
+
+/** player one's ship stops where it is and stops turning */
+pub fn stop_ship(state: &mut State) {
+    for (physics, input_controlled) in
+        <(&mut Physics, &InputControlled)>::query().iter_mut(&mut state.ecs)
+    {
+        if input_controlled.player == 0 {
+            physics.vel = Vec2::ZERO;
+            physics.rot_vel = 0.0;
+        }
+    }
+}
+
+/** player one's ship stops at pos, the gun follows it there on the next step */
+pub fn park_ship(state: &mut State, pos: Vec2) {
+    for (transform, input_controlled) in <(&mut CTransform, &InputControlled)>::query()
+        .filter(component::<Physics>())
+        .iter_mut(&mut state.ecs)
+    {
+        if input_controlled.player == 0 {
+            transform.pos = pos;
+        }
+    }
+    stop_ship(state);
+}
+
+/** where player one's first gun is and the way it points */
+pub fn gun_transform(state: &State) -> CTransform {
+    <(&CTransform, &InputControlled)>::query()
+        .filter(component::<Gun>())
+        .iter(&state.ecs)
+        .find(|(_, input_controlled)| input_controlled.player == 0)
+        .map(|(transform, _)| *transform)
+        .expect("player one starts with a gun")
+}
+
+pub fn total_score(state: &State) -> u32 {
+    <&Score>::query()
+        .iter(&state.ecs)
+        .map(|score| score.score)
+        .sum()
+}
@@ -0,0 +1,82 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use ecsteroids::{
+    playing::{self, ControlScheme, PlayerInputs, PlayingInputs, MAX_PLAYERS},
+    snapshot::Snapshot,
+    state::{RunConfig, State},
+    systems::playing::init_state::init_with_config,
+    topology::TOPOLOGIES,
+};
+use glam::Vec2;
+
+const STEPS: u32 = 400;
+
+/** a fresh run, stepped through the same made up inputs every time */
+fn play(run_config: RunConfig) -> u64 {
+    let mut state = State::new();
+    init_with_config(&mut state, run_config);
+    for step in 0..STEPS {
+        let mut inputs = PlayerInputs::default();
+        for (player, player_inputs) in inputs.0.iter_mut().enumerate() {
+            let phase = step + player as u32 * 37;
+            *player_inputs = PlayingInputs {
+                turn: ((phase / 20) % 3) as f32 - 1.0,
+                thrust: if phase % 90 < 50 { 1.0 } else { -0.5 },
+                shoot: phase % 7 < 3,
+            };
+        }
+        state.resources.insert(inputs);
+        state
+            .resources
+            .insert(Vec2::new((step * 3 % 240) as f32, (step * 5 % 160) as f32));
+        playing::step(&mut state);
+    }
+    world_checksum(&state)
+}
+
+/** of everything a snapshot keeps, with entities numbered by where they are rather than their ids */
+fn world_checksum(state: &State) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    Snapshot::capture(state).to_bytes().hash(&mut hasher);
+    hasher.finish()
+}
+
+fn run_configs() -> Vec<RunConfig> {
+    TOPOLOGIES
+        .iter()
+        .map(|topology| RunConfig {
+            seed: 7,
+            control_scheme: ControlScheme::Classic,
+            players: 2,
+            friendly_fire: true,
+            topology: *topology,
+        })
+        .chain([RunConfig {
+            seed: 7,
+            control_scheme: ControlScheme::TwinStick,
+            players: MAX_PLAYERS,
+            ..Default::default()
+        }])
+        .collect()
+}
+
+#[test]
+fn same_seed_and_inputs_give_the_same_world() {
+    for run_config in run_configs() {
+        assert_eq!(play(run_config), play(run_config), "{:?}", run_config);
+    }
+}
+
+#[test]
+fn different_seeds_give_different_worlds() {
+    for run_config in run_configs() {
+        let reseeded = RunConfig {
+            seed: run_config.seed + 1,
+            ..run_config
+        };
+        assert_ne!(play(run_config), play(reseeded), "{:?}", run_config);
+    }
+}
@@ -0,0 +1,616 @@
+mod common;
+
+use ecsteroids::{
+    components::{
+        Asteroid, Attachable, AttachedTo, Bullet, CTransform, Collider, Enemy, ExtraLives, Gun,
+        InputControlled, Invulnerable, Physics, Pickup, Player, PlayerSlot, PowerupKind, Powerups,
+        Respawning, Score, WantsToGoTo,
+    },
+    events::{DeathEvent, DeathEvents},
+    modes,
+    playing::PlayingInputs,
+    state::{GameMode, State},
+    systems::playing::{
+        collision::{asteroid_collider, enemy_collider, gun_collider, ship_collider},
+        init_state::{PLAYER_SPAWNS, STARTING_LIVES},
+        lives::{EXTRA_LIFE_SCORE, INVULNERABLE_STEPS, RESPAWN_STEPS},
+        physics::{DESPAWN_RADIUS, WALL_RESTITUTION},
+        powerups::{pickup_collider, MAX_POWERUP_LEVEL},
+    },
+    topology::{Camera, Topology},
+    DIMS,
+};
+use glam::Vec2;
+use legion::{query::component, Entity, IntoQuery};
+
+use common::{
+    gun_transform, hold, park_ship, shoot, start_playing, start_playing_on, stop_ship, total_score,
+};
+
+#[test]
+fn shooting_an_asteroid_destroys_it_and_scores() {
+    let mut state = start_playing(1);
+    // one neutral step so the ship stops spinning the way it starts
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+    stop_ship(&mut state);
+    assert_eq!(total_score(&state), 0);
+
+    let gun = gun_transform(&state);
+    let asteroid = push_still_asteroid(&mut state, gun.pos + gun.rot * 30.0, 2);
+
+    hold(&mut state, shoot());
+    for _ in 0..30 {
+        modes::step(&mut state);
+        if state.ecs.entry(asteroid).is_none() {
+            break;
+        }
+    }
+
+    assert!(state.ecs.entry(asteroid).is_none());
+    assert_eq!(total_score(&state), 1);
+}
+
+#[test]
+fn fast_bullets_hit_the_first_thing_they_pass_between_steps() {
+    let mut state = start_playing(1);
+    // near the top, facing down the screen, so a whole step of the bullet's path is on the field
+    park_ship(&mut state, Vec2::new(120.0, 20.0));
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+    let gun = gun_transform(&state);
+    assert_eq!(gun.rot, Vec2::Y);
+
+    // both well clear of where the bullet starts and ends its first full step
+    let first = push_still_asteroid(&mut state, gun.pos + gun.rot * 40.0, 1);
+    let second = push_still_asteroid(&mut state, gun.pos + gun.rot * 70.0, 1);
+
+    hold(&mut state, shoot());
+    modes::step(&mut state);
+    hold(&mut state, PlayingInputs::default());
+    let bullet_start = bullets(&state);
+    assert_eq!(bullet_start.len(), 1);
+    assert!((bullet_start[0].pos - gun.pos).length() < 5.0);
+
+    modes::step(&mut state);
+    assert!(state.ecs.entry(first).is_none());
+    assert!(state.ecs.entry(second).is_some());
+    assert!(bullets(&state).is_empty());
+    assert_eq!(total_score(&state), 1);
+}
+
+#[test]
+fn bullets_keep_their_speed() {
+    let mut state = start_playing(1);
+    park_ship(&mut state, Vec2::new(20.0, 20.0));
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+    hold(&mut state, shoot());
+    modes::step(&mut state);
+    hold(&mut state, PlayingInputs::default());
+
+    let start = bullets(&state)[0].pos;
+    modes::step(&mut state);
+    let end = bullets(&state)[0].pos;
+    assert_eq!((end - start).length().round(), 100.0);
+}
+
+#[test]
+fn ships_grab_guns_across_the_edge() {
+    let mut state = start_playing(1);
+    park_ship(&mut state, Vec2::new(3.0, 80.0));
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+    let ship = ship(&state);
+
+    let gun = state.ecs.push((
+        CTransform {
+            pos: Vec2::new(236.0, 80.0),
+            rot: Vec2::Y,
+        },
+        Physics {
+            vel: Vec2::ZERO,
+            rot_vel: 0.0,
+        },
+        Gun {
+            wants_to_shoot: false,
+            fire_delay: 30,
+            cooldown: 0,
+        },
+        gun_collider(),
+        Attachable,
+    ));
+    modes::step(&mut state);
+
+    let attached = *state
+        .ecs
+        .entry(gun)
+        .unwrap()
+        .get_component::<AttachedTo>()
+        .expect("the gun was grabbed");
+    assert_eq!(attached.entity, ship);
+    // held from the near side, not a whole field away
+    assert!(attached.offset.length() < 15.0, "{:?}", attached.offset);
+}
+
+#[test]
+fn enemies_steer_the_short_way_across_the_edge() {
+    let mut state = start_playing(1);
+    park_ship(&mut state, Vec2::new(120.0, 20.0));
+    let enemy = state.ecs.push((
+        Enemy,
+        CTransform {
+            pos: Vec2::new(230.0, 80.0),
+            rot: Vec2::Y,
+        },
+        Physics {
+            vel: Vec2::ZERO,
+            rot_vel: 0.0,
+        },
+        enemy_collider(),
+        WantsToGoTo {
+            pos: Vec2::new(20.0, 80.0),
+        },
+    ));
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+
+    let entry = state.ecs.entry(enemy).unwrap();
+    let vel = entry.get_component::<Physics>().unwrap().vel;
+    assert!(vel.x > 0.0, "{:?}", vel);
+    assert_eq!(vel.y, 0.0);
+}
+
+#[test]
+fn asteroids_bounce_off_each_other_by_mass() {
+    let mut state = start_playing(1);
+    park_ship(&mut state, Vec2::new(20.0, 20.0));
+    let (heavy_size, light_size) = (8, 4);
+    let (heavy_mass, light_mass) = (
+        (heavy_size * heavy_size) as f32,
+        (light_size * light_size) as f32,
+    );
+    // just clear of each other, and closing in head on
+    let heavy = push_moving_asteroid(&mut state, Vec2::new(100.0, 80.0), Vec2::X, heavy_size);
+    let light = push_moving_asteroid(&mut state, Vec2::new(112.0, 80.0), -Vec2::X, light_size);
+    let momentum_before = Vec2::X * heavy_mass - Vec2::X * light_mass;
+    hold(&mut state, PlayingInputs::default());
+    for _ in 0..5 {
+        modes::step(&mut state);
+        if velocity(&state, heavy) != Vec2::X {
+            break;
+        }
+    }
+
+    let (heavy_vel, light_vel) = (velocity(&state, heavy), velocity(&state, light));
+    let momentum_after = heavy_vel * heavy_mass + light_vel * light_mass;
+    assert!(
+        (momentum_after - momentum_before).length() < 1e-3,
+        "{:?} then {:?}",
+        momentum_before,
+        momentum_after
+    );
+    // the heavy one carries on slower and the light one is knocked back the way it came
+    assert!(heavy_vel.x > 0.0 && heavy_vel.x < 1.0, "{:?}", heavy_vel);
+    assert!(light_vel.x > 1.0, "{:?}", light_vel);
+
+    // and they go their separate ways rather than sticking together
+    let mut distance = position(&state, light).distance(position(&state, heavy));
+    for _ in 0..10 {
+        modes::step(&mut state);
+        let next = position(&state, light).distance(position(&state, heavy));
+        assert!(next > distance, "{} then {}", distance, next);
+        distance = next;
+    }
+}
+
+#[test]
+fn wrapping_carries_things_across_the_field() {
+    let mut state = start_playing_on(1, Topology::Wrap);
+    park_ship(&mut state, Vec2::new(120.0, 80.0));
+    let right = push_drifting(&mut state, Vec2::new(239.0, 40.0), Vec2::new(2.0, 0.0));
+    let top = push_drifting(&mut state, Vec2::new(60.0, 1.0), Vec2::new(0.0, -2.0));
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+
+    assert_eq!(position(&state, right), Vec2::new(1.0, 40.0));
+    assert_eq!(position(&state, top), Vec2::new(60.0, 159.0));
+}
+
+#[test]
+fn arena_walls_bounce_things_back_slower() {
+    let mut state = start_playing_on(1, Topology::Arena);
+    park_ship(&mut state, Vec2::new(120.0, 80.0));
+    let size = 10;
+    let reach = asteroid_collider(size).shape.reach();
+    let heading_out = push_moving_asteroid(
+        &mut state,
+        Vec2::new(DIMS.x as f32 - reach - 1.0, 40.0),
+        Vec2::new(1.5, 0.0),
+        size,
+    );
+    let heading_in = push_moving_asteroid(
+        &mut state,
+        Vec2::new(reach - 1.0, 130.0),
+        Vec2::new(1.5, 0.0),
+        size,
+    );
+    // with no collider it bounces with its middle on the wall
+    let point = push_drifting(&mut state, Vec2::new(60.0, 1.0), Vec2::new(0.0, -2.0));
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+
+    assert_eq!(
+        velocity(&state, heading_out),
+        Vec2::new(-1.5 * WALL_RESTITUTION, 0.0)
+    );
+    assert_eq!(velocity(&state, heading_in), Vec2::new(1.5, 0.0));
+    assert_eq!(
+        velocity(&state, point),
+        Vec2::new(0.0, 2.0 * WALL_RESTITUTION)
+    );
+    assert_eq!(position(&state, point), Vec2::new(60.0, 0.0));
+}
+
+#[test]
+fn open_fields_drop_what_is_left_far_behind() {
+    let mut state = start_playing_on(1, Topology::Open);
+    let ship_pos = Vec2::new(120.0, 80.0);
+    park_ship(&mut state, ship_pos);
+    let near = push_drifting(
+        &mut state,
+        ship_pos + Vec2::new(DESPAWN_RADIUS - 10.0, 0.0),
+        Vec2::ZERO,
+    );
+    let far = push_drifting(
+        &mut state,
+        ship_pos - Vec2::new(0.0, DESPAWN_RADIUS + 10.0),
+        Vec2::ZERO,
+    );
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+
+    assert!(state.ecs.entry(near).is_some());
+    assert!(state.ecs.entry(far).is_none());
+}
+
+#[test]
+fn the_camera_only_follows_the_ships_on_an_open_field() {
+    let ship_pos = Vec2::new(300.0, -50.0);
+    for topology in [Topology::Wrap, Topology::Arena, Topology::Open] {
+        let mut state = start_playing_on(1, topology);
+        park_ship(&mut state, ship_pos);
+        hold(&mut state, PlayingInputs::default());
+        modes::step(&mut state);
+
+        let camera = *state.resources.get::<Camera>().unwrap();
+        if topology == Topology::Open {
+            assert_eq!(camera.pos, ship_pos - DIMS.as_vec2() / 2.0);
+            assert_eq!(camera.center(), ship_pos);
+        } else {
+            assert_eq!(camera.pos, Vec2::ZERO, "{:?}", topology);
+        }
+        // the ship is kept on the field everywhere else
+        let ship_now = position(&state, ship(&state));
+        match topology {
+            Topology::Wrap => assert_eq!(ship_now, Vec2::new(60.0, 110.0)),
+            Topology::Arena => assert_eq!(ship_now, Vec2::new(DIMS.x as f32, 0.0)),
+            Topology::Open => assert_eq!(ship_now, ship_pos),
+        }
+    }
+}
+
+#[test]
+fn losing_a_ship_costs_a_life_and_the_next_comes_after_a_delay() {
+    let mut state = start_playing(1);
+    hold(&mut state, PlayingInputs::default());
+    destroy_ship(&mut state);
+    // something sitting on the spawn point, so the next ship has to turn up somewhere else
+    push_still_asteroid(&mut state, PLAYER_SPAWNS[0], 30);
+    assert_eq!(player_slot(&state).lives, STARTING_LIVES - 1);
+    assert!(player_ship(&state).is_none());
+
+    let mut steps = 0;
+    while player_ship(&state).is_none() {
+        assert!(respawning(&state).is_some());
+        assert!(steps <= RESPAWN_STEPS + 1, "no ship after {} steps", steps);
+        modes::step(&mut state);
+        steps += 1;
+    }
+    assert!(steps >= RESPAWN_STEPS, "a ship after only {} steps", steps);
+    assert!(respawning(&state).is_none());
+
+    // clear of everything that could hit it, as far as their colliders reach
+    let ship_pos = position(&state, ship(&state));
+    let ship_reach = ship_collider().shape.reach();
+    for (transform, collider) in <(&CTransform, &Collider)>::query()
+        .filter(component::<Asteroid>() | component::<Enemy>())
+        .iter(&state.ecs)
+    {
+        let apart = (transform.pos - ship_pos).length();
+        assert!(apart > ship_reach + collider.shape.reach(), "{}", apart);
+    }
+    assert_ne!(ship_pos, PLAYER_SPAWNS[0]);
+}
+
+#[test]
+fn new_ships_stop_being_invulnerable() {
+    let mut state = start_playing(1);
+    hold(&mut state, PlayingInputs::default());
+    destroy_ship(&mut state);
+    while player_ship(&state).is_none() {
+        modes::step(&mut state);
+    }
+    let ship = ship(&state);
+    let steps_left = invulnerable(&state, ship).expect("a new ship can't be hit at first");
+    assert!(steps_left > INVULNERABLE_STEPS - 2, "{}", steps_left);
+
+    for _ in 0..steps_left {
+        assert!(invulnerable(&state, ship).is_some());
+        modes::step(&mut state);
+    }
+    assert_eq!(invulnerable(&state, ship), None);
+    assert_eq!(player_ship(&state), Some(ship));
+}
+
+#[test]
+fn scoring_earns_extra_lives() {
+    let mut state = start_playing(1);
+    hold(&mut state, PlayingInputs::default());
+    set_score(&mut state, EXTRA_LIFE_SCORE - 1);
+    modes::step(&mut state);
+    assert_eq!(player_slot(&state).lives, STARTING_LIVES);
+
+    set_score(&mut state, EXTRA_LIFE_SCORE * 2 + 1);
+    modes::step(&mut state);
+    assert_eq!(player_slot(&state).lives, STARTING_LIVES + 2);
+
+    // each is only given once
+    modes::step(&mut state);
+    assert_eq!(player_slot(&state).lives, STARTING_LIVES + 2);
+    let awarded = <&ExtraLives>::query()
+        .iter(&state.ecs)
+        .next()
+        .unwrap()
+        .awarded;
+    assert_eq!(awarded, 2);
+}
+
+#[test]
+fn the_run_is_over_once_the_last_life_is_gone() {
+    let mut state = start_playing(1);
+    hold(&mut state, PlayingInputs::default());
+    for slot in <&mut PlayerSlot>::query().iter_mut(&mut state.ecs) {
+        slot.lives = 1;
+    }
+    destroy_ship(&mut state);
+    assert_eq!(player_slot(&state).lives, 0);
+    assert!(respawning(&state).is_none());
+
+    // nobody comes back, and it waits on the last screen until shoot is pressed
+    for _ in 0..RESPAWN_STEPS + 2 {
+        modes::step(&mut state);
+        modes::apply_transition(&mut state);
+        assert!(player_ship(&state).is_none());
+        assert_eq!(modes::current(&state), Some(GameMode::Playing));
+    }
+    hold(&mut state, shoot());
+    modes::step(&mut state);
+    modes::apply_transition(&mut state);
+    assert_eq!(modes::current(&state), Some(GameMode::GameOver));
+}
+
+#[test]
+fn ships_pick_up_what_they_fly_over() {
+    let mut state = start_playing(1);
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+    let ship = ship(&state);
+    let pos = position(&state, ship);
+    let pickup = push_pickup(&mut state, pos, PowerupKind::LongerRange);
+    modes::step(&mut state);
+    assert!(state.ecs.entry(pickup).is_none());
+    assert_eq!(powerups(&state, ship).longer_range, 1);
+
+    // one far away stays where it is
+    let elsewhere = position(&state, ship) + Vec2::new(60.0, 0.0);
+    let pickup = push_pickup(&mut state, elsewhere, PowerupKind::Laser);
+    modes::step(&mut state);
+    assert!(state.ecs.entry(pickup).is_some());
+    assert!(!powerups(&state, ship).has_laser());
+}
+
+#[test]
+fn stacking_powerups_stop_at_the_cap() {
+    let mut state = start_playing(1);
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+    let ship = ship(&state);
+    for picked_up in 1..=MAX_POWERUP_LEVEL + 2 {
+        let pos = position(&state, ship);
+        push_pickup(&mut state, pos, PowerupKind::BiggerBullets);
+        modes::step(&mut state);
+        assert_eq!(
+            powerups(&state, ship).bigger_bullets,
+            picked_up.min(MAX_POWERUP_LEVEL)
+        );
+    }
+}
+
+#[test]
+fn streams_and_the_laser_run_out() {
+    let mut state = start_playing(1);
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+    let ship = ship(&state);
+    for kind in [PowerupKind::ThreeStreams, PowerupKind::Laser] {
+        let pos = position(&state, ship);
+        push_pickup(&mut state, pos, kind);
+        modes::step(&mut state);
+    }
+    let picked_up = powerups(&state, ship);
+    assert_eq!(picked_up.streams, 3);
+    assert!(picked_up.has_laser());
+
+    // a second laser tops its time up, more streams start theirs again
+    modes::step(&mut state);
+    let counted_down = powerups(&state, ship);
+    assert_eq!(
+        counted_down.streams_steps_left,
+        picked_up.streams_steps_left - 1
+    );
+    assert_eq!(
+        counted_down.laser_steps_left,
+        picked_up.laser_steps_left - 1
+    );
+    for kind in [PowerupKind::TwoStreams, PowerupKind::Laser] {
+        let pos = position(&state, ship);
+        push_pickup(&mut state, pos, kind);
+        modes::step(&mut state);
+    }
+    let topped_up = powerups(&state, ship);
+    assert_eq!(topped_up.streams, 3);
+    // started again, so one step later it has as long left as the first did
+    assert_eq!(topped_up.streams_steps_left, picked_up.streams_steps_left);
+    assert!(topped_up.laser_steps_left > picked_up.laser_steps_left);
+
+    // the rest of the way down, near the end so the ship isn't left out among the asteroids
+    for powerups in <&mut Powerups>::query().iter_mut(&mut state.ecs) {
+        powerups.streams_steps_left = 2;
+        powerups.laser_steps_left = 2;
+    }
+    modes::step(&mut state);
+    assert_eq!(powerups(&state, ship).streams, 3);
+    assert!(powerups(&state, ship).has_laser());
+    modes::step(&mut state);
+    assert_eq!(powerups(&state, ship).streams, 0);
+    assert!(!powerups(&state, ship).has_laser());
+    assert!(powerups(&state, ship).active().is_empty());
+}
+
+#[test]
+fn powerups_are_lost_with_the_ship() {
+    let mut state = start_playing(1);
+    hold(&mut state, PlayingInputs::default());
+    modes::step(&mut state);
+    for kind in [PowerupKind::FasterShooting, PowerupKind::TwoStreams] {
+        let pos = position(&state, ship(&state));
+        push_pickup(&mut state, pos, kind);
+        modes::step(&mut state);
+    }
+    assert_eq!(powerups(&state, ship(&state)).active().len(), 2);
+
+    destroy_ship(&mut state);
+    while player_ship(&state).is_none() {
+        modes::step(&mut state);
+    }
+    assert_eq!(powerups(&state, ship(&state)), Powerups::default());
+}
+
+fn player_ship(state: &State) -> Option<Entity> {
+    <(Entity, &InputControlled)>::query()
+        .filter(component::<Player>())
+        .iter(&state.ecs)
+        .find(|(_, input_controlled)| input_controlled.player == 0)
+        .map(|(entity, _)| *entity)
+}
+
+fn ship(state: &State) -> Entity {
+    player_ship(state).expect("player one has a ship")
+}
+
+/** player one's ship goes down as if something had hit it, and the step goes on from there */
+fn destroy_ship(state: &mut State) {
+    let entity = ship(state);
+    state
+        .resources
+        .get_mut::<DeathEvents>()
+        .unwrap()
+        .push(DeathEvent {
+            entity,
+            killer: None,
+        });
+    modes::step(state);
+}
+
+fn player_slot(state: &State) -> PlayerSlot {
+    *<&PlayerSlot>::query()
+        .iter(&state.ecs)
+        .find(|slot| slot.index == 0)
+        .unwrap()
+}
+
+fn respawning(state: &State) -> Option<Respawning> {
+    <&Respawning>::query().iter(&state.ecs).next().copied()
+}
+
+fn invulnerable(state: &State, ship: Entity) -> Option<u32> {
+    <&Invulnerable>::query()
+        .get(&state.ecs, ship)
+        .ok()
+        .map(|invulnerable| invulnerable.steps_left)
+}
+
+fn set_score(state: &mut State, score: u32) {
+    for player_score in <&mut Score>::query().iter_mut(&mut state.ecs) {
+        player_score.score = score;
+    }
+}
+
+fn push_pickup(state: &mut State, pos: Vec2, kind: PowerupKind) -> Entity {
+    state.ecs.push((
+        CTransform { pos, rot: Vec2::Y },
+        Pickup { kind },
+        pickup_collider(),
+    ))
+}
+
+fn powerups(state: &State, ship: Entity) -> Powerups {
+    *<&Powerups>::query().get(&state.ecs, ship).unwrap()
+}
+
+/** sitting still and too small to split, so one hit is the end of it */
+fn push_still_asteroid(state: &mut State, pos: Vec2, size: u32) -> Entity {
+    state.ecs.push((
+        CTransform { pos, rot: Vec2::Y },
+        Asteroid { size },
+        asteroid_collider(size),
+        Physics {
+            vel: Vec2::ZERO,
+            rot_vel: 0.0,
+        },
+    ))
+}
+
+fn push_moving_asteroid(state: &mut State, pos: Vec2, vel: Vec2, size: u32) -> Entity {
+    state.ecs.push((
+        CTransform { pos, rot: Vec2::Y },
+        Asteroid { size },
+        asteroid_collider(size),
+        Physics { vel, rot_vel: 0.0 },
+    ))
+}
+
+/** something with nothing to collide with, that only moves */
+fn push_drifting(state: &mut State, pos: Vec2, vel: Vec2) -> Entity {
+    state.ecs.push((
+        CTransform { pos, rot: Vec2::Y },
+        Physics { vel, rot_vel: 0.0 },
+    ))
+}
+
+fn position(state: &State, entity: Entity) -> Vec2 {
+    <&CTransform>::query().get(&state.ecs, entity).unwrap().pos
+}
+
+fn velocity(state: &State, entity: Entity) -> Vec2 {
+    <&Physics>::query().get(&state.ecs, entity).unwrap().vel
+}
+
+fn bullets(state: &State) -> Vec<CTransform> {
+    <&CTransform>::query()
+        .filter(component::<Bullet>())
+        .iter(&state.ecs)
+        .copied()
+        .collect()
+}